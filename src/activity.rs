@@ -0,0 +1,510 @@
+//! The sync activity history, recording everything the sync engine does to
+//! files along with the page used to browse it.
+use crate::{
+    entities::{
+        ActivityActiveModel, ActivityColumn, ActivityEntity, ActivityModel, RemotesEntity,
+        SyncDirsModel,
+    },
+    gtk_util, util, versions,
+};
+use adw::{
+    glib,
+    gtk::{
+        pango::EllipsizeMode, Align, Box, Button, DropDown, FileChooserAction, FileChooserDialog,
+        FileFilter, Label, ListBox, ListBoxRow, Orientation, ResponseType, ScrolledWindow,
+        SearchEntry, SelectionMode,
+    },
+    prelude::*,
+};
+use sea_orm::{
+    entity::prelude::*,
+    sea_query::{Expr, SimpleExpr},
+    ActiveValue, Condition, DatabaseConnection, IdenStatic, QueryOrder, QuerySelect,
+};
+use serde_json::json;
+use std::{cell::RefCell, collections::HashSet, fs, path::Path, rc::Rc};
+use time::OffsetDateTime;
+
+/// The kinds of sync activity.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ActivityKind {
+    /// A local item was copied to the remote.
+    Push,
+    /// A remote item was copied to the local machine.
+    Pull,
+    /// A local item was deleted, because it was deleted on the remote.
+    DeleteLocal,
+    /// A remote item was deleted, because it was deleted locally.
+    DeleteRemote,
+    /// A local item was moved, because it was moved on the remote.
+    MoveLocal,
+    /// A remote item was moved, because it was moved locally.
+    MoveRemote,
+    /// The user chose which side to keep after both were updated.
+    ConflictResolved,
+    /// An error was found while syncing.
+    Error,
+}
+
+impl ActivityKind {
+    /// All the kinds of activity, in the order they're shown in the UI.
+    const ALL: [Self; 8] = [
+        Self::Push,
+        Self::Pull,
+        Self::DeleteLocal,
+        Self::DeleteRemote,
+        Self::MoveLocal,
+        Self::MoveRemote,
+        Self::ConflictResolved,
+        Self::Error,
+    ];
+
+    /// Get the name of this kind, as stored in the database.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Push => "push",
+            Self::Pull => "pull",
+            Self::DeleteLocal => "delete_local",
+            Self::DeleteRemote => "delete_remote",
+            Self::MoveLocal => "move_local",
+            Self::MoveRemote => "move_remote",
+            Self::ConflictResolved => "conflict_resolved",
+            Self::Error => "error",
+        }
+    }
+
+    /// Get a label describing this kind, for use in the UI.
+    pub fn label(&self) -> String {
+        match self {
+            Self::Push => tr::tr!("Uploaded"),
+            Self::Pull => tr::tr!("Downloaded"),
+            Self::DeleteLocal => tr::tr!("Deleted locally"),
+            Self::DeleteRemote => tr::tr!("Deleted on remote"),
+            Self::MoveLocal => tr::tr!("Moved locally"),
+            Self::MoveRemote => tr::tr!("Moved on remote"),
+            Self::ConflictResolved => tr::tr!("Conflict resolved"),
+            Self::Error => tr::tr!("Error"),
+        }
+    }
+
+    /// Get a kind from its name in the database.
+    fn from_str(kind: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|item| item.as_str() == kind)
+    }
+}
+
+/// The most activity entries kept for each remote. Older entries get pruned at
+/// the end of each sync pass.
+const MAX_ENTRIES: u64 = 10_000;
+
+/// How long activity entries are kept for, in seconds (90 days).
+const MAX_AGE: i64 = 90 * 24 * 60 * 60;
+
+/// The most activity entries shown on the activity page at once, starting from
+/// the most recent.
+const SHOWN_ENTRIES: u64 = 1000;
+
+/// Record some sync activity for a sync directory.
+pub fn record(
+    db: &DatabaseConnection,
+    sync_dir: &SyncDirsModel,
+    kind: ActivityKind,
+    local_path: &str,
+    remote_path: &str,
+    size: Option<u64>,
+    message: &str,
+) {
+    util::await_future(
+        ActivityActiveModel {
+            remote_id: ActiveValue::Set(sync_dir.remote_id),
+            sync_dir_id: ActiveValue::Set(sync_dir.id),
+            kind: ActiveValue::Set(kind.as_str().to_owned()),
+            local_path: ActiveValue::Set(local_path.to_owned()),
+            remote_path: ActiveValue::Set(remote_path.to_owned()),
+            size: ActiveValue::Set(size.and_then(|size| size.try_into().ok())),
+            message: ActiveValue::Set(message.to_owned()),
+            timestamp: ActiveValue::Set(OffsetDateTime::now_utc().unix_timestamp()),
+            ..Default::default()
+        }
+        .insert(db),
+    )
+    .unwrap();
+}
+
+/// Delete activity entries that are older than [`MAX_AGE`], along with any of a
+/// remote's entries that go past [`MAX_ENTRIES`]. This gets called once at the
+/// end of each sync pass, instead of every time activity gets recorded.
+pub fn prune(db: &DatabaseConnection) {
+    util::await_future(async {
+        let cutoff = OffsetDateTime::now_utc().unix_timestamp() - MAX_AGE;
+        ActivityEntity::delete_many()
+            .filter(ActivityColumn::Timestamp.lt(cutoff))
+            .exec(db)
+            .await
+            .unwrap();
+
+        for remote in RemotesEntity::find().all(db).await.unwrap() {
+            // IDs only ever go up, so the newest entry past the limit and everything
+            // before it can go.
+            let newest_dropped = ActivityEntity::find()
+                .filter(ActivityColumn::RemoteId.eq(remote.id))
+                .order_by_desc(ActivityColumn::Id)
+                .offset(MAX_ENTRIES)
+                .one(db)
+                .await
+                .unwrap();
+
+            if let Some(newest_dropped) = newest_dropped {
+                ActivityEntity::delete_many()
+                    .filter(ActivityColumn::RemoteId.eq(remote.id))
+                    .filter(ActivityColumn::Id.lte(newest_dropped.id))
+                    .exec(db)
+                    .await
+                    .unwrap();
+            }
+        }
+    });
+}
+
+/// Get a database condition for `column` containing `text`, ignoring case. This
+/// is used over SeaORM's `contains`, as that becomes a `LIKE` pattern that
+/// treats '_' and '%' in the search as wildcards.
+fn contains<C: ColumnTrait>(column: C, text: &str) -> SimpleExpr {
+    Expr::cust_with_values(
+        &format!("instr(lower(\"{}\"), lower(?)) > 0", column.as_str()),
+        [text],
+    )
+}
+
+/// Find a remote's activity, newest first. Only activity of `kind` is returned
+/// if it's set, and only activity whose paths or message contain `search` if
+/// it isn't empty.
+fn find(remote_id: i32, kind: Option<ActivityKind>, search: &str) -> Select<ActivityEntity> {
+    let mut query = ActivityEntity::find().filter(ActivityColumn::RemoteId.eq(remote_id));
+
+    if let Some(kind) = kind {
+        query = query.filter(ActivityColumn::Kind.eq(kind.as_str()));
+    }
+    if !search.is_empty() {
+        query = query.filter(
+            Condition::any()
+                .add(contains(ActivityColumn::LocalPath, search))
+                .add(contains(ActivityColumn::RemotePath, search))
+                .add(contains(ActivityColumn::Message, search)),
+        );
+    }
+
+    query
+        .order_by_desc(ActivityColumn::Timestamp)
+        .order_by_desc(ActivityColumn::Id)
+}
+
+/// Get the kind of activity selected in the page's filter, or [`None`] if all
+/// activity is being shown.
+fn selected_kind(kind_filter: &DropDown) -> Option<ActivityKind> {
+    // The first item in the filter is for showing all activity.
+    match kind_filter.selected() {
+        0 => None,
+        index => ActivityKind::ALL.get(index as usize - 1).copied(),
+    }
+}
+
+/// Format a UNIX timestamp in the user's local time.
+fn fmt_timestamp(timestamp: i64) -> String {
+    glib::DateTime::from_unix_local(timestamp)
+        .and_then(|datetime| datetime.format("%c"))
+        .map(|datetime| datetime.to_string())
+        .unwrap_or_else(|_| timestamp.to_string())
+}
+
+/// Export activity as CSV.
+pub fn to_csv(entries: &[ActivityModel]) -> String {
+    let escape = |field: &str| {
+        if field.contains([',', '"', '\n']) {
+            format!("\"{}\"", field.replace('"', "\"\""))
+        } else {
+            field.to_owned()
+        }
+    };
+    let mut csv = "timestamp,kind,local_path,remote_path,size,message\n".to_owned();
+
+    for entry in entries {
+        let timestamp = OffsetDateTime::from_unix_timestamp(entry.timestamp)
+            .ok()
+            .and_then(|timestamp| {
+                timestamp
+                    .format(&time::format_description::well_known::Rfc3339)
+                    .ok()
+            })
+            .unwrap_or_else(|| entry.timestamp.to_string());
+        let size = entry.size.map(|size| size.to_string()).unwrap_or_default();
+        let fields = [
+            timestamp,
+            entry.kind.clone(),
+            entry.local_path.clone(),
+            entry.remote_path.clone(),
+            size,
+            entry.message.clone(),
+        ];
+
+        csv.push_str(
+            &fields
+                .iter()
+                .map(|field| escape(field))
+                .collect::<Vec<_>>()
+                .join(","),
+        );
+        csv.push('\n');
+    }
+
+    csv
+}
+
+/// Export activity as JSON.
+pub fn to_json(entries: &[ActivityModel]) -> String {
+    let entries: Vec<_> = entries
+        .iter()
+        .map(|entry| {
+            json!({
+                "timestamp": entry.timestamp,
+                "kind": entry.kind,
+                "local_path": entry.local_path,
+                "remote_path": entry.remote_path,
+                "size": entry.size,
+                "message": entry.message,
+            })
+        })
+        .collect();
+
+    serde_json::to_string_pretty(&entries).unwrap()
+}
+
+/// Generate the row for an activity entry. `has_versions` is whether the
+/// entry's local item has any previous versions that can be restored.
+fn gen_row(db: &DatabaseConnection, entry: &ActivityModel, has_versions: bool) -> ListBoxRow {
+    let kind_label = ActivityKind::from_str(&entry.kind)
+        .map(|kind| kind.label())
+        .unwrap_or_else(|| entry.kind.clone());
+    let sections = Box::builder()
+        .orientation(Orientation::Vertical)
+        .spacing(2)
+        .margin_top(6)
+        .margin_end(6)
+        .margin_bottom(6)
+        .margin_start(6)
+        .build();
+    let title = Label::builder()
+        .label(&format!(
+            "{kind_label}: {}",
+            util::fmt_home(&entry.local_path)
+        ))
+        .halign(Align::Start)
+        .ellipsize(EllipsizeMode::Start)
+        .build();
+
+    let mut details = vec![fmt_timestamp(entry.timestamp)];
+    if let Some(size) = entry.size {
+        details.push(glib::format_size(size as u64).to_string());
+    }
+    if !entry.message.is_empty() {
+        details.push(entry.message.clone());
+    }
+    let subtitle = Label::builder()
+        .label(&details.join(" · "))
+        .halign(Align::Start)
+        .ellipsize(EllipsizeMode::End)
+        .css_classes(vec!["caption".to_string(), "dim-label".to_string()])
+        .build();
+
+    sections.append(&title);
+    sections.append(&subtitle);
+
+    let row = Box::builder()
+        .orientation(Orientation::Horizontal)
+        .spacing(6)
+        .build();
+    sections.set_hexpand(true);
+    row.append(&sections);
+
+    if has_versions {
+        let versions_button = Button::builder()
+            .icon_name("document-revert-symbolic")
+            .has_tooltip(true)
+            .tooltip_text(&tr::tr!("Restore previous version"))
+            .valign(Align::Center)
+            .margin_end(6)
+            .css_classes(vec!["flat".to_string()])
+            .build();
+        let local_path = entry.local_path.clone();
+        versions_button.connect_clicked(glib::clone!(@strong db, @strong local_path => move |_| {
+            versions::dialog(&db, &local_path);
+        }));
+        row.append(&versions_button);
+    }
+
+    ListBoxRow::builder().child(&row).build()
+}
+
+/// Generate the activity page for a remote. `on_back` gets called when the
+/// page's back button is clicked. Returns the page, along with a closure to
+/// reload the activity shown in it.
+pub fn page<F: Fn() + 'static>(
+    db: &DatabaseConnection,
+    remote_id: i32,
+    on_back: F,
+) -> (Box, Rc<dyn Fn()>) {
+    let page = Box::builder()
+        .orientation(Orientation::Vertical)
+        .vexpand_set(true)
+        .vexpand(true)
+        .css_classes(vec!["background".to_string()])
+        .build();
+    let header = Box::builder()
+        .orientation(Orientation::Horizontal)
+        .margin_bottom(10)
+        .build();
+    let back_button = Button::builder()
+        .icon_name("go-previous-symbolic")
+        .halign(Align::Start)
+        .hexpand_set(true)
+        .hexpand(true)
+        .build();
+    back_button.connect_clicked(move |_| on_back());
+    let export_button = Button::builder()
+        .icon_name("document-save-symbolic")
+        .has_tooltip(true)
+        .tooltip_text(&tr::tr!("Export activity"))
+        .halign(Align::End)
+        .build();
+    header.append(&back_button);
+    header.append(&export_button);
+
+    // The search and filter options.
+    let filters = Box::builder()
+        .orientation(Orientation::Horizontal)
+        .spacing(10)
+        .margin_bottom(10)
+        .build();
+    let search = SearchEntry::builder()
+        .placeholder_text(&tr::tr!("Search activity"))
+        .hexpand_set(true)
+        .hexpand(true)
+        .build();
+    let mut kind_labels = vec![tr::tr!("All activity")];
+    kind_labels.extend(ActivityKind::ALL.iter().map(|kind| kind.label()));
+    let kind_filter = DropDown::from_strings(
+        &kind_labels
+            .iter()
+            .map(|label| label.as_str())
+            .collect::<Vec<_>>(),
+    );
+    filters.append(&search);
+    filters.append(&kind_filter);
+
+    let list = ListBox::builder()
+        .selection_mode(SelectionMode::None)
+        .css_classes(vec!["boxed-list".to_string()])
+        .valign(Align::Start)
+        .margin_top(5)
+        .margin_end(5)
+        .margin_bottom(5)
+        .margin_start(5)
+        .build();
+    list.set_placeholder(Some(
+        &Label::builder()
+            .label(&tr::tr!("No activity found."))
+            .margin_top(10)
+            .margin_bottom(10)
+            .css_classes(vec!["dim-label".to_string()])
+            .build(),
+    ));
+    let list_scrolled = ScrolledWindow::builder()
+        .child(&list)
+        .vexpand_set(true)
+        .vexpand(true)
+        .build();
+
+    page.append(&header);
+    page.append(&filters);
+    page.append(&list_scrolled);
+
+    // The local items that have previous versions stored.
+    let versioned_paths: Rc<RefCell<HashSet<String>>> = Rc::new(RefCell::new(HashSet::new()));
+
+    let populate = Rc::new(
+        glib::clone!(@strong db, @strong versioned_paths, @weak list, @weak search, @weak kind_filter => move || {
+            while let Some(child) = list.first_child() {
+                list.remove(&child);
+            }
+
+            let entries = util::await_future(
+                find(remote_id, selected_kind(&kind_filter), &search.text())
+                    .limit(SHOWN_ENTRIES)
+                    .all(&db),
+            )
+            .unwrap();
+
+            for entry in &entries {
+                let has_versions = versioned_paths.borrow().contains(&entry.local_path);
+                list.append(&gen_row(&db, entry, has_versions));
+            }
+        }),
+    );
+    search.connect_search_changed(glib::clone!(@strong populate => move |_| populate()));
+    kind_filter.connect_selected_notify(glib::clone!(@strong populate => move |_| populate()));
+
+    let reload: Rc<dyn Fn()> = Rc::new(
+        glib::clone!(@strong db, @strong versioned_paths, @strong populate => move || {
+            *versioned_paths.borrow_mut() = versions::paths_with_versions(&db);
+            populate();
+        }),
+    );
+
+    // Export all the activity matching the current search and filter (including
+    // any past what's shown), as JSON if the chosen file ends in '.json' and as
+    // CSV otherwise.
+    export_button.connect_clicked(glib::clone!(@strong db, @weak search, @weak kind_filter => move |_| {
+        let filter = FileFilter::new();
+        filter.add_suffix("csv");
+        filter.add_suffix("json");
+        filter.set_name(Some(&tr::tr!("CSV or JSON files")));
+        let dialog = FileChooserDialog::builder()
+            .title(&util::get_title!("Export Activity"))
+            .action(FileChooserAction::Save)
+            .filter(&filter)
+            .build();
+        dialog.set_current_name("activity.csv");
+        let cancel_button = Button::with_label(&tr::tr!("Cancel"));
+        let ok_button = Button::with_label(&tr::tr!("Export"));
+        dialog.add_action_widget(&cancel_button, ResponseType::Cancel);
+        dialog.add_action_widget(&ok_button, ResponseType::Ok);
+        cancel_button.connect_clicked(glib::clone!(@weak dialog => move |_| {
+            dialog.close();
+        }));
+        ok_button.connect_clicked(glib::clone!(@weak dialog, @strong db, @weak search, @weak kind_filter => move |_| {
+            let path = match dialog.file().and_then(|file| file.path()) {
+                Some(path) => path,
+                None => return,
+            };
+            let entries = util::await_future(
+                find(remote_id, selected_kind(&kind_filter), &search.text()).all(&db),
+            )
+            .unwrap();
+            let is_json = Path::new(&path).extension().is_some_and(|ext| ext == "json");
+            let content = if is_json {
+                to_json(&entries)
+            } else {
+                to_csv(&entries)
+            };
+
+            if let Err(err) = fs::write(&path, content) {
+                gtk_util::show_error(&tr::tr!("Unable to export activity"), Some(&err.to_string()));
+            }
+
+            dialog.close();
+        }));
+        dialog.show();
+    }));
+
+    (page, reload)
+}
@@ -0,0 +1,299 @@
+//! A browser for the folders on a remote, used when picking the remote folder
+//! to sync a directory with.
+use crate::{
+    gtk_util,
+    rclone::{self, RcloneListFilter, RcloneRemoteItem},
+    util,
+};
+use adw::{
+    glib,
+    gtk::{
+        pango::EllipsizeMode, Align, Box, Button, Entry, Image, Label, ListBox, Orientation,
+        PolicyType, ScrolledWindow, SelectionMode, Spinner,
+    },
+    prelude::*,
+    ActionRow, MessageDialog,
+};
+use std::{cell::RefCell, rc::Rc};
+
+/// The state of a remote browser.
+struct Browser {
+    /// The name of the remote being browsed.
+    remote_name: String,
+    /// The folder currently being shown, with no '/' at either end.
+    path: RefCell<String>,
+    breadcrumbs: Box,
+    list: ListBox,
+    spinner: Spinner,
+    summary: Label,
+    /// Called with the new folder whenever it changes.
+    on_navigate: std::boxed::Box<dyn Fn(&str)>,
+}
+
+/// Join a folder and the name of an item in it.
+fn join(path: &str, name: &str) -> String {
+    if path.is_empty() {
+        name.to_owned()
+    } else {
+        format!("{path}/{name}")
+    }
+}
+
+/// Generate the row for an item in the current folder.
+fn gen_row(item: &RcloneRemoteItem) -> ActionRow {
+    let row = ActionRow::builder()
+        .title(&glib::markup_escape_text(&item.name))
+        .build();
+
+    if item.is_dir {
+        row.add_prefix(&Image::from_icon_name("folder-symbolic"));
+        row.add_suffix(&Image::from_icon_name("go-next-symbolic"));
+        row.set_activatable(true);
+    } else {
+        // Files can't be synced on their own, so they're just shown for reference.
+        row.add_prefix(&Image::from_icon_name("text-x-generic-symbolic"));
+        if let Ok(size) = u64::try_from(item.size) {
+            row.set_subtitle(&glib::format_size(size));
+        }
+        row.add_css_class("dim-label");
+    }
+
+    row
+}
+
+/// Rebuild the breadcrumbs for the current folder.
+fn update_breadcrumbs(browser: &Rc<Browser>) {
+    while let Some(child) = browser.breadcrumbs.first_child() {
+        browser.breadcrumbs.remove(&child);
+    }
+
+    let path = browser.path.borrow().clone();
+    let mut crumbs = vec![("/".to_owned(), String::new())];
+    let mut crumb_path = String::new();
+    for component in path.split('/').filter(|component| !component.is_empty()) {
+        crumb_path = join(&crumb_path, component);
+        crumbs.push((component.to_owned(), crumb_path.clone()));
+    }
+
+    let last_index = crumbs.len() - 1;
+    for (index, (label, crumb_path)) in crumbs.into_iter().enumerate() {
+        if index != 0 {
+            browser.breadcrumbs.append(
+                &Label::builder()
+                    .label("›")
+                    .css_classes(vec!["dim-label".to_string()])
+                    .build(),
+            );
+        }
+
+        let button = Button::builder()
+            .child(
+                &Label::builder()
+                    .label(&label)
+                    .ellipsize(EllipsizeMode::Middle)
+                    .max_width_chars(20)
+                    .build(),
+            )
+            .css_classes(vec!["flat".to_string()])
+            .sensitive(index != last_index)
+            .build();
+        button.connect_clicked(glib::clone!(@strong browser => move |_| {
+            navigate(&browser, &crumb_path);
+        }));
+        browser.breadcrumbs.append(&button);
+    }
+}
+
+/// Show the contents of a folder in the browser.
+fn navigate(browser: &Rc<Browser>, path: &str) {
+    let path = util::strip_slashes(path);
+    *browser.path.borrow_mut() = path.clone();
+    update_breadcrumbs(browser);
+    (browser.on_navigate)(&path);
+
+    while let Some(child) = browser.list.first_child() {
+        browser.list.remove(&child);
+    }
+    browser.summary.set_label("");
+    browser.spinner.set_visible(true);
+    browser.spinner.start();
+
+    // Listing runs in the background, so the rest of the UI stays usable while
+    // it happens.
+    let result = rclone::sync::list(&browser.remote_name, &path, false, RcloneListFilter::All);
+
+    browser.spinner.stop();
+    browser.spinner.set_visible(false);
+
+    // The user may have navigated somewhere else while the listing was running.
+    if *browser.path.borrow() != path {
+        return;
+    }
+
+    let mut items = match result {
+        Ok(items) => items,
+        Err(err) => {
+            browser
+                .summary
+                .set_label(&tr::tr!("Unable to list this folder [{}].", err.error));
+            return;
+        }
+    };
+    // Show folders first, and then everything else alphabetically.
+    items.sort_by(|a, b| {
+        b.is_dir
+            .cmp(&a.is_dir)
+            .then_with(|| a.name.to_lowercase().cmp(&b.name.to_lowercase()))
+    });
+
+    let num_dirs = items.iter().filter(|item| item.is_dir).count();
+    let num_files = items.len() - num_dirs;
+    let total_size: u64 = items
+        .iter()
+        .filter_map(|item| u64::try_from(item.size).ok())
+        .sum();
+    browser.summary.set_label(&tr::tr!(
+        "{} folders, {} files ({})",
+        num_dirs,
+        num_files,
+        glib::format_size(total_size)
+    ));
+
+    for item in &items {
+        let row = gen_row(item);
+        if item.is_dir {
+            row.set_widget_name(&join(&path, &item.name));
+        }
+        browser.list.append(&row);
+    }
+}
+
+/// Show a dialog for creating a new folder in the browser's current folder.
+fn new_folder(browser: &Rc<Browser>) {
+    let entry = Entry::builder()
+        .placeholder_text(&tr::tr!("Folder name"))
+        .activates_default(true)
+        .build();
+    let dialog = MessageDialog::builder()
+        .heading(&tr::tr!("New Folder"))
+        .extra_child(&entry)
+        .modal(true)
+        .build();
+    dialog.add_response("cancel", &tr::tr!("Cancel"));
+    dialog.add_response("create", &tr::tr!("Create"));
+    dialog.set_default_response(Some("create"));
+    dialog.set_response_enabled("create", false);
+    entry.connect_changed(glib::clone!(@weak dialog => move |entry| {
+        let name = entry.text();
+        dialog.set_response_enabled("create", !name.is_empty() && !name.contains('/'));
+    }));
+    dialog.connect_response(
+        None,
+        glib::clone!(@strong browser, @weak entry => move |dialog, resp| {
+            dialog.close();
+
+            if resp != "create" {
+                return;
+            }
+
+            let current_path = browser.path.borrow().clone();
+            let folder_path = join(&current_path, entry.text().as_str());
+            if let Err(err) = rclone::sync::mkdir(&browser.remote_name, &folder_path) {
+                gtk_util::show_error(&tr::tr!("Unable to create folder '/{}'", folder_path), Some(&err.error));
+                return;
+            }
+
+            navigate(&browser, &folder_path);
+        }),
+    );
+    dialog.show();
+}
+
+/// Generate a browser for the folders on a remote. `on_navigate` gets called
+/// with the current folder whenever it changes. Returns the browser, along
+/// with a closure to show a given folder in it.
+pub fn remote_browser<F: Fn(&str) + 'static>(
+    remote_name: &str,
+    on_navigate: F,
+) -> (Box, Rc<dyn Fn(&str)>) {
+    let container = Box::builder()
+        .orientation(Orientation::Vertical)
+        .spacing(5)
+        .build();
+    let header = Box::builder()
+        .orientation(Orientation::Horizontal)
+        .spacing(5)
+        .build();
+    let breadcrumbs = Box::builder()
+        .orientation(Orientation::Horizontal)
+        .valign(Align::Center)
+        .build();
+    let breadcrumbs_scrolled = ScrolledWindow::builder()
+        .child(&breadcrumbs)
+        .vscrollbar_policy(PolicyType::Never)
+        .hexpand_set(true)
+        .hexpand(true)
+        .build();
+    let spinner = Spinner::builder().visible(false).build();
+    let new_folder_button = Button::builder()
+        .icon_name("folder-new-symbolic")
+        .has_tooltip(true)
+        .tooltip_text(&tr::tr!("New folder"))
+        .build();
+    header.append(&breadcrumbs_scrolled);
+    header.append(&spinner);
+    header.append(&new_folder_button);
+
+    let list = ListBox::builder()
+        .selection_mode(SelectionMode::None)
+        .css_classes(vec!["boxed-list".to_string()])
+        .valign(Align::Start)
+        .margin_top(5)
+        .margin_end(5)
+        .margin_bottom(5)
+        .margin_start(5)
+        .build();
+    list.set_placeholder(Some(
+        &Label::builder()
+            .label(&tr::tr!("This folder is empty."))
+            .margin_top(10)
+            .margin_bottom(10)
+            .css_classes(vec!["dim-label".to_string()])
+            .build(),
+    ));
+    let list_scrolled = ScrolledWindow::builder()
+        .child(&list)
+        .min_content_height(250)
+        .vexpand_set(true)
+        .vexpand(true)
+        .build();
+    let summary = Label::builder()
+        .halign(Align::Start)
+        .css_classes(vec!["caption".to_string(), "dim-label".to_string()])
+        .build();
+
+    container.append(&header);
+    container.append(&list_scrolled);
+    container.append(&summary);
+
+    let browser = Rc::new(Browser {
+        remote_name: remote_name.to_owned(),
+        path: RefCell::new(String::new()),
+        breadcrumbs,
+        list: list.clone(),
+        spinner,
+        summary,
+        on_navigate: std::boxed::Box::new(on_navigate),
+    });
+
+    // Only folder rows are activatable, and their names hold their paths.
+    list.connect_row_activated(glib::clone!(@strong browser => move |_, row| {
+        navigate(&browser, &row.widget_name());
+    }));
+    new_folder_button.connect_clicked(glib::clone!(@strong browser => move |_| {
+        new_folder(&browser);
+    }));
+
+    let navigate_to: Rc<dyn Fn(&str)> = Rc::new(move |path: &str| navigate(&browser, path));
+    (container, navigate_to)
+}
@@ -0,0 +1,701 @@
+//! The command-line interface, for managing Celeste without going through the
+//! GUI.
+use crate::{
+    entities::{
+        RemotesActiveModel, RemotesColumn, RemotesEntity, RemotesModel, SyncDirsActiveModel,
+        SyncDirsColumn, SyncDirsEntity, SyncDirsModel,
+    },
+    exclude,
+    launch::FILE_IGNORE_NAME,
+    rclone,
+    status::{RemoteConnection, Status},
+    util,
+};
+use adw::{
+    gio::{self, prelude::*},
+    glib::{ToVariant, Variant},
+};
+use clap::Subcommand;
+use file_lock::{FileLock, FileOptions};
+use indexmap::IndexMap;
+use nix::sys::termios::{self, LocalFlags, SetArg};
+use regex::Regex;
+use sea_orm::{entity::prelude::*, ActiveValue, DatabaseConnection};
+use serde_json::json;
+use std::{
+    collections::HashMap,
+    env, fs,
+    io::{self, Write},
+    os::unix::io::AsRawFd,
+    path::Path,
+};
+
+#[derive(Subcommand)]
+pub enum Commands {
+    /// Manage remotes.
+    #[command(subcommand)]
+    Remote(RemoteCommands),
+    /// Manage sync directories.
+    #[command(subcommand)]
+    Dir(DirCommands),
+    /// Show the sync status of each directory.
+    Status {
+        /// Print the status as JSON, for use in scripts and status bars.
+        #[arg(long)]
+        json: bool,
+    },
+    /// Show any errors found while syncing.
+    Errors,
+    /// Sync every directory now, instead of waiting for changes to be found.
+    SyncNow,
+    /// Manage the exclusion rules for a sync directory.
+    #[command(subcommand)]
+    Ignore(IgnoreCommands),
+}
+
+#[derive(Subcommand)]
+pub enum RemoteCommands {
+    /// List all remotes.
+    List,
+    /// Add a new remote.
+    Add {
+        /// The name of the remote.
+        name: String,
+        /// The Rclone type of the remote (i.e. 'dropbox' or 'webdav').
+        #[arg(value_name = "TYPE")]
+        remote_type: String,
+        /// Rclone config parameters for the remote, as 'KEY=VALUE' pairs.
+        /// Passwords get obscured automatically.
+        #[arg(value_name = "KEY=VALUE", value_parser = parse_param)]
+        params: Vec<(String, String)>,
+    },
+    /// Remove a remote, along with all of its sync directories.
+    Remove {
+        /// The name of the remote.
+        name: String,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum DirCommands {
+    /// List all sync directories.
+    List {
+        /// Only list the directories for this remote.
+        #[arg(long)]
+        remote: Option<String>,
+    },
+    /// Add a new sync directory.
+    Add {
+        /// The name of the remote to sync with.
+        remote: String,
+        /// The local directory to sync.
+        local_path: String,
+        /// The directory on the remote to sync.
+        remote_path: String,
+        /// Encrypt files on the remote. The password to encrypt files with is
+        /// taken from the CELESTE_ENCRYPTION_PASSWORD environment variable if
+        /// it's set, and asked for otherwise.
+        #[arg(long)]
+        encrypt: bool,
+    },
+    /// Remove a sync directory.
+    Remove {
+        /// The local directory being synced.
+        local_path: String,
+        /// The directory on the remote being synced.
+        remote_path: String,
+    },
+    /// Pause syncing of a directory.
+    Pause {
+        /// The local directory being synced.
+        local_path: String,
+        /// The directory on the remote being synced.
+        remote_path: String,
+    },
+    /// Resume syncing of a paused directory.
+    Resume {
+        /// The local directory being synced.
+        local_path: String,
+        /// The directory on the remote being synced.
+        remote_path: String,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum IgnoreCommands {
+    /// Add an exclusion rule.
+    Add {
+        /// The local directory being synced.
+        local_path: String,
+        /// The glob pattern of files to exclude.
+        pattern: String,
+    },
+    /// List the exclusion rules.
+    List {
+        /// The local directory being synced.
+        local_path: String,
+    },
+}
+
+/// The environment variable `dir add --encrypt` reads the encryption password
+/// from.
+const PASSWORD_ENV: &str = "CELESTE_ENCRYPTION_PASSWORD";
+
+/// Ask for the password to encrypt a directory with. The password isn't shown
+/// while it's typed, and has to be typed twice when coming from a terminal.
+fn read_password() -> Result<String, String> {
+    let stdin = io::stdin();
+    let fd = stdin.as_raw_fd();
+
+    // If standard input isn't a terminal, the password is being piped in.
+    let Ok(old_termios) = termios::tcgetattr(fd) else {
+        let mut password = String::new();
+        stdin
+            .read_line(&mut password)
+            .map_err(|err| tr::tr!("Unable to read the password [{}].", err))?;
+        return Ok(password.trim_end_matches(['\r', '\n']).to_owned());
+    };
+
+    let mut termios = old_termios.clone();
+    termios.local_flags.remove(LocalFlags::ECHO);
+    termios.local_flags.insert(LocalFlags::ECHONL);
+    termios::tcsetattr(fd, SetArg::TCSANOW, &termios)
+        .map_err(|err| tr::tr!("Unable to read the password [{}].", err))?;
+
+    let prompt = |text: String| {
+        eprint!("{text}");
+        let mut password = String::new();
+        stdin
+            .read_line(&mut password)
+            .map(|_| password.trim_end_matches(['\r', '\n']).to_owned())
+    };
+    let passwords = prompt(tr::tr!("Encryption password: ")).and_then(|password| {
+        prompt(tr::tr!("Confirm encryption password: "))
+            .map(|confirmation| (password, confirmation))
+    });
+    let _ = termios::tcsetattr(fd, SetArg::TCSANOW, &old_termios);
+
+    match passwords {
+        Ok((password, confirmation)) if password == confirmation => Ok(password),
+        Ok(_) => Err(tr::tr!("The passwords don't match")),
+        Err(err) => Err(tr::tr!("Unable to read the password [{}].", err)),
+    }
+}
+
+/// Parse a 'KEY=VALUE' parameter.
+fn parse_param(param: &str) -> Result<(String, String), String> {
+    match param.split_once('=') {
+        Some((key, value)) => Ok((key.to_owned(), value.to_owned())),
+        None => Err(tr::tr!("Parameters must be in the form of 'KEY=VALUE'.")),
+    }
+}
+
+/// Get the running instance of Celeste, if there is one.
+fn running_app() -> Option<gio::Application> {
+    let app = gio::Application::new(Some(util::APP_ID), gio::ApplicationFlags::empty());
+    app.register(None::<&gio::Cancellable>).ok()?;
+    app.is_remote().then_some(app)
+}
+
+/// Activate an action on the running instance. Returns [`false`] if Celeste
+/// isn't running.
+fn activate_action(action: &str, param: Option<&Variant>) -> bool {
+    let app = match running_app() {
+        Some(app) => app,
+        None => return false,
+    };
+
+    app.activate_action(action, param);
+
+    // Make sure the action gets sent out before we exit.
+    if let Some(conn) = app.dbus_connection() {
+        let _ = conn.flush_sync(None::<&gio::Cancellable>);
+    }
+
+    true
+}
+
+/// Format a local path as an absolute path, with a '/' at the beginning but not
+/// the end.
+fn fmt_local_path(path: &str) -> String {
+    let path = Path::new(path);
+    let path = if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        env::current_dir().unwrap().join(path)
+    };
+
+    "/".to_string() + &util::strip_slashes(path.to_str().unwrap())
+}
+
+/// Find a remote in the database.
+fn find_remote(db: &DatabaseConnection, name: &str) -> Result<RemotesModel, String> {
+    util::await_future(
+        RemotesEntity::find()
+            .filter(RemotesColumn::Name.eq(name))
+            .one(db),
+    )
+    .unwrap()
+    .ok_or_else(|| tr::tr!("The remote '{}' doesn't exist.", name))
+}
+
+/// Find a sync directory in the database.
+fn find_dir(
+    db: &DatabaseConnection,
+    local_path: &str,
+    remote_path: &str,
+) -> Result<SyncDirsModel, String> {
+    let local_path = fmt_local_path(local_path);
+    let remote_path = util::strip_slashes(remote_path);
+
+    util::await_future(
+        SyncDirsEntity::find()
+            .filter(SyncDirsColumn::LocalPath.eq(local_path.clone()))
+            .filter(SyncDirsColumn::RemotePath.eq(remote_path.clone()))
+            .one(db),
+    )
+    .unwrap()
+    .ok_or_else(|| {
+        tr::tr!(
+            "The directory pair '{}' and '/{}' isn't being synced.",
+            local_path,
+            remote_path
+        )
+    })
+}
+
+/// Run a CLI command.
+pub fn run(command: Commands) -> Result<(), String> {
+    let db = util::open_db()?;
+
+    match command {
+        Commands::Remote(command) => run_remote(&db, command),
+        Commands::Dir(command) => run_dir(&db, command),
+        Commands::Status { json } => {
+            let status = get_status()?;
+
+            if json {
+                println!("{}", serde_json::to_string_pretty(&status).unwrap());
+                return Ok(());
+            }
+
+            for remote in status.remotes {
+                match remote.connection {
+                    RemoteConnection::Connected => println!("{}:", remote.name),
+                    RemoteConnection::NeedsSignIn => {
+                        println!("{} ({}):", remote.name, tr::tr!("sign-in required"))
+                    }
+                    RemoteConnection::Disconnected => {
+                        println!("{} ({}):", remote.name, tr::tr!("offline"))
+                    }
+                }
+
+                for dir in remote.dirs {
+                    println!(
+                        "  {} -> /{}: {}",
+                        util::fmt_home(&dir.local_path),
+                        dir.remote_path,
+                        dir.status.trim()
+                    );
+                }
+            }
+
+            Ok(())
+        }
+        Commands::Errors => {
+            let status = get_status()?;
+            let mut found_errors = false;
+
+            for remote in status.remotes {
+                for dir in remote.dirs.into_iter().filter(|dir| !dir.errors.is_empty()) {
+                    found_errors = true;
+                    println!(
+                        "{}: {} -> /{}",
+                        remote.name,
+                        util::fmt_home(&dir.local_path),
+                        dir.remote_path
+                    );
+
+                    for error in dir.errors {
+                        println!("  - {error}");
+                    }
+                }
+            }
+
+            if !found_errors {
+                println!("{}", tr::tr!("No errors have been found."));
+            }
+
+            Ok(())
+        }
+        Commands::SyncNow => {
+            if activate_action("sync-now", None) {
+                Ok(())
+            } else {
+                Err(tr::tr!("Celeste isn't currently running."))
+            }
+        }
+        Commands::Ignore(command) => run_ignore(&db, command),
+    }
+}
+
+/// Get the status of the running instance.
+fn get_status() -> Result<Status, String> {
+    if running_app().is_none() {
+        return Err(tr::tr!("Celeste isn't currently running."));
+    }
+
+    Status::read().ok_or_else(|| tr::tr!("Celeste hasn't reported its status yet."))
+}
+
+/// Run a `remote` subcommand.
+fn run_remote(db: &DatabaseConnection, command: RemoteCommands) -> Result<(), String> {
+    match command {
+        RemoteCommands::List => {
+            for remote in util::await_future(RemotesEntity::find().all(db)).unwrap() {
+                println!("{}", remote.name);
+            }
+
+            Ok(())
+        }
+        RemoteCommands::Add {
+            name,
+            remote_type,
+            params,
+        } => {
+            if !Regex::new(r"^[0-9a-zA-Z_.][0-9a-zA-Z_. -]*[0-9a-zA-Z_.-]$")
+                .unwrap()
+                .is_match(&name)
+            {
+                return Err(tr::tr!("Invalid name. Names must:\n- Only contain numbers, letters, '_', '-', '.', and spaces\n- Not start with '-' or a space\n- Not end with a space"));
+            }
+
+            let existing_remotes = rclone::get_remote_names();
+
+            if existing_remotes.contains(&name) || find_remote(db, &name).is_ok() {
+                return Err(tr::tr!("A remote with the name '{}' already exists.", name));
+            }
+
+            let mut config: IndexMap<String, String> = params.into_iter().collect();
+            config.insert("type".to_owned(), remote_type.clone());
+
+            if rclone::config_type_name(&config).is_none() {
+                return Err(tr::tr!(
+                    "Remotes of type '{}' aren't supported.",
+                    remote_type
+                ));
+            }
+
+            // Celeste only knows about the Nextcloud and Owncloud WebDAV vendors, so treat
+            // any others as a generic WebDAV server.
+            if remote_type == "webdav"
+                && !["nextcloud", "owncloud"].contains(
+                    &config
+                        .get("vendor")
+                        .map(|vendor| vendor.as_str())
+                        .unwrap_or(""),
+                )
+            {
+                config.insert("vendor".to_owned(), "webdav".to_owned());
+            }
+
+            config.shift_remove("type");
+            let parameters: HashMap<String, String> = config.into_iter().collect();
+            let config_query = json!({
+                "name": name,
+                "parameters": parameters,
+                "type": remote_type,
+                "opt": {
+                    "obscure": true,
+                    "nonInteractive": true
+                }
+            });
+            librclone::rpc("config/create", config_query.to_string())
+                .map_err(|err| tr::tr!("Unable to create the remote [{}].", err))?;
+
+            // Make sure we can actually log in with the given details.
+            if let Err(err) = rclone::sync::stat(&name, "/") {
+                rclone::sync::delete_config(&name).unwrap();
+                return Err(tr::tr!("Unable to log in to the remote [{}].", err.error));
+            }
+
+            // Move the credentials out of the config file and into the keyring, the same as
+            // is done for remotes that get added from the UI.
+            rclone::store_secrets(&name);
+
+            util::await_future(
+                RemotesActiveModel {
+                    name: ActiveValue::Set(name),
+                    ..Default::default()
+                }
+                .insert(db),
+            )
+            .unwrap();
+            activate_action("reload", None);
+
+            Ok(())
+        }
+        RemoteCommands::Remove { name } => {
+            let remote = find_remote(db, &name)?;
+
+            // If Celeste is running, let it remove the remote once it's at a safe point to
+            // do so.
+            if activate_action("remove-remote", Some(&name.to_variant())) {
+                return Ok(());
+            }
+
+            let sync_dirs = util::await_future(
+                SyncDirsEntity::find()
+                    .filter(SyncDirsColumn::RemoteId.eq(remote.id))
+                    .all(db),
+            )
+            .unwrap();
+
+            for sync_dir in sync_dirs {
+                util::delete_sync_dir(db, sync_dir);
+            }
+
+            util::await_future(remote.delete(db)).unwrap();
+            rclone::sync::delete_config(&name).unwrap();
+
+            Ok(())
+        }
+    }
+}
+
+/// Run a `dir` subcommand.
+fn run_dir(db: &DatabaseConnection, command: DirCommands) -> Result<(), String> {
+    match command {
+        DirCommands::List { remote } => {
+            let remotes = match remote {
+                Some(name) => vec![find_remote(db, &name)?],
+                None => util::await_future(RemotesEntity::find().all(db)).unwrap(),
+            };
+
+            for remote in remotes {
+                let sync_dirs = util::await_future(
+                    SyncDirsEntity::find()
+                        .filter(SyncDirsColumn::RemoteId.eq(remote.id))
+                        .all(db),
+                )
+                .unwrap();
+
+                for sync_dir in sync_dirs {
+                    let mut flags = vec![];
+
+                    if sync_dir.crypt_remote.is_some() {
+                        flags.push(tr::tr!("encrypted"));
+                    }
+                    if sync_dir.paused {
+                        flags.push(tr::tr!("paused"));
+                    }
+
+                    let flags = if flags.is_empty() {
+                        String::new()
+                    } else {
+                        format!(" ({})", flags.join(", "))
+                    };
+                    println!(
+                        "{}: {} -> /{}{}",
+                        remote.name, sync_dir.local_path, sync_dir.remote_path, flags
+                    );
+                }
+            }
+
+            Ok(())
+        }
+        DirCommands::Add {
+            remote,
+            local_path,
+            remote_path,
+            encrypt,
+        } => {
+            let db_remote = find_remote(db, &remote)?;
+            let local_text = fmt_local_path(&local_path);
+            let remote_text = util::strip_slashes(&remote_path);
+            let local_path = Path::new(&local_text);
+
+            if find_dir(db, &local_text, &remote_text).is_ok() {
+                return Err(tr::tr!(
+                    "The specified directory pair is already being synced"
+                ));
+            } else if !local_path.exists() {
+                return Err(tr::tr!("The specified local directory doesn't exist"));
+            } else if !local_path.is_dir() {
+                return Err(tr::tr!("The specified local path isn't a directory"));
+            }
+
+            let crypt_remote = if encrypt {
+                let password = match env::var(PASSWORD_ENV) {
+                    Ok(password) => password,
+                    Err(_) => read_password()?,
+                };
+                if password.is_empty() {
+                    return Err(tr::tr!("A password is needed to encrypt the directory"));
+                }
+                let crypt_remote =
+                    rclone::create_crypt_remote(&remote, &password).map_err(|err| {
+                        tr::tr!(
+                            "Failed to set up encryption for the directory [{}].",
+                            err.error
+                        )
+                    })?;
+
+                if let Err(err) = rclone::sync::mkdir(&crypt_remote, &remote_text) {
+                    rclone::sync::delete_config(&crypt_remote).unwrap();
+                    return Err(tr::tr!(
+                        "Failed to create the encrypted directory on the remote [{}].",
+                        err.error
+                    ));
+                }
+
+                Some(crypt_remote)
+            } else {
+                match rclone::sync::stat(&remote, &remote_text) {
+                    Ok(Some(_)) => (),
+                    Ok(None) => {
+                        return Err(tr::tr!("The specified remote directory doesn't exist"))
+                    }
+                    Err(err) => {
+                        return Err(tr::tr!(
+                            "Failed to check if the specified remote directory exists [{}].",
+                            err.error
+                        ))
+                    }
+                }
+
+                None
+            };
+
+            util::await_future(
+                SyncDirsActiveModel {
+                    remote_id: ActiveValue::Set(db_remote.id),
+                    local_path: ActiveValue::Set(local_text),
+                    remote_path: ActiveValue::Set(remote_text),
+                    crypt_remote: ActiveValue::Set(crypt_remote),
+                    ..Default::default()
+                }
+                .insert(db),
+            )
+            .unwrap();
+            activate_action("reload", None);
+
+            Ok(())
+        }
+        DirCommands::Remove {
+            local_path,
+            remote_path,
+        } => {
+            let sync_dir = find_dir(db, &local_path, &remote_path)?;
+            let remote = util::await_future(RemotesEntity::find_by_id(sync_dir.remote_id).one(db))
+                .unwrap()
+                .unwrap();
+
+            // If Celeste is running, let it remove the directory once it's at a safe point
+            // to do so.
+            let data = (
+                remote.name,
+                sync_dir.local_path.clone(),
+                sync_dir.remote_path.clone(),
+            );
+            if activate_action("remove-dir", Some(&data.to_variant())) {
+                return Ok(());
+            }
+
+            util::delete_sync_dir(db, sync_dir);
+            Ok(())
+        }
+        DirCommands::Pause {
+            local_path,
+            remote_path,
+        } => set_paused(db, &local_path, &remote_path, true),
+        DirCommands::Resume {
+            local_path,
+            remote_path,
+        } => set_paused(db, &local_path, &remote_path, false),
+    }
+}
+
+/// Set whether syncing of a directory is paused.
+fn set_paused(
+    db: &DatabaseConnection,
+    local_path: &str,
+    remote_path: &str,
+    paused: bool,
+) -> Result<(), String> {
+    let sync_dir = find_dir(db, local_path, remote_path)?;
+    let mut active_model: SyncDirsActiveModel = sync_dir.into();
+    active_model.paused = ActiveValue::Set(paused);
+    util::await_future(active_model.update(db)).unwrap();
+
+    Ok(())
+}
+
+/// Run an `ignore` subcommand.
+fn run_ignore(db: &DatabaseConnection, command: IgnoreCommands) -> Result<(), String> {
+    let local_path = match &command {
+        IgnoreCommands::Add { local_path, .. } | IgnoreCommands::List { local_path } => {
+            fmt_local_path(local_path)
+        }
+    };
+    let sync_dir = util::await_future(
+        SyncDirsEntity::find()
+            .filter(SyncDirsColumn::LocalPath.eq(local_path.clone()))
+            .one(db),
+    )
+    .unwrap();
+
+    if sync_dir.is_none() {
+        return Err(tr::tr!(
+            "The directory '{}' isn't being synced.",
+            local_path
+        ));
+    }
+
+    let ignore_path = format!("{local_path}/{FILE_IGNORE_NAME}");
+
+    match command {
+        IgnoreCommands::Add { pattern, .. } => {
+            if let Err(err) = exclude::parse_line(&pattern) {
+                return Err(tr::tr!("Invalid pattern '{}' [{}].", pattern, err));
+            }
+
+            let mut lock = FileLock::lock(
+                &ignore_path,
+                true,
+                FileOptions::new().create(true).read(true).append(true),
+            )
+            .map_err(|err| tr::tr!("Unable to open '{}' [{}].", ignore_path, err))?;
+            let content = fs::read_to_string(&ignore_path).unwrap();
+
+            if content.lines().any(|line| line == pattern) {
+                return Ok(());
+            }
+
+            let line = if content.is_empty() || content.ends_with('\n') {
+                pattern
+            } else {
+                format!("\n{pattern}")
+            };
+            lock.file.write_all(line.as_bytes()).unwrap();
+        }
+        IgnoreCommands::List { .. } => {
+            // Listing shouldn't leave an exclusion file behind in directories that
+            // don't have one.
+            let _lock = match FileLock::lock(&ignore_path, true, FileOptions::new().read(true)) {
+                Ok(lock) => lock,
+                Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(()),
+                Err(err) => {
+                    return Err(tr::tr!("Unable to open '{}' [{}].", ignore_path, err));
+                }
+            };
+            let content = fs::read_to_string(&ignore_path).unwrap();
+
+            for line in content.lines().filter(|line| !line.is_empty()) {
+                println!("{line}");
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,167 @@
+//! Detection of item names that a remote can't tell apart, such as
+//! `Readme.md` and `README.md` on a remote that ignores case, or the same name
+//! written with different Unicode normalization forms.
+use crate::{entities::SyncDirsModel, names, rclone, util};
+use std::{collections::HashMap, path::Path};
+use unicode_normalization::UnicodeNormalization;
+
+/// The rules a remote uses to compare item names.
+#[derive(Clone, Copy, Debug)]
+pub struct NameRules {
+    /// Whether names that only differ in case are treated as the same name.
+    case_insensitive: bool,
+}
+
+impl NameRules {
+    /// Get the rules for the remote a sync directory is synced with. This asks
+    /// the remote, so it should only be called once per sync directory on each
+    /// pass.
+    pub fn for_sync_dir(remote_name: &str, sync_dir: &SyncDirsModel) -> Self {
+        // Crypt remotes report the features of the names they store, so encrypted
+        // directories get the right rules here too. If the remote can't be reached,
+        // syncing the directory will report it, so nothing is flagged here.
+        let features =
+            rclone::sync::features(&sync_dir.rclone_remote(remote_name)).unwrap_or_default();
+
+        Self {
+            case_insensitive: features.case_insensitive,
+        }
+    }
+
+    /// Get the form of a name that's compared against other names. Names with
+    /// the same key can't both exist in the same folder on the remote.
+    pub fn key(&self, name: &str) -> String {
+        // Names are always normalized, as the different forms look identical and
+        // many systems (i.e. macOS) convert between them on their own.
+        let normalized: String = name.nfc().collect();
+
+        if self.case_insensitive {
+            normalized.to_lowercase()
+        } else {
+            normalized
+        }
+    }
+
+    /// Group a folder's item names by their keys (see [`NameRules::key`]).
+    /// Any group with more than one name is a collision. Names in each group
+    /// are sorted, so the same name always comes first.
+    pub fn group<'a, I: IntoIterator<Item = &'a str>>(
+        &self,
+        names: I,
+    ) -> HashMap<String, Vec<String>> {
+        let mut groups: HashMap<String, Vec<String>> = HashMap::new();
+
+        for name in names {
+            groups
+                .entry(self.key(name))
+                .or_default()
+                .push(name.to_owned());
+        }
+        for group in groups.values_mut() {
+            group.sort();
+        }
+
+        groups
+    }
+}
+
+/// Show a dialog for renaming the local item at `local_path`, so it no longer
+/// collides with `other_path`. `on_renamed` is called once the item has been
+/// renamed, and `on_cancel` if the dialog is closed without renaming it.
+pub fn rename_dialog<F1: Fn() + 'static, F2: Fn() + 'static>(
+    local_path: &str,
+    other_path: &str,
+    on_renamed: F1,
+    on_cancel: F2,
+) {
+    let name = Path::new(local_path)
+        .file_name()
+        .unwrap()
+        .to_string_lossy()
+        .to_string();
+    let other_name = Path::new(other_path)
+        .file_name()
+        .unwrap()
+        .to_string_lossy()
+        .to_string();
+
+    // Check new names with the strictest rules, so a renamed item doesn't collide
+    // on any remote.
+    let rules = NameRules {
+        case_insensitive: true,
+    };
+
+    names::rename_dialog(
+        local_path,
+        &tr::tr!(
+            "'{}' can't be synced alongside '{}'. Enter a new name for it.",
+            util::fmt_home(local_path),
+            other_path
+        ),
+        &name,
+        move |new_name| rules.key(new_name) != rules.key(&other_name),
+        on_renamed,
+        on_cancel,
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CASE_SENSITIVE: NameRules = NameRules {
+        case_insensitive: false,
+    };
+    const CASE_INSENSITIVE: NameRules = NameRules {
+        case_insensitive: true,
+    };
+
+    /// 'café' with a precomposed 'é' (NFC).
+    const CAFE_NFC: &str = "caf\u{e9}";
+    /// 'café' with an 'e' followed by a combining acute accent (NFD).
+    const CAFE_NFD: &str = "cafe\u{301}";
+
+    #[test]
+    fn key_normalizes() {
+        assert_ne!(CAFE_NFC, CAFE_NFD);
+        assert_eq!(CASE_SENSITIVE.key(CAFE_NFD), CAFE_NFC);
+        assert_eq!(CASE_SENSITIVE.key(CAFE_NFC), CAFE_NFC);
+    }
+
+    #[test]
+    fn key_case() {
+        assert_eq!(CASE_SENSITIVE.key("README.md"), "README.md");
+        assert_eq!(CASE_INSENSITIVE.key("README.md"), "readme.md");
+        assert_eq!(CASE_INSENSITIVE.key("CAFE\u{301}"), "caf\u{e9}");
+    }
+
+    #[test]
+    fn group_normalization_forms() {
+        let groups = CASE_SENSITIVE.group([CAFE_NFD, CAFE_NFC, "other"]);
+
+        assert_eq!(groups.len(), 2);
+        assert_eq!(
+            groups[CAFE_NFC],
+            vec![CAFE_NFD.to_owned(), CAFE_NFC.to_owned()]
+        );
+        assert_eq!(groups["other"], vec!["other".to_owned()]);
+    }
+
+    #[test]
+    fn group_case() {
+        let names = ["Readme.md", "README.md", "notes.txt"];
+
+        // Names that only differ in case only collide on case-insensitive remotes.
+        let groups = CASE_SENSITIVE.group(names);
+        assert_eq!(groups.len(), 3);
+        assert!(groups.values().all(|group| group.len() == 1));
+
+        let groups = CASE_INSENSITIVE.group(names);
+        assert_eq!(groups.len(), 2);
+        assert_eq!(
+            groups["readme.md"],
+            vec!["README.md".to_owned(), "Readme.md".to_owned()]
+        );
+        assert_eq!(groups["notes.txt"], vec!["notes.txt".to_owned()]);
+    }
+}
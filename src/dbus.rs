@@ -0,0 +1,516 @@
+//! The D-Bus interface for controlling the running instance. This gets exported
+//! on the same session bus connection as the [`adw::Application`], so scripts
+//! and shell extensions can talk to it at [`OBJECT_PATH`] under Celeste's app
+//! ID.
+use crate::{
+    entities::{SyncDirsColumn, SyncDirsEntity},
+    status::Status,
+    util,
+};
+use adw::{
+    gio,
+    glib::{self, ToVariant, Variant},
+};
+use sea_orm::{entity::prelude::*, DatabaseConnection};
+use std::sync::Mutex;
+
+/// The object path the interface is exported at.
+pub const OBJECT_PATH: &str = "/com/hunterwittenborn/Celeste/Sync";
+
+/// The name of the interface.
+pub const INTERFACE: &str = "com.hunterwittenborn.Celeste.Sync";
+
+/// The introspection data for the interface. Directories are always referred
+/// to by their local and remote path, and are reported alongside the name of
+/// their remote.
+const INTROSPECTION_XML: &str = r#"
+<node>
+  <interface name="com.hunterwittenborn.Celeste.Sync">
+    <method name="SyncNow"/>
+    <method name="Pause">
+      <arg name="local_path" type="s" direction="in"/>
+      <arg name="remote_path" type="s" direction="in"/>
+    </method>
+    <method name="Resume">
+      <arg name="local_path" type="s" direction="in"/>
+      <arg name="remote_path" type="s" direction="in"/>
+    </method>
+    <method name="ListDirs">
+      <arg name="dirs" type="a(ssss)" direction="out"/>
+    </method>
+    <method name="ListErrors">
+      <arg name="errors" type="a(ssss)" direction="out"/>
+    </method>
+    <signal name="StatusChanged">
+      <arg name="remote" type="s"/>
+      <arg name="local_path" type="s"/>
+      <arg name="remote_path" type="s"/>
+      <arg name="status" type="s"/>
+    </signal>
+    <signal name="ErrorAdded">
+      <arg name="remote" type="s"/>
+      <arg name="local_path" type="s"/>
+      <arg name="remote_path" type="s"/>
+      <arg name="message" type="s"/>
+    </signal>
+  </interface>
+</node>
+"#;
+
+/// A request made over D-Bus, to be processed at the start of the next sync
+/// pass.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Request {
+    /// Sync every directory now, instead of waiting for changes to be found.
+    SyncNow,
+    /// Pause syncing of a directory. A tuple of the local and remote path.
+    Pause(String, String),
+    /// Resume syncing of a directory. A tuple of the local and remote path.
+    Resume(String, String),
+}
+
+lazy_static::lazy_static! {
+    /// The connection the interface has been exported on.
+    static ref CONNECTION: Mutex<Option<gio::DBusConnection>> = Mutex::new(None);
+    /// The last status reported through [`set_status`].
+    static ref STATUS: Mutex<Status> = Mutex::new(Status { remotes: vec![] });
+    /// Any requests that haven't been processed yet.
+    pub static ref REQUESTS: Mutex<Vec<Request>> = Mutex::new(vec![]);
+}
+
+/// Export the interface on the given connection.
+pub fn register(conn: &gio::DBusConnection, db: &DatabaseConnection) -> Result<(), glib::Error> {
+    let db = db.clone();
+    let node_info = gio::DBusNodeInfo::for_xml(INTROSPECTION_XML)?;
+    let interface_info = node_info.lookup_interface(INTERFACE).unwrap();
+
+    conn.register_object(
+        OBJECT_PATH,
+        &interface_info,
+        move |_, _, _, _, method, params, invocation| {
+            handle_method_call(&db, method, params, invocation)
+        },
+        |_, _, _, _, _| unreachable!(),
+        |_, _, _, _, _, _| unreachable!(),
+    )?;
+    *(*CONNECTION).lock().unwrap() = Some(conn.clone());
+
+    Ok(())
+}
+
+/// Handle a method call on the interface.
+fn handle_method_call(
+    db: &DatabaseConnection,
+    method: &str,
+    params: Variant,
+    invocation: gio::DBusMethodInvocation,
+) {
+    match method {
+        "SyncNow" => {
+            (*REQUESTS).lock().unwrap().push(Request::SyncNow);
+            invocation.return_value(None);
+        }
+        "Pause" | "Resume" => {
+            let (local_path, remote_path): (String, String) = params.get().unwrap();
+            let local_path = "/".to_string() + &util::strip_slashes(&local_path);
+            let remote_path = util::strip_slashes(&remote_path);
+            // The status isn't written until the first sync pass gets going, so check
+            // the database instead.
+            let exists = util::await_future(
+                SyncDirsEntity::find()
+                    .filter(SyncDirsColumn::LocalPath.eq(local_path.clone()))
+                    .filter(SyncDirsColumn::RemotePath.eq(remote_path.clone()))
+                    .one(db),
+            )
+            .unwrap()
+            .is_some();
+
+            if !exists {
+                invocation.return_dbus_error(
+                    "org.freedesktop.DBus.Error.InvalidArgs",
+                    &tr::tr!(
+                        "The directory pair '{}' and '/{}' isn't being synced.",
+                        local_path,
+                        remote_path
+                    ),
+                );
+                return;
+            }
+
+            let request = if method == "Pause" {
+                Request::Pause(local_path, remote_path)
+            } else {
+                Request::Resume(local_path, remote_path)
+            };
+            (*REQUESTS).lock().unwrap().push(request);
+            invocation.return_value(None);
+        }
+        "ListDirs" => {
+            let dirs: Vec<(String, String, String, String)> = (*STATUS)
+                .lock()
+                .unwrap()
+                .remotes
+                .iter()
+                .flat_map(|remote| {
+                    remote.dirs.iter().map(|dir| {
+                        (
+                            remote.name.clone(),
+                            dir.local_path.clone(),
+                            dir.remote_path.clone(),
+                            dir.status.trim().to_owned(),
+                        )
+                    })
+                })
+                .collect();
+            invocation.return_value(Some(&(dirs,).to_variant()));
+        }
+        "ListErrors" => {
+            let errors: Vec<(String, String, String, String)> = (*STATUS)
+                .lock()
+                .unwrap()
+                .remotes
+                .iter()
+                .flat_map(|remote| {
+                    remote.dirs.iter().flat_map(|dir| {
+                        dir.errors.iter().map(|error| {
+                            (
+                                remote.name.clone(),
+                                dir.local_path.clone(),
+                                dir.remote_path.clone(),
+                                error.clone(),
+                            )
+                        })
+                    })
+                })
+                .collect();
+            invocation.return_value(Some(&(errors,).to_variant()));
+        }
+        _ => unreachable!(),
+    }
+}
+
+/// Emit a signal on the interface, if it's been exported.
+fn emit(signal: &str, params: Variant) {
+    if let Some(conn) = &*(*CONNECTION).lock().unwrap() {
+        // There's nothing useful to do if a signal can't be sent, the status can still
+        // be fetched through the interface's methods.
+        let _ = conn.emit_signal(None, OBJECT_PATH, INTERFACE, signal, Some(&params));
+    }
+}
+
+/// Update the status reported over D-Bus, emitting `StatusChanged` for any
+/// directories whose status has changed.
+pub fn set_status(status: &Status) {
+    let mut old_status = (*STATUS).lock().unwrap();
+
+    for remote in &status.remotes {
+        for dir in &remote.dirs {
+            let old_dir = old_status
+                .remotes
+                .iter()
+                .find(|old_remote| old_remote.name == remote.name)
+                .and_then(|old_remote| {
+                    old_remote.dirs.iter().find(|old_dir| {
+                        old_dir.local_path == dir.local_path
+                            && old_dir.remote_path == dir.remote_path
+                    })
+                });
+
+            if old_dir.is_none_or(|old_dir| old_dir.status != dir.status) {
+                emit(
+                    "StatusChanged",
+                    (
+                        &remote.name,
+                        &dir.local_path,
+                        &dir.remote_path,
+                        dir.status.trim(),
+                    )
+                        .to_variant(),
+                );
+            }
+        }
+    }
+
+    *old_status = status.clone();
+}
+
+/// Emit `ErrorAdded` for a newly found sync error.
+pub fn error_added(remote: &str, local_path: &str, remote_path: &str, message: &str) {
+    emit(
+        "ErrorAdded",
+        (remote, local_path, remote_path, message).to_variant(),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    //! These talk to the interface over a real session bus, so they need to be
+    //! ran under `dbus-run-session` (i.e. through `just test`).
+    use super::*;
+    use crate::{
+        entities::{RemotesActiveModel, SyncDirsActiveModel},
+        migrations::{Migrator, MigratorTrait},
+        status::{DirState, DirStatus, RemoteConnection, RemoteStatus},
+    };
+    use sea_orm::{ActiveValue, Database};
+    use std::{cell::RefCell, rc::Rc};
+
+    lazy_static::lazy_static! {
+        /// The interface's state is global, so only one test can use it at a time.
+        static ref LOCK: Mutex<()> = Mutex::new(());
+    }
+
+    const LOCAL_PATH: &str = "/home/user/Documents";
+    const REMOTE_PATH: &str = "Documents";
+
+    /// The reply from `ListDirs` and `ListErrors`.
+    type Listing = (Vec<(String, String, String, String)>,);
+
+    /// The interface exported on its own connection, along with a separate
+    /// connection to call it from.
+    struct Bus {
+        context: glib::MainContext,
+        client: gio::DBusConnection,
+        server_name: String,
+    }
+
+    impl Bus {
+        /// Call `method` on the interface.
+        fn call(&self, method: &str, params: Option<Variant>) -> Result<Variant, glib::Error> {
+            self.context.block_on(self.client.call_future(
+                Some(&self.server_name),
+                OBJECT_PATH,
+                INTERFACE,
+                method,
+                params.as_ref(),
+                None,
+                gio::DBusCallFlags::NONE,
+                5000,
+            ))
+        }
+
+        /// Run `action`, returning the parameters of each `signal` that got
+        /// emitted while it ran.
+        fn signals(&self, signal: &str, action: impl FnOnce()) -> Vec<Variant> {
+            let received = Rc::new(RefCell::new(vec![]));
+            let id = self.client.signal_subscribe(
+                Some(&self.server_name),
+                Some(INTERFACE),
+                Some(signal),
+                Some(OBJECT_PATH),
+                None,
+                gio::DBusSignalFlags::NONE,
+                glib::clone!(@strong received => move |_, _, _, _, _, params| {
+                    received.borrow_mut().push(params.clone());
+                }),
+            );
+
+            // Messages are delivered in order, so a round trip to the interface makes
+            // sure the subscription is in place before `action` runs, and that
+            // anything it emitted has arrived after.
+            self.call("ListDirs", None).unwrap();
+            action();
+            self.call("ListDirs", None).unwrap();
+            while self.context.iteration(false) {}
+
+            self.client.signal_unsubscribe(id);
+            received.take()
+        }
+    }
+
+    /// Open a new connection to the session bus.
+    fn connect() -> gio::DBusConnection {
+        let address =
+            gio::dbus_address_get_for_bus_sync(gio::BusType::Session, gio::Cancellable::NONE)
+                .expect("the tests need to be ran under `dbus-run-session`");
+        gio::DBusConnection::for_address_sync(
+            &address,
+            gio::DBusConnectionFlags::AUTHENTICATION_CLIENT
+                | gio::DBusConnectionFlags::MESSAGE_BUS_CONNECTION,
+            None,
+            gio::Cancellable::NONE,
+        )
+        .unwrap()
+    }
+
+    /// Run `test` with the interface exported for a database containing a
+    /// single sync directory at [`LOCAL_PATH`] and [`REMOTE_PATH`].
+    fn with_bus(test: impl FnOnce(&Bus)) {
+        let _lock = LOCK.lock().unwrap_or_else(|err| err.into_inner());
+        let context = glib::MainContext::new();
+
+        context
+            .with_thread_default(|| {
+                let dir = tempfile::tempdir().unwrap();
+                let db = util::await_future(Database::connect(format!(
+                    "sqlite://{}?mode=rwc",
+                    dir.path().join("celeste.db").display()
+                )))
+                .unwrap();
+                util::await_future(Migrator::up(&db, None)).unwrap();
+                let remote = util::await_future(
+                    RemotesActiveModel {
+                        name: ActiveValue::Set("remote".to_owned()),
+                        ..Default::default()
+                    }
+                    .insert(&db),
+                )
+                .unwrap();
+                util::await_future(
+                    SyncDirsActiveModel {
+                        remote_id: ActiveValue::Set(remote.id),
+                        local_path: ActiveValue::Set(LOCAL_PATH.to_owned()),
+                        remote_path: ActiveValue::Set(REMOTE_PATH.to_owned()),
+                        ..Default::default()
+                    }
+                    .insert(&db),
+                )
+                .unwrap();
+
+                (*REQUESTS).lock().unwrap().clear();
+                *(*STATUS).lock().unwrap() = Status { remotes: vec![] };
+
+                let server = connect();
+                register(&server, &db).unwrap();
+                let bus = Bus {
+                    context: context.clone(),
+                    client: connect(),
+                    server_name: server.unique_name().unwrap().to_string(),
+                };
+                test(&bus);
+            })
+            .unwrap();
+    }
+
+    /// A status with the sync directory showing `text` and `errors`.
+    fn status(text: &str, errors: &[&str]) -> Status {
+        Status {
+            remotes: vec![RemoteStatus {
+                name: "remote".to_owned(),
+                connection: RemoteConnection::Connected,
+                dirs: vec![DirStatus {
+                    local_path: LOCAL_PATH.to_owned(),
+                    remote_path: REMOTE_PATH.to_owned(),
+                    state: DirState::Synced,
+                    status: text.to_owned(),
+                    last_sync: None,
+                    error_count: errors.len(),
+                    errors: errors.iter().map(|error| error.to_string()).collect(),
+                    progress: None,
+                }],
+            }],
+        }
+    }
+
+    #[test]
+    fn sync_now() {
+        with_bus(|bus| {
+            bus.call("SyncNow", None).unwrap();
+            assert_eq!(*(*REQUESTS).lock().unwrap(), vec![Request::SyncNow]);
+        });
+    }
+
+    #[test]
+    fn pause_and_resume() {
+        with_bus(|bus| {
+            // Paths get normalized to how they're stored in the database.
+            bus.call(
+                "Pause",
+                Some(("/home/user/Documents/", "/Documents/").to_variant()),
+            )
+            .unwrap();
+            bus.call("Resume", Some((LOCAL_PATH, REMOTE_PATH).to_variant()))
+                .unwrap();
+
+            assert_eq!(
+                *(*REQUESTS).lock().unwrap(),
+                vec![
+                    Request::Pause(LOCAL_PATH.to_owned(), REMOTE_PATH.to_owned()),
+                    Request::Resume(LOCAL_PATH.to_owned(), REMOTE_PATH.to_owned()),
+                ]
+            );
+        });
+    }
+
+    #[test]
+    fn pause_unknown_dir() {
+        with_bus(|bus| {
+            let err = bus
+                .call("Pause", Some((LOCAL_PATH, "Pictures").to_variant()))
+                .unwrap_err();
+            assert!(err.matches(gio::DBusError::InvalidArgs));
+            assert!((*REQUESTS).lock().unwrap().is_empty());
+        });
+    }
+
+    #[test]
+    fn list_dirs_and_errors() {
+        with_bus(|bus| {
+            set_status(&status("Directory has 1 error. ", &["Failed to sync 'a'."]));
+
+            let dirs: Listing = bus.call("ListDirs", None).unwrap().get().unwrap();
+            assert_eq!(
+                dirs.0,
+                vec![(
+                    "remote".to_owned(),
+                    LOCAL_PATH.to_owned(),
+                    REMOTE_PATH.to_owned(),
+                    "Directory has 1 error.".to_owned()
+                )]
+            );
+
+            let errors: Listing = bus.call("ListErrors", None).unwrap().get().unwrap();
+            assert_eq!(
+                errors.0,
+                vec![(
+                    "remote".to_owned(),
+                    LOCAL_PATH.to_owned(),
+                    REMOTE_PATH.to_owned(),
+                    "Failed to sync 'a'.".to_owned()
+                )]
+            );
+        });
+    }
+
+    #[test]
+    fn status_changed() {
+        with_bus(|bus| {
+            let signals = bus.signals("StatusChanged", || set_status(&status("Syncing...", &[])));
+            assert_eq!(
+                signals,
+                vec![("remote", LOCAL_PATH, REMOTE_PATH, "Syncing...").to_variant()]
+            );
+
+            // Nothing gets emitted for directories whose status stays the same.
+            let signals = bus.signals("StatusChanged", || set_status(&status("Syncing...", &[])));
+            assert!(signals.is_empty());
+
+            let signals = bus.signals("StatusChanged", || {
+                set_status(&status("Directory has been synced.", &[]))
+            });
+            assert_eq!(
+                signals,
+                vec![(
+                    "remote",
+                    LOCAL_PATH,
+                    REMOTE_PATH,
+                    "Directory has been synced."
+                )
+                    .to_variant()]
+            );
+        });
+    }
+
+    #[test]
+    fn error_added_signal() {
+        with_bus(|bus| {
+            let signals = bus.signals("ErrorAdded", || {
+                error_added("remote", LOCAL_PATH, REMOTE_PATH, "Failed to sync 'a'.")
+            });
+            assert_eq!(
+                signals,
+                vec![("remote", LOCAL_PATH, REMOTE_PATH, "Failed to sync 'a'.").to_variant()]
+            );
+        });
+    }
+}
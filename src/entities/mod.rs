@@ -1,7 +1,21 @@
 //! `SeaORM` Entity. Generated by sea-orm-codegen 0.10.3
+mod activity;
+mod file_versions;
 mod remotes;
 mod sync_dirs;
+mod sync_errors;
 mod sync_items;
+mod unselected_folders;
+
+pub use activity::ActiveModel as ActivityActiveModel;
+pub use activity::Column as ActivityColumn;
+pub use activity::Entity as ActivityEntity;
+pub use activity::Model as ActivityModel;
+
+pub use file_versions::ActiveModel as FileVersionsActiveModel;
+pub use file_versions::Column as FileVersionsColumn;
+pub use file_versions::Entity as FileVersionsEntity;
+pub use file_versions::Model as FileVersionsModel;
 
 pub use remotes::ActiveModel as RemotesActiveModel;
 pub use remotes::Column as RemotesColumn;
@@ -13,7 +27,17 @@ pub use sync_dirs::Column as SyncDirsColumn;
 pub use sync_dirs::Entity as SyncDirsEntity;
 pub use sync_dirs::Model as SyncDirsModel;
 
+pub use sync_errors::ActiveModel as SyncErrorsActiveModel;
+pub use sync_errors::Column as SyncErrorsColumn;
+pub use sync_errors::Entity as SyncErrorsEntity;
+pub use sync_errors::Model as SyncErrorsModel;
+
 pub use sync_items::ActiveModel as SyncItemsActiveModel;
 pub use sync_items::Column as SyncItemsColumn;
 pub use sync_items::Entity as SyncItemsEntity;
 pub use sync_items::Model as SyncItemsModel;
+
+pub use unselected_folders::ActiveModel as UnselectedFoldersActiveModel;
+pub use unselected_folders::Column as UnselectedFoldersColumn;
+pub use unselected_folders::Entity as UnselectedFoldersEntity;
+pub use unselected_folders::Model as UnselectedFoldersModel;
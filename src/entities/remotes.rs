@@ -12,10 +12,18 @@ pub struct Model {
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
+    #[sea_orm(has_many = "super::activity::Entity")]
+    Activity,
     #[sea_orm(has_many = "super::sync_dirs::Entity")]
     SyncDirs,
 }
 
+impl Related<super::activity::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Activity.def()
+    }
+}
+
 impl Related<super::sync_dirs::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::SyncDirs.def()
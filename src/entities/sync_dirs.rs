@@ -15,6 +15,27 @@ pub struct Model {
     /// The remote path being synced, as an absolute path (though it won't start
     /// with `/`).
     pub remote_path: String,
+    /// The Rclone crypt remote that files in this directory are encrypted
+    /// through, if the directory is encrypted.
+    pub crypt_remote: Option<String>,
+    /// Whether syncing of this directory has been paused.
+    pub paused: bool,
+    /// Whether `.gitignore` files in this directory are honored alongside its
+    /// exclusion files.
+    pub honor_gitignore: bool,
+    /// The largest files to sync in bytes, or [`None`] if there's no limit.
+    pub max_size: Option<i64>,
+    /// The oldest files to sync, in seconds since they were last modified, or
+    /// [`None`] if there's no limit.
+    pub max_age: Option<i64>,
+    /// A comma-separated list of the file extensions to sync, or an empty
+    /// string to sync files of every type.
+    pub file_types: String,
+    /// Whether hidden files and folders are skipped.
+    pub skip_hidden: bool,
+    /// How symbolic links are handled, as stored by
+    /// [`crate::symlinks::SymlinkPolicy::setting`].
+    pub symlinks: String,
 }
 
 impl Model {
@@ -30,6 +51,15 @@ impl Model {
         .unwrap()
         .is_some()
     }
+
+    /// Get the name of the Rclone remote to sync this directory with. This is
+    /// the crypt remote if the directory is encrypted, and `remote_name`
+    /// otherwise.
+    pub fn rclone_remote(&self, remote_name: &str) -> String {
+        self.crypt_remote
+            .clone()
+            .unwrap_or_else(|| remote_name.to_owned())
+    }
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -44,6 +74,14 @@ pub enum Relation {
     Remotes,
     #[sea_orm(has_many = "super::sync_items::Entity")]
     SyncItems,
+    #[sea_orm(has_many = "super::sync_errors::Entity")]
+    SyncErrors,
+    #[sea_orm(has_many = "super::activity::Entity")]
+    Activity,
+    #[sea_orm(has_many = "super::file_versions::Entity")]
+    FileVersions,
+    #[sea_orm(has_many = "super::unselected_folders::Entity")]
+    UnselectedFolders,
 }
 
 impl Related<super::remotes::Entity> for Entity {
@@ -58,4 +96,28 @@ impl Related<super::sync_items::Entity> for Entity {
     }
 }
 
+impl Related<super::sync_errors::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::SyncErrors.def()
+    }
+}
+
+impl Related<super::activity::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Activity.def()
+    }
+}
+
+impl Related<super::file_versions::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::FileVersions.def()
+    }
+}
+
+impl Related<super::unselected_folders::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::UnselectedFolders.def()
+    }
+}
+
 impl ActiveModelBehavior for ActiveModel {}
@@ -17,6 +17,13 @@ pub struct Model {
     pub last_local_timestamp: i32,
     /// The remote UNIX timestamp of the item when last synced.
     pub last_remote_timestamp: i32,
+    /// The inode of the local item when last synced, used to recognize the item
+    /// after it gets moved or renamed locally.
+    pub local_inode: Option<i64>,
+    /// The ID the remote gave the item when last synced, used to recognize the
+    /// item after it gets moved or renamed on the remote. Not every remote
+    /// gives out IDs.
+    pub remote_id: Option<String>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -0,0 +1,769 @@
+//! Parsing and matching of exclusion files, which use the same syntax as
+//! `.gitignore` files.
+//!
+//! Each line is a glob pattern. Blank lines and lines starting with `#` are
+//! skipped, a leading `!` re-includes anything a previous pattern excluded, and
+//! a trailing `/` only matches directories. Patterns containing a `/` are
+//! anchored to the directory of the file they're in, while any others match
+//! items with that name at any depth. Exclusion files in subdirectories apply
+//! to everything below them, and take priority over those further up.
+//!
+//! On top of each sync directory's own exclusion files, there's a global
+//! exclusion file that applies to every sync directory, and sync directories
+//! can opt in to also honoring any `.gitignore` files in them.
+use crate::{entities::SyncDirsModel, launch::FILE_IGNORE_NAME, traits::prelude::*, util};
+use adw::{
+    glib,
+    gtk::{
+        Align, Box, Button, ListBox, MenuButton, Orientation, Popover, ScrolledWindow,
+        SelectionMode,
+    },
+    prelude::*,
+    EntryRow, MessageDialog,
+};
+use file_lock::{FileLock, FileOptions};
+use glob::{MatchOptions, Pattern};
+use indexmap::IndexMap;
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    fs::{self, OpenOptions},
+    io::Write,
+    path::Path,
+    rc::Rc,
+};
+
+/// The name of the global exclusion file, in Celeste's config directory.
+static GLOBAL_FILE_NAME: &str = "global-exclude.lst";
+
+/// The name of Git's exclusion files.
+static GITIGNORE_NAME: &str = ".gitignore";
+
+/// The options patterns get matched with. Wildcards never match a `/`, so a
+/// `*` stays within a single directory like it does in Git.
+const MATCH_OPTIONS: MatchOptions = MatchOptions {
+    case_sensitive: true,
+    require_literal_separator: true,
+    require_literal_leading_dot: false,
+};
+
+/// A single rule from an exclusion file.
+#[derive(Clone, Debug)]
+pub struct Rule {
+    pattern: Pattern,
+    /// Whether the rule re-includes items instead of excluding them.
+    negated: bool,
+    /// Whether the rule only applies to directories.
+    dir_only: bool,
+    /// Whether the rule is matched against the full path from the exclusion
+    /// file's directory, instead of just the item's name.
+    anchored: bool,
+}
+
+impl Rule {
+    /// Check if this rule matches a path, relative to the directory of the
+    /// exclusion file the rule is from.
+    fn matches(&self, path: &str, is_dir: bool) -> bool {
+        if self.dir_only && !is_dir {
+            return false;
+        }
+
+        if self.anchored {
+            self.pattern.matches_with(path, MATCH_OPTIONS)
+        } else {
+            let name = path.rsplit('/').next().unwrap();
+            self.pattern.matches_with(name, MATCH_OPTIONS)
+        }
+    }
+}
+
+/// A line in an exclusion file that couldn't be parsed.
+#[derive(Clone, Debug)]
+pub struct InvalidRule {
+    /// The exclusion file the line is in.
+    pub file: String,
+    /// The line number, starting at 1.
+    pub line: usize,
+    /// The content of the line.
+    pub pattern: String,
+    /// Why the line couldn't be parsed.
+    pub error: String,
+}
+
+impl InvalidRule {
+    /// Get a message describing the problem, for use in the UI.
+    pub fn message(&self) -> String {
+        tr::tr!(
+            "Invalid exclusion '{}' on line {} [{}].",
+            self.pattern,
+            self.line,
+            self.error
+        )
+    }
+}
+
+/// Parse a single line from an exclusion file. Returns [`None`] if the line is
+/// blank or a comment.
+pub fn parse_line(line: &str) -> Result<Option<Rule>, String> {
+    let line = line.trim_end();
+    if line.is_empty() || line.starts_with('#') {
+        return Ok(None);
+    }
+
+    // A leading backslash lets patterns start with a literal '#' or '!'.
+    let (negated, line) = if let Some(escaped) = line.strip_prefix('\\')
+        && (escaped.starts_with('#') || escaped.starts_with('!'))
+    {
+        (false, escaped)
+    } else if let Some(stripped) = line.strip_prefix('!') {
+        (true, stripped)
+    } else {
+        (false, line)
+    };
+    let (dir_only, line) = match line.strip_suffix('/') {
+        Some(stripped) => (true, stripped),
+        None => (false, line),
+    };
+    let anchored = line.contains('/');
+    let line = line.strip_prefix('/').unwrap_or(line);
+
+    if line.is_empty() {
+        return Err(tr::tr!("The pattern doesn't match any items"));
+    }
+
+    let pattern = Pattern::new(line).map_err(|err| err.to_string())?;
+    Ok(Some(Rule {
+        pattern,
+        negated,
+        dir_only,
+        anchored,
+    }))
+}
+
+/// Parse the content of an exclusion file. Returns the valid rules, along with
+/// the line number, content, and error of each invalid line.
+pub fn parse(content: &str) -> (Vec<Rule>, Vec<(usize, String, String)>) {
+    let mut rules = vec![];
+    let mut invalid = vec![];
+
+    for (index, line) in content.lines().enumerate() {
+        match parse_line(line) {
+            Ok(Some(rule)) => rules.push(rule),
+            Ok(None) => (),
+            Err(err) => invalid.push((index + 1, line.to_owned(), err)),
+        }
+    }
+
+    (rules, invalid)
+}
+
+/// The rules from each exclusion file that's been read, keyed by the file's
+/// path ([`None`] if the file doesn't exist).
+type ReadFiles = Rc<RefCell<HashMap<String, Option<Rc<Vec<Rule>>>>>>;
+
+/// The exclusion rules that apply to a directory in a sync directory.
+///
+/// These are built up while walking down a sync directory:
+/// [`Exclusions::global`] gets the rules from the global exclusion file, and
+/// [`Exclusions::for_dir`] adds the rules from each directory's own files on
+/// top of its parent's.
+#[derive(Clone)]
+pub struct Exclusions {
+    /// The rules from each exclusion file, along with the directory the file is
+    /// in relative to the sync directory (or an empty string for the sync
+    /// directory itself). These are ordered from the top of the tree down.
+    files: Vec<(String, Rc<Vec<Rule>>)>,
+    /// Any lines that couldn't be parsed in the files that were read when
+    /// these exclusions were made.
+    invalid: Vec<InvalidRule>,
+    /// Every exclusion file that's been read. This is shared with every
+    /// [`Exclusions`] made from the same [`Exclusions::global`], so each file
+    /// only gets read (and has its invalid lines reported) once.
+    read_files: ReadFiles,
+}
+
+impl Exclusions {
+    /// Load the rules from the global exclusion file. The exclusions for the
+    /// directories in a sync directory are then made with
+    /// [`Exclusions::for_dir`].
+    pub fn global() -> Self {
+        let mut exclusions = Self {
+            files: vec![],
+            invalid: vec![],
+            read_files: Rc::new(RefCell::new(HashMap::new())),
+        };
+        exclusions.load_file(&global_file(), "");
+        exclusions
+    }
+
+    /// Get the exclusions that apply to `dir`, a directory relative to the
+    /// local path of `sync_dir`. `self` should be the exclusions for the
+    /// directory above `dir`, or those from [`Exclusions::global`] for the
+    /// sync directory itself.
+    pub fn for_dir(&self, sync_dir: &SyncDirsModel, dir: &str) -> Self {
+        let mut exclusions = Self {
+            files: self.files.clone(),
+            invalid: vec![],
+            read_files: self.read_files.clone(),
+        };
+        let base = util::strip_slashes(dir);
+        let dir_string = if base.is_empty() {
+            sync_dir.local_path.clone()
+        } else {
+            format!("{}/{base}", sync_dir.local_path)
+        };
+
+        // Celeste's own exclusion files come after '.gitignore' files, so they can
+        // re-include anything Git ignores.
+        if sync_dir.honor_gitignore {
+            exclusions.load_file(&format!("{dir_string}/{GITIGNORE_NAME}"), &base);
+        }
+        exclusions.load_file(&format!("{dir_string}/{FILE_IGNORE_NAME}"), &base);
+
+        exclusions
+    }
+
+    /// Load the rules from an exclusion file, which apply to `base` and
+    /// everything below it. Nothing is loaded if the file doesn't exist.
+    fn load_file(&mut self, file_string: &str, base: &str) {
+        let already_read = self.read_files.get_ref().get(file_string).cloned();
+        if let Some(rules) = already_read {
+            if let Some(rules) = rules {
+                self.files.push((base.to_owned(), rules));
+            }
+            return;
+        }
+
+        let file_path = Path::new(file_string);
+        let content = if file_path.exists() {
+            let _lock = FileLock::lock(file_path, true, FileOptions::new().write(true).read(true));
+            fs::read_to_string(file_path).ok()
+        } else {
+            None
+        };
+        let rules = content.map(|content| {
+            let (rules, invalid) = parse(&content);
+            self.invalid.extend(
+                invalid
+                    .into_iter()
+                    .map(|(line, pattern, error)| InvalidRule {
+                        file: file_string.to_owned(),
+                        line,
+                        pattern,
+                        error,
+                    }),
+            );
+            Rc::new(rules)
+        });
+
+        if let Some(rules) = &rules {
+            self.files.push((base.to_owned(), rules.clone()));
+        }
+        self.read_files
+            .get_mut_ref()
+            .insert(file_string.to_owned(), rules);
+    }
+
+    /// Check if an item is excluded. `path` is relative to the sync directory.
+    pub fn is_excluded(&self, path: &str, is_dir: bool) -> bool {
+        let mut excluded = false;
+
+        for (base, rules) in &self.files {
+            let relative_path = if base.is_empty() {
+                path
+            } else {
+                match path.strip_prefix(&format!("{base}/")) {
+                    Some(relative_path) => relative_path,
+                    None => continue,
+                }
+            };
+
+            // Later rules take priority, so the last one that matches decides.
+            for rule in rules.iter() {
+                if rule.matches(relative_path, is_dir) {
+                    excluded = !rule.negated;
+                }
+            }
+        }
+
+        excluded
+    }
+
+    /// Get the lines that couldn't be parsed in the files that were read when
+    /// these exclusions were made. Files that were already read for other
+    /// exclusions aren't included, so each invalid line only gets reported
+    /// once.
+    pub fn invalid(&self) -> &[InvalidRule] {
+        &self.invalid
+    }
+}
+
+/// Get the path of the global exclusion file.
+pub fn global_file() -> String {
+    let mut path = util::get_config_dir();
+    path.push(GLOBAL_FILE_NAME);
+    path.into_os_string().into_string().unwrap()
+}
+
+/// Get the built-in presets for the global exclusion file, as a list of each
+/// preset's name and rules.
+pub fn presets() -> Vec<(String, &'static [&'static str])> {
+    vec![
+        (
+            tr::tr!("Editor swap files"),
+            &["*.swp", "*.swo", "*~", ".#*", "\\#*#", "*.kate-swp"],
+        ),
+        (
+            tr::tr!("Operating system files"),
+            &[
+                ".DS_Store",
+                "._*",
+                "Thumbs.db",
+                "desktop.ini",
+                ".directory",
+                ".Trash-*/",
+            ],
+        ),
+        (
+            tr::tr!("Build outputs"),
+            &[
+                "target/",
+                "node_modules/",
+                ".venv/",
+                "__pycache__/",
+                "*.pyc",
+                ".gradle/",
+            ],
+        ),
+    ]
+}
+
+/// Check that the rule in an exclusion row is valid, marking it as an error
+/// and hiding its apply button if it isn't.
+fn validate_row(row: &EntryRow) {
+    if let Err(err) = parse_line(&row.text()) {
+        row.set_show_apply_button(false);
+        row.add_css_class("error");
+        row.set_tooltip_text(Some(&err));
+    } else {
+        row.remove_css_class("error");
+        row.set_tooltip_text(None);
+        row.set_show_apply_button(true);
+    }
+}
+
+/// Fill `list` with editable rows for the rules in the exclusion file at
+/// `file_path`, saving any changes back to the file. Returns a closure that
+/// adds a rule to the list: an empty row for the user to fill in when given
+/// [`None`], or the given rule otherwise, which gets saved straight away if it
+/// isn't in the file already.
+pub fn editor(file_path: &str, list: &ListBox) -> Rc<dyn Fn(Option<&str>)> {
+    let file_path = file_path.to_owned();
+
+    // Read the exclusion file to see if anything exists in it so far.
+    let get_lock = glib::clone!(@strong file_path => move || {
+        // This will return an [`Err`] if the parent folder doesn't exist, so handle that case instead of `.unwrap`ing it.
+        FileLock::lock(&file_path, true, FileOptions::new().create(true).read(true).write(true).append(false))
+    });
+
+    let file_content = if get_lock().is_ok() {
+        Some(fs::read_to_string(&file_path).unwrap())
+    } else {
+        None
+    };
+
+    let rules: Rc<RefCell<IndexMap<EntryRow, String>>> = Rc::new(RefCell::new(IndexMap::new()));
+    let write_file = glib::clone!(@strong file_path, @strong rules, @strong get_lock => move || {
+        let ptr = rules.get_ref();
+        let strings: Vec<String> = ptr.values().map(|item| item.to_owned()).collect();
+
+        // First truncate the file.
+        OpenOptions::new().write(true).truncate(true).open(&file_path).unwrap();
+
+        // And then write to it.
+        if let Ok(mut lock) = get_lock() {
+            lock.file.write_all(strings.join("\n").as_bytes()).unwrap()
+        };
+    });
+    let gen_row = glib::clone!(@strong write_file, @strong rules, @strong list => move |content: Option<&str>| {
+        let row = EntryRow::builder().css_classes(vec!["celeste-no-title".to_string()]).build();
+        if let Some(text) = content {
+            row.set_text(text);
+        } else {
+            row.set_show_apply_button(true);
+        }
+        let remove_button = Button::builder().icon_name("list-remove-symbolic").valign(Align::Center).css_classes(vec!["flat".to_string()]).build();
+        row.connect_apply(glib::clone!(@strong write_file, @strong rules => move |row| {
+            // Make sure our rules have the latest string for this item.
+            let mut ptr = rules.get_mut_ref();
+            ptr.insert(row.clone(), row.text().to_string());
+            drop(ptr);
+
+            // Write out all the current rules to the file.
+            write_file();
+        }));
+        remove_button.connect_clicked(glib::clone!(@strong write_file, @strong rules, @weak row, @weak list => move |_| {
+            row.set_sensitive(false);
+            list.remove(&row);
+
+            // This returns [`None`] if the item hasn't been added via `row.connect_apply` above yet.
+            let mut ptr = rules.get_mut_ref();
+            if ptr.remove(&row).is_none() {
+                return;
+            }
+
+            drop(ptr);
+            write_file();
+        }));
+        row.connect_changed(validate_row);
+        // Rules loaded from the file also need checking, so invalid ones that were
+        // written by hand get pointed out.
+        if parse_line(&row.text()).is_err() {
+            validate_row(&row);
+        }
+        row.add_suffix(&remove_button);
+        row
+    });
+
+    if let Some(content) = file_content {
+        for line in content.lines() {
+            let row = gen_row(Some(line));
+            list.append(&row);
+            rules.get_mut_ref().insert(row, line.to_owned());
+        }
+    }
+
+    Rc::new(glib::clone!(@strong list => move |rule: Option<&str>| {
+        let rule = match rule {
+            Some(rule) => rule,
+            None => {
+                list.append(&gen_row(None));
+                return;
+            }
+        };
+
+        if rules.get_ref().values().any(|existing| existing == rule) {
+            return;
+        }
+
+        let row = gen_row(Some(rule));
+        list.append(&row);
+        rules.get_mut_ref().insert(row, rule.to_owned());
+        write_file();
+    }))
+}
+
+/// Show a dialog for managing the global exclusion file.
+pub fn global_dialog() {
+    let list = ListBox::builder()
+        .selection_mode(SelectionMode::None)
+        .css_classes(vec!["boxed-list".to_string()])
+        .valign(Align::Start)
+        .margin_top(5)
+        .margin_end(5)
+        .margin_bottom(5)
+        .margin_start(5)
+        .build();
+    let list_scrolled = ScrolledWindow::builder()
+        .child(&list)
+        .min_content_height(250)
+        .vexpand_set(true)
+        .vexpand(true)
+        .build();
+    let add_rule = editor(&global_file(), &list);
+
+    // The presets menu, and the button to add a rule.
+    let presets_box = Box::builder()
+        .orientation(Orientation::Vertical)
+        .spacing(2)
+        .build();
+    let presets_popover = Popover::builder().child(&presets_box).build();
+    for (name, rules) in presets() {
+        let button = Button::builder()
+            .label(&name)
+            .css_classes(vec!["flat".to_string()])
+            .build();
+        button.connect_clicked(
+            glib::clone!(@strong add_rule, @weak presets_popover => move |_| {
+                for rule in rules {
+                    add_rule(Some(rule));
+                }
+                presets_popover.popdown();
+            }),
+        );
+        presets_box.append(&button);
+    }
+    let presets_button = MenuButton::builder()
+        .label(&tr::tr!("Add Preset"))
+        .popover(&presets_popover)
+        .build();
+    let add_button = Button::builder()
+        .icon_name("list-add-symbolic")
+        .halign(Align::End)
+        .hexpand_set(true)
+        .hexpand(true)
+        .build();
+    add_button.connect_clicked(glib::clone!(@strong add_rule => move |_| add_rule(None)));
+    let header = Box::builder()
+        .orientation(Orientation::Horizontal)
+        .spacing(10)
+        .build();
+    header.append(&presets_button);
+    header.append(&add_button);
+
+    let content = Box::builder()
+        .orientation(Orientation::Vertical)
+        .spacing(10)
+        .build();
+    content.append(&header);
+    content.append(&list_scrolled);
+
+    let dialog = MessageDialog::builder()
+        .heading(&tr::tr!("Global Exclusions"))
+        .body(&tr::tr!(
+            "These exclusions apply to every sync directory, and use the same syntax as '.gitignore' files."
+        ))
+        .extra_child(&content)
+        .modal(true)
+        .resizable(true)
+        .build();
+    dialog.add_response("close", &tr::tr!("Close"));
+    dialog.connect_response(None, |dialog, _| dialog.close());
+    dialog.show();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    /// Parse a line that's expected to be a valid rule.
+    fn rule(line: &str) -> Rule {
+        parse_line(line).unwrap().unwrap()
+    }
+
+    /// Make a sync directory in a temporary directory, with the exclusion files
+    /// in `files` (a list of each file's path and content, relative to the
+    /// sync directory).
+    fn make_sync_dir(files: &[(&str, &str)], honor_gitignore: bool) -> (TempDir, SyncDirsModel) {
+        let dir = tempfile::tempdir().unwrap();
+        for (path, content) in files {
+            let path = dir.path().join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+        }
+
+        let sync_dir = SyncDirsModel {
+            id: 1,
+            remote_id: 1,
+            local_path: dir.path().to_str().unwrap().to_owned(),
+            remote_path: "Documents".to_owned(),
+            crypt_remote: None,
+            paused: false,
+            honor_gitignore,
+            max_size: None,
+            max_age: None,
+            file_types: String::new(),
+            skip_hidden: false,
+            symlinks: "skip".to_owned(),
+        };
+        (dir, sync_dir)
+    }
+
+    /// Exclusions without any global rules, to build sync directory exclusions
+    /// on top of.
+    fn no_global() -> Exclusions {
+        Exclusions {
+            files: vec![],
+            invalid: vec![],
+            read_files: Rc::new(RefCell::new(HashMap::new())),
+        }
+    }
+
+    #[test]
+    fn parse_blank_and_comments() {
+        assert!(parse_line("").unwrap().is_none());
+        assert!(parse_line("   ").unwrap().is_none());
+        assert!(parse_line("# a comment").unwrap().is_none());
+    }
+
+    #[test]
+    fn parse_flags() {
+        let plain = rule("*.log  ");
+        assert!(!plain.negated && !plain.dir_only && !plain.anchored);
+        assert_eq!(plain.pattern.as_str(), "*.log");
+
+        let negated = rule("!keep.log");
+        assert!(negated.negated);
+        assert_eq!(negated.pattern.as_str(), "keep.log");
+
+        let dir_only = rule("build/");
+        assert!(dir_only.dir_only && !dir_only.anchored);
+        assert_eq!(dir_only.pattern.as_str(), "build");
+
+        let anchored = rule("/build");
+        assert!(anchored.anchored);
+        assert_eq!(anchored.pattern.as_str(), "build");
+        assert!(rule("docs/*.tmp").anchored);
+    }
+
+    #[test]
+    fn parse_escapes() {
+        let hash = rule("\\#notes#");
+        assert!(!hash.negated);
+        assert_eq!(hash.pattern.as_str(), "#notes#");
+
+        let bang = rule("\\!important");
+        assert!(!bang.negated);
+        assert_eq!(bang.pattern.as_str(), "!important");
+    }
+
+    #[test]
+    fn parse_invalid() {
+        assert!(parse_line("/").is_err());
+        assert!(parse_line("!/").is_err());
+        assert!(parse_line("[abc").is_err());
+        assert!(parse_line("a***b").is_err());
+    }
+
+    #[test]
+    fn parse_reports_line_numbers() {
+        let (rules, invalid) = parse("*.log\n\n# comment\n[abc\ntarget/\n/\n");
+        assert_eq!(rules.len(), 2);
+        assert_eq!(
+            invalid
+                .iter()
+                .map(|(line, pattern, _)| (*line, pattern.as_str()))
+                .collect::<Vec<_>>(),
+            vec![(4, "[abc"), (6, "/")]
+        );
+    }
+
+    #[test]
+    fn unanchored_rules_match_names() {
+        let build = rule("build");
+        assert!(build.matches("build", true));
+        assert!(build.matches("src/build", true));
+        assert!(build.matches("src/build", false));
+        assert!(!build.matches("src/builds", false));
+
+        let log = rule("*.log");
+        assert!(log.matches("a/b/c.log", false));
+        assert!(!log.matches("a.log/c", false));
+    }
+
+    #[test]
+    fn anchored_rules_match_paths() {
+        let root_build = rule("/build");
+        assert!(root_build.matches("build", true));
+        assert!(!root_build.matches("src/build", true));
+
+        // '*' doesn't match across folders, but '**' does.
+        let tmp = rule("docs/*.tmp");
+        assert!(tmp.matches("docs/a.tmp", false));
+        assert!(!tmp.matches("docs/sub/a.tmp", false));
+        assert!(!tmp.matches("other/docs/a.tmp", false));
+
+        let all_tmp = rule("docs/**/*.tmp");
+        assert!(all_tmp.matches("docs/a.tmp", false));
+        assert!(all_tmp.matches("docs/sub/a.tmp", false));
+    }
+
+    #[test]
+    fn dir_only_rules() {
+        let target = rule("target/");
+        assert!(target.matches("target", true));
+        assert!(target.matches("crate/target", true));
+        assert!(!target.matches("target", false));
+    }
+
+    #[test]
+    fn escaped_rules_match_literally() {
+        assert!(rule("\\#notes#").matches("#notes#", false));
+        assert!(rule("\\!important").matches("!important", false));
+    }
+
+    #[test]
+    fn negation() {
+        let (_dir, sync_dir) = make_sync_dir(&[(FILE_IGNORE_NAME, "*.log\n!keep.log\n")], false);
+        let exclusions = no_global().for_dir(&sync_dir, "");
+
+        assert!(exclusions.is_excluded("debug.log", false));
+        assert!(exclusions.is_excluded("logs/debug.log", false));
+        assert!(!exclusions.is_excluded("keep.log", false));
+        assert!(!exclusions.is_excluded("logs/keep.log", false));
+        assert!(!exclusions.is_excluded("notes.txt", false));
+    }
+
+    #[test]
+    fn later_rules_take_priority() {
+        let (_dir, sync_dir) = make_sync_dir(&[(FILE_IGNORE_NAME, "!keep.log\n*.log\n")], false);
+        let exclusions = no_global().for_dir(&sync_dir, "");
+
+        assert!(exclusions.is_excluded("keep.log", false));
+    }
+
+    #[test]
+    fn nested_files_take_priority() {
+        let (_dir, sync_dir) = make_sync_dir(
+            &[
+                (FILE_IGNORE_NAME, "*.log\n/top.txt\n"),
+                (&format!("logs/{FILE_IGNORE_NAME}"), "!keep.log\n/top.txt\n"),
+            ],
+            false,
+        );
+        let root = no_global().for_dir(&sync_dir, "");
+        let logs = root.for_dir(&sync_dir, "logs");
+        let other = root.for_dir(&sync_dir, "other");
+
+        assert!(!logs.is_excluded("logs/keep.log", false));
+        assert!(logs.is_excluded("logs/debug.log", false));
+        assert!(other.is_excluded("other/keep.log", false));
+
+        // Anchored rules are relative to the directory of the file they're in.
+        assert!(root.is_excluded("top.txt", false));
+        assert!(!root.is_excluded("other/top.txt", false));
+        assert!(logs.is_excluded("logs/top.txt", false));
+    }
+
+    #[test]
+    fn gitignore() {
+        let files = [
+            (GITIGNORE_NAME, "*.o\n*.log\n"),
+            (FILE_IGNORE_NAME, "!debug.log\n"),
+        ];
+
+        let (_dir, sync_dir) = make_sync_dir(&files, false);
+        let exclusions = no_global().for_dir(&sync_dir, "");
+        assert!(!exclusions.is_excluded("main.o", false));
+
+        // Celeste's own exclusion files can re-include what Git ignores.
+        let (_dir, sync_dir) = make_sync_dir(&files, true);
+        let exclusions = no_global().for_dir(&sync_dir, "");
+        assert!(exclusions.is_excluded("main.o", false));
+        assert!(exclusions.is_excluded("trace.log", false));
+        assert!(!exclusions.is_excluded("debug.log", false));
+    }
+
+    #[test]
+    fn invalid_lines_are_reported_once() {
+        let (dir, sync_dir) = make_sync_dir(&[(FILE_IGNORE_NAME, "*.log\n[abc\n")], false);
+        let global = no_global();
+        let exclusions = global.for_dir(&sync_dir, "");
+
+        assert_eq!(exclusions.invalid().len(), 1);
+        let invalid = &exclusions.invalid()[0];
+        assert_eq!(
+            invalid.file,
+            format!("{}/{FILE_IGNORE_NAME}", dir.path().display())
+        );
+        assert_eq!(invalid.line, 2);
+        assert_eq!(invalid.pattern, "[abc");
+
+        // The valid lines still apply, and the file isn't read (or reported) again.
+        assert!(exclusions.is_excluded("debug.log", false));
+        let again = global.for_dir(&sync_dir, "");
+        assert!(again.invalid().is_empty());
+        assert!(again.is_excluded("debug.log", false));
+    }
+}
@@ -0,0 +1,267 @@
+//! Size, age, and file type filters for sync directories. Unlike exclusions,
+//! these are stored with each sync directory in the database.
+use crate::{
+    entities::{SyncDirsActiveModel, SyncDirsColumn, SyncDirsEntity, SyncDirsModel},
+    rclone::RcloneItemFilter,
+    symlinks::{self, SymlinkPolicy},
+    util,
+};
+use adw::{
+    glib,
+    gtk::{Align, Box, Inhibit, Label, ListBox, Orientation, SelectionMode, SpinButton, Switch},
+    prelude::*,
+    ActionRow, EntryRow,
+};
+use sea_orm::{entity::prelude::*, ActiveValue, DatabaseConnection};
+use time::OffsetDateTime;
+
+/// The number of bytes in a megabyte, as used by the size filter's UI.
+const BYTES_PER_MB: u64 = 1024 * 1024;
+
+/// The number of seconds in a day, as used by the age filter's UI.
+const SECONDS_PER_DAY: u64 = 60 * 60 * 24;
+
+/// The filters set on a sync directory.
+#[derive(Clone, Debug, Default)]
+pub struct Filters {
+    /// The largest files to sync, in bytes.
+    pub max_size: Option<u64>,
+    /// The oldest files to sync, in seconds since they were last modified.
+    pub max_age: Option<u64>,
+    /// The file extensions to sync, in lowercase and without a leading '.'. If
+    /// this is empty, files of every type are synced.
+    pub file_types: Vec<String>,
+    /// Whether to skip hidden files and folders (those whose names start with
+    /// '.').
+    pub skip_hidden: bool,
+}
+
+impl Filters {
+    /// Get the filters set on a sync directory.
+    pub fn from_sync_dir(sync_dir: &SyncDirsModel) -> Self {
+        Self {
+            max_size: sync_dir.max_size.and_then(|size| size.try_into().ok()),
+            max_age: sync_dir.max_age.and_then(|age| age.try_into().ok()),
+            file_types: parse_file_types(&sync_dir.file_types),
+            skip_hidden: sync_dir.skip_hidden,
+        }
+    }
+
+    /// Check if an item should be skipped. `size` and `mod_time` are only
+    /// checked for files.
+    pub fn is_filtered(&self, name: &str, is_dir: bool, size: u64, mod_time: i64) -> bool {
+        if self.skip_hidden && name.starts_with('.') {
+            return true;
+        }
+
+        if is_dir {
+            return false;
+        }
+
+        if let Some(max_size) = self.max_size
+            && size > max_size
+        {
+            return true;
+        }
+
+        if let Some(max_age) = self.max_age {
+            let age = OffsetDateTime::now_utc().unix_timestamp() - mod_time;
+            if age > 0 && age as u64 > max_age {
+                return true;
+            }
+        }
+
+        if !self.file_types.is_empty() {
+            let extension = match name.rsplit_once('.') {
+                Some((stem, extension)) if !stem.is_empty() => extension.to_lowercase(),
+                _ => return true,
+            };
+
+            if !self.file_types.contains(&extension) {
+                return true;
+            }
+        }
+
+        false
+    }
+
+    /// Get the filters that Rclone can apply itself while listing a remote
+    /// directory, so filtered files don't have to be sent over at all.
+    pub fn rclone_filter(&self) -> RcloneItemFilter {
+        RcloneItemFilter {
+            max_size: self.max_size,
+            max_age: self.max_age,
+        }
+    }
+}
+
+/// Parse a list of file types, as entered by the user (i.e. `pdf, .docx`).
+pub fn parse_file_types(file_types: &str) -> Vec<String> {
+    file_types
+        .split([',', ' '])
+        .map(|file_type| file_type.trim().trim_start_matches('.').to_lowercase())
+        .filter(|file_type| !file_type.is_empty())
+        .collect()
+}
+
+/// Update a sync directory in the database. Nothing happens if the directory
+/// has since been removed.
+pub fn update_sync_dir<F: FnOnce(&mut SyncDirsActiveModel)>(
+    db: &DatabaseConnection,
+    local_path: &str,
+    remote_path: &str,
+    f: F,
+) {
+    let sync_dir = util::await_future(
+        SyncDirsEntity::find()
+            .filter(SyncDirsColumn::LocalPath.eq(local_path))
+            .filter(SyncDirsColumn::RemotePath.eq(remote_path))
+            .one(db),
+    )
+    .unwrap();
+
+    if let Some(sync_dir) = sync_dir {
+        let mut active_model: SyncDirsActiveModel = sync_dir.into();
+        f(&mut active_model);
+        util::await_future(active_model.update(db)).unwrap();
+    }
+}
+
+/// Generate the section for editing a sync directory's filters. Changes are
+/// saved straight away, and get picked up on the next sync pass.
+pub fn section(db: &DatabaseConnection, local_path: &str, remote_path: &str) -> Box {
+    let sync_dir = util::await_future(
+        SyncDirsEntity::find()
+            .filter(SyncDirsColumn::LocalPath.eq(local_path))
+            .filter(SyncDirsColumn::RemotePath.eq(remote_path))
+            .one(db),
+    )
+    .unwrap();
+    let filters = sync_dir
+        .as_ref()
+        .map(Filters::from_sync_dir)
+        .unwrap_or_default();
+    let symlink_policy = sync_dir
+        .as_ref()
+        .map(SymlinkPolicy::from_sync_dir)
+        .unwrap_or_default();
+    let local_path = local_path.to_owned();
+    let remote_path = remote_path.to_owned();
+
+    let section = Box::builder()
+        .orientation(Orientation::Vertical)
+        .margin_top(20)
+        .build();
+    let label = Label::builder()
+        .label(&tr::tr!("Filters"))
+        .halign(Align::Start)
+        .margin_bottom(10)
+        .css_classes(vec!["heading".to_string()])
+        .build();
+    let list = ListBox::builder()
+        .selection_mode(SelectionMode::None)
+        .css_classes(vec!["boxed-list".to_string()])
+        .margin_top(5)
+        .margin_end(5)
+        .margin_bottom(5)
+        .margin_start(5)
+        .build();
+
+    // Hidden files.
+    let hidden_switch = Switch::builder()
+        .active(filters.skip_hidden)
+        .valign(Align::Center)
+        .build();
+    hidden_switch.connect_state_set(
+        glib::clone!(@strong db, @strong local_path, @strong remote_path => move |_, state| {
+            update_sync_dir(&db, &local_path, &remote_path, |sync_dir| {
+                sync_dir.skip_hidden = ActiveValue::Set(state);
+            });
+            Inhibit(false)
+        }),
+    );
+    let hidden_row = ActionRow::builder()
+        .title(&tr::tr!("Skip hidden files"))
+        .activatable_widget(&hidden_switch)
+        .build();
+    hidden_row.add_suffix(&hidden_switch);
+
+    // The largest files to sync, in megabytes.
+    let size_spin = SpinButton::with_range(0.0, 1_000_000.0, 1.0);
+    size_spin.set_valign(Align::Center);
+    size_spin.set_value(
+        filters
+            .max_size
+            .map_or(0.0, |size| (size / BYTES_PER_MB) as f64),
+    );
+    size_spin.connect_value_changed(
+        glib::clone!(@strong db, @strong local_path, @strong remote_path => move |spin| {
+            let max_size = match spin.value_as_int() {
+                0 => None,
+                size => Some(i64::from(size) * BYTES_PER_MB as i64),
+            };
+            update_sync_dir(&db, &local_path, &remote_path, |sync_dir| {
+                sync_dir.max_size = ActiveValue::Set(max_size);
+            });
+        }),
+    );
+    let size_row = ActionRow::builder()
+        .title(&tr::tr!("Skip files larger than (MB)"))
+        .subtitle(&tr::tr!("Set to 0 to sync files of any size."))
+        .build();
+    size_row.add_suffix(&size_spin);
+
+    // The oldest files to sync, in days.
+    let age_spin = SpinButton::with_range(0.0, 36_500.0, 1.0);
+    age_spin.set_valign(Align::Center);
+    age_spin.set_value(
+        filters
+            .max_age
+            .map_or(0.0, |age| (age / SECONDS_PER_DAY) as f64),
+    );
+    age_spin.connect_value_changed(
+        glib::clone!(@strong db, @strong local_path, @strong remote_path => move |spin| {
+            let max_age = match spin.value_as_int() {
+                0 => None,
+                age => Some(i64::from(age) * SECONDS_PER_DAY as i64),
+            };
+            update_sync_dir(&db, &local_path, &remote_path, |sync_dir| {
+                sync_dir.max_age = ActiveValue::Set(max_age);
+            });
+        }),
+    );
+    let age_row = ActionRow::builder()
+        .title(&tr::tr!("Skip files older than (days)"))
+        .subtitle(&tr::tr!("Set to 0 to sync files of any age."))
+        .build();
+    age_row.add_suffix(&age_spin);
+
+    // The file types to sync.
+    let types_row = EntryRow::builder()
+        .title(&tr::tr!("Only sync these file types (i.e. 'pdf, docx')"))
+        .show_apply_button(true)
+        .build();
+    types_row.set_text(&filters.file_types.join(", "));
+    types_row.connect_apply(
+        glib::clone!(@strong db, @strong local_path, @strong remote_path => move |row| {
+            let file_types = parse_file_types(&row.text()).join(",");
+            update_sync_dir(&db, &local_path, &remote_path, |sync_dir| {
+                sync_dir.file_types = ActiveValue::Set(file_types);
+            });
+        }),
+    );
+
+    list.append(&hidden_row);
+    list.append(&size_row);
+    list.append(&age_row);
+    list.append(&types_row);
+    list.append(&symlinks::row(
+        db,
+        &local_path,
+        &remote_path,
+        symlink_policy,
+    ));
+    section.append(&label);
+    section.append(&list);
+    section
+}
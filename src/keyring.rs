@@ -0,0 +1,234 @@
+//! Storage of remote credentials in the freedesktop Secret Service (i.e. GNOME
+//! Keyring or KWallet).
+use crate::util;
+use adw::{
+    gio,
+    glib::{self, variant::ObjectPath, ToVariant, Variant},
+};
+use std::{cell::Cell, collections::HashMap, rc::Rc};
+
+/// The bus name of the Secret Service.
+const SECRETS_NAME: &str = "org.freedesktop.secrets";
+
+/// The object path of the Secret Service.
+const SERVICE_PATH: &str = "/org/freedesktop/secrets";
+
+/// The object path of the user's default collection.
+const DEFAULT_COLLECTION_PATH: &str = "/org/freedesktop/secrets/aliases/default";
+
+// The D-Bus interfaces used by the Secret Service.
+const SERVICE_INTERFACE: &str = "org.freedesktop.Secret.Service";
+const COLLECTION_INTERFACE: &str = "org.freedesktop.Secret.Collection";
+const ITEM_INTERFACE: &str = "org.freedesktop.Secret.Item";
+const PROMPT_INTERFACE: &str = "org.freedesktop.Secret.Prompt";
+const SESSION_INTERFACE: &str = "org.freedesktop.Secret.Session";
+
+/// How long to wait on a call to the Secret Service before giving up on it, in
+/// milliseconds. Credentials are looked up from the main thread, so a keyring
+/// that's stopped responding mustn't be able to hang Celeste.
+const CALL_TIMEOUT: i32 = 5000;
+
+/// Get the attributes used to find the keyring item for a remote.
+fn attributes(remote: &str) -> HashMap<String, String> {
+    HashMap::from([
+        ("application".to_owned(), util::APP_ID.to_owned()),
+        ("remote".to_owned(), remote.to_owned()),
+    ])
+}
+
+/// An open session with the Secret Service.
+struct Service {
+    conn: gio::DBusConnection,
+    session: ObjectPath,
+}
+
+impl Service {
+    /// Connect to the Secret Service and open a new session.
+    fn connect() -> Result<Self, glib::Error> {
+        let conn = gio::bus_get_sync(gio::BusType::Session, None::<&gio::Cancellable>)?;
+
+        // Secrets only ever travel over the session bus, so we don't bother with
+        // encrypting them in transit.
+        let resp = conn.call_sync(
+            Some(SECRETS_NAME),
+            SERVICE_PATH,
+            SERVICE_INTERFACE,
+            "OpenSession",
+            Some(&("plain", "".to_variant()).to_variant()),
+            None,
+            gio::DBusCallFlags::NONE,
+            CALL_TIMEOUT,
+            None::<&gio::Cancellable>,
+        )?;
+        let session = resp.child_value(1).get::<ObjectPath>().unwrap();
+
+        Ok(Self { conn, session })
+    }
+
+    /// Call a method on an object owned by the Secret Service.
+    fn call(
+        &self,
+        path: &str,
+        interface: &str,
+        method: &str,
+        args: Option<&Variant>,
+    ) -> Result<Variant, glib::Error> {
+        self.conn.call_sync(
+            Some(SECRETS_NAME),
+            path,
+            interface,
+            method,
+            args,
+            None,
+            gio::DBusCallFlags::NONE,
+            CALL_TIMEOUT,
+            None::<&gio::Cancellable>,
+        )
+    }
+
+    /// Show a prompt returned from the Secret Service (i.e. to unlock the
+    /// keyring), and wait until the user is finished with it. Returns an error
+    /// if the user dismissed the prompt.
+    fn prompt(&self, prompt: &ObjectPath) -> Result<(), glib::Error> {
+        // A path of '/' means no prompt is needed.
+        if prompt.as_str() == "/" {
+            return Ok(());
+        }
+
+        let dismissed: Rc<Cell<Option<bool>>> = Rc::new(Cell::new(None));
+        let subscription = self.conn.signal_subscribe(
+            Some(SECRETS_NAME),
+            Some(PROMPT_INTERFACE),
+            Some("Completed"),
+            Some(prompt.as_str()),
+            None,
+            gio::DBusSignalFlags::NONE,
+            glib::clone!(@strong dismissed => move |_, _, _, _, _, params| {
+                dismissed.set(Some(params.child_value(0).get::<bool>().unwrap_or(true)));
+            }),
+        );
+        self.call(
+            prompt,
+            PROMPT_INTERFACE,
+            "Prompt",
+            Some(&("",).to_variant()),
+        )?;
+
+        let context = glib::MainContext::default();
+        let was_dismissed = loop {
+            if let Some(was_dismissed) = dismissed.get() {
+                break was_dismissed;
+            }
+            context.iteration(true);
+        };
+        self.conn.signal_unsubscribe(subscription);
+
+        if was_dismissed {
+            Err(glib::Error::new(
+                gio::IOErrorEnum::Cancelled,
+                "The keyring prompt was dismissed",
+            ))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Unlock the given objects, prompting the user if needed.
+    fn unlock(&self, objects: Vec<ObjectPath>) -> Result<(), glib::Error> {
+        let resp = self.call(
+            SERVICE_PATH,
+            SERVICE_INTERFACE,
+            "Unlock",
+            Some(&(objects,).to_variant()),
+        )?;
+        self.prompt(&resp.child_value(1).get().unwrap())
+    }
+
+    /// Find the keyring items for a remote, unlocking any that are locked.
+    fn find_items(&self, remote: &str) -> Result<Vec<ObjectPath>, glib::Error> {
+        let resp = self.call(
+            SERVICE_PATH,
+            SERVICE_INTERFACE,
+            "SearchItems",
+            Some(&(attributes(remote),).to_variant()),
+        )?;
+        let unlocked: Vec<ObjectPath> = resp.child_value(0).get().unwrap();
+        let locked: Vec<ObjectPath> = resp.child_value(1).get().unwrap();
+
+        if !locked.is_empty() {
+            self.unlock(locked.clone())?;
+        }
+
+        Ok(unlocked.into_iter().chain(locked).collect())
+    }
+}
+
+impl Drop for Service {
+    fn drop(&mut self) {
+        let _ = self.call(&self.session, SESSION_INTERFACE, "Close", None);
+    }
+}
+
+/// Store the credentials for a remote in the keyring, replacing any that were
+/// already stored for it.
+pub fn store(remote: &str, secrets: &HashMap<String, String>) -> Result<(), glib::Error> {
+    let service = Service::connect()?;
+    service.unlock(vec![ObjectPath::try_from(DEFAULT_COLLECTION_PATH).unwrap()])?;
+
+    let properties: HashMap<String, Variant> = HashMap::from([
+        (
+            "org.freedesktop.Secret.Item.Label".to_owned(),
+            tr::tr!("Celeste credentials for '{}'", remote).to_variant(),
+        ),
+        (
+            "org.freedesktop.Secret.Item.Attributes".to_owned(),
+            attributes(remote).to_variant(),
+        ),
+    ]);
+    let secret = (
+        service.session.clone(),
+        Vec::<u8>::new(),
+        serde_json::to_vec(secrets).unwrap(),
+        "application/json".to_owned(),
+    );
+    let resp = service.call(
+        DEFAULT_COLLECTION_PATH,
+        COLLECTION_INTERFACE,
+        "CreateItem",
+        Some(&(properties, secret, true).to_variant()),
+    )?;
+
+    service.prompt(&resp.child_value(1).get().unwrap())
+}
+
+/// Get the credentials stored in the keyring for a remote. Returns [`None`] if
+/// none are stored.
+pub fn lookup(remote: &str) -> Result<Option<HashMap<String, String>>, glib::Error> {
+    let service = Service::connect()?;
+    let item = match service.find_items(remote)?.into_iter().next() {
+        Some(item) => item,
+        None => return Ok(None),
+    };
+
+    let resp = service.call(
+        &item,
+        ITEM_INTERFACE,
+        "GetSecret",
+        Some(&(service.session.clone(),).to_variant()),
+    )?;
+    let value: Vec<u8> = resp.child_value(0).child_value(2).get().unwrap();
+
+    Ok(serde_json::from_slice(&value).ok())
+}
+
+/// Delete the credentials stored in the keyring for a remote.
+pub fn delete(remote: &str) -> Result<(), glib::Error> {
+    let service = Service::connect()?;
+
+    for item in service.find_items(remote)? {
+        let resp = service.call(&item, ITEM_INTERFACE, "Delete", None)?;
+        service.prompt(&resp.child_value(0).get().unwrap())?;
+    }
+
+    Ok(())
+}
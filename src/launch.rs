@@ -1,47 +1,56 @@
 use crate::{
+    activity::{self, ActivityKind},
+    browser,
+    collisions::{self, NameRules},
+    dbus,
     entities::{
         RemotesColumn, RemotesEntity, RemotesModel, SyncDirsActiveModel, SyncDirsColumn,
-        SyncDirsEntity, SyncDirsModel, SyncItemsActiveModel, SyncItemsColumn, SyncItemsEntity,
+        SyncDirsEntity, SyncDirsModel, SyncErrorsActiveModel, SyncErrorsColumn, SyncErrorsEntity,
+        SyncErrorsModel, SyncItemsActiveModel, SyncItemsColumn, SyncItemsEntity,
     },
+    exclude,
+    filters::{self, Filters},
     gtk_util,
     login::{self},
-    migrations::{Migrator, MigratorTrait},
-    rclone::{self, RcloneListFilter},
+    moves,
+    names::{self, NameLimits},
+    rclone::{self, RcloneErrorKind, RcloneListFilter},
+    selective::{self, Selection},
+    status::{DirState, DirStatus, Progress, RemoteConnection, RemoteStatus, Status},
+    symlinks::{self, SymlinkPolicy},
     traits::prelude::*,
-    util,
+    util, versions,
 };
 use adw::{
-    glib,
+    gio, glib,
     gtk::{
-        pango::EllipsizeMode, Align, Box, Button, ButtonsType, Entry, EntryCompletion,
+        pango::EllipsizeMode, Align, Box, Button, ButtonsType, CheckButton, Entry,
         FileChooserDialog, FileFilter, GestureClick, Image, Inhibit, Label, ListBox, ListBoxRow,
-        ListStore, MessageDialog, Orientation, PolicyType, Popover, PositionType, ResponseType,
+        MessageDialog, Orientation, PasswordEntry, PolicyType, Popover, PositionType, ResponseType,
         ScrolledWindow, SelectionMode, Separator, Spinner, Stack, StackSidebar,
-        StackTransitionType, Widget,
+        StackTransitionType, Switch, Widget,
     },
     prelude::*,
-    Application, ApplicationWindow, Bin, EntryRow, HeaderBar, Leaflet, LeafletTransitionType,
-    WindowTitle,
+    Application, ApplicationWindow, Bin, HeaderBar, Leaflet, LeafletTransitionType, WindowTitle,
 };
-use file_lock::{FileLock, FileOptions};
 use indexmap::IndexMap;
-use sea_orm::{entity::prelude::*, ActiveValue, Database, DatabaseConnection};
+use sea_orm::{entity::prelude::*, ActiveValue, DatabaseConnection};
 
 use std::{
     boxed,
-    cell::RefCell,
-    collections::HashMap,
-    fs::{self, File, OpenOptions},
-    io::Write,
-    path::{Path, PathBuf},
+    cell::{Cell, RefCell},
+    collections::{HashMap, HashSet},
+    fs::{self, File},
+    path::Path,
     rc::Rc,
     sync::{Arc, Mutex},
     thread,
-    time::{Duration, SystemTime},
+    time::{Duration, Instant, SystemTime},
 };
+use time::OffsetDateTime;
 
 // The location for file ignore lists.
-static FILE_IGNORE_NAME: &str = ".sync-exclude.lst";
+pub static FILE_IGNORE_NAME: &str = ".sync-exclude.lst";
 
 // A [`HashMap`] containing the status and progress for a directory sync label.
 // This is done here because if we try to get the child from a `Box` or
@@ -56,6 +65,49 @@ type RemoteDeletionQueue = Rc<RefCell<Vec<String>>>;
 // occurring.
 type SyncDirDeletionQueue = Rc<RefCell<Vec<(String, String, String)>>>;
 
+// A [`HashMap`] containing the [`RemoteItem`] for each remote.
+type RemoteMap = Rc<RefCell<HashMap<String, RemoteItem>>>;
+
+/// How long to wait before first trying to reconnect to a disconnected remote.
+const RETRY_DELAY_MIN: Duration = Duration::from_secs(5);
+
+/// The longest we'll wait between attempts to reconnect to a disconnected
+/// remote.
+const RETRY_DELAY_MAX: Duration = Duration::from_secs(300);
+
+/// The reasons a remote can't currently be synced.
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum RemoteState {
+    /// The remote's login details are no longer valid (i.e. from an expired or
+    /// revoked OAuth token), and the user needs to sign in again.
+    NeedsSignIn,
+    /// The remote couldn't be reached (i.e. from the network being down or DNS
+    /// lookups failing), and we're waiting to try connecting to it again.
+    Disconnected,
+}
+
+/// A struct representing the sync state of a remote, along with the UI used to
+/// report it.
+struct RemoteItem {
+    /// Why this remote can't currently be synced, or [`None`] if it can be.
+    state: Option<RemoteState>,
+    /// The banner at the top of the remote's page, shown when [`Self::state`]
+    /// is set.
+    banner: Box,
+    /// The label in [`Self::banner`] describing the issue.
+    banner_label: Label,
+    /// The button in [`Self::banner`] used to resolve the issue.
+    banner_button: Button,
+    /// How long to wait before the next attempt to reconnect to the remote.
+    retry_delay: Duration,
+    /// When the next attempt to reconnect to the remote is due.
+    retry_at: Instant,
+    /// A closure to add a sync directory to the remote's UI. The arguments are
+    /// the remote name, the local path, the remote path, and whether the
+    /// directory is encrypted.
+    add_dir: Rc<dyn Fn(String, String, String, bool)>,
+}
+
 /// The errors that can be found while syncing.
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 enum SyncError {
@@ -65,9 +117,109 @@ enum SyncError {
     /// An error when both the local and remote file are more current than at
     /// the last sync. A tuple of the local and remote file.
     BothMoreCurrent(String, String),
+    /// An error when an item's name collides with another item's under the
+    /// remote's rules for comparing names (see [`collisions::NameRules`]). A
+    /// tuple of the local item that was skipped, and the local or remote item
+    /// it collides with.
+    NameCollision(String, String),
+    /// An error when a local item's name isn't accepted by the remote (see
+    /// [`names::NameLimits`]). A tuple of the local item, and why its name
+    /// isn't accepted.
+    InvalidName(String, String),
 }
 
 impl SyncError {
+    /// Get the name of this error's kind, as stored in the database.
+    fn kind(&self) -> &'static str {
+        match self {
+            SyncError::General(_, _) => "general",
+            SyncError::BothMoreCurrent(_, _) => "both_more_current",
+            SyncError::NameCollision(_, _) => "name_collision",
+            SyncError::InvalidName(_, _) => "invalid_name",
+        }
+    }
+
+    /// Get the local path, remote path, and message stored in the database for
+    /// this error.
+    fn db_fields(&self) -> (String, String, String) {
+        match self {
+            SyncError::General(file_path, err) => (file_path.clone(), String::new(), err.clone()),
+            SyncError::BothMoreCurrent(local_path, remote_path) => {
+                (local_path.clone(), remote_path.clone(), String::new())
+            }
+            SyncError::NameCollision(local_path, other_path) => {
+                (local_path.clone(), other_path.clone(), String::new())
+            }
+            SyncError::InvalidName(local_path, problem) => {
+                (local_path.clone(), String::new(), problem.clone())
+            }
+        }
+    }
+
+    /// Convert an error stored in the database back into a [`SyncError`].
+    /// Returns [`None`] if the error's kind isn't known.
+    fn from_model(model: &SyncErrorsModel) -> Option<Self> {
+        match model.kind.as_str() {
+            "general" => Some(SyncError::General(
+                model.local_path.clone(),
+                model.message.clone(),
+            )),
+            "both_more_current" => Some(SyncError::BothMoreCurrent(
+                model.local_path.clone(),
+                model.remote_path.clone(),
+            )),
+            "name_collision" => Some(SyncError::NameCollision(
+                model.local_path.clone(),
+                model.remote_path.clone(),
+            )),
+            "invalid_name" => Some(SyncError::InvalidName(
+                model.local_path.clone(),
+                model.message.clone(),
+            )),
+            _ => None,
+        }
+    }
+
+    /// Find this error in the database for the given sync directory.
+    fn find_model(&self, db: &DatabaseConnection, sync_dir_id: i32) -> Option<SyncErrorsModel> {
+        let (local_path, remote_path, _) = self.db_fields();
+        let mut query = SyncErrorsEntity::find()
+            .filter(SyncErrorsColumn::SyncDirId.eq(sync_dir_id))
+            .filter(SyncErrorsColumn::Kind.eq(self.kind()))
+            .filter(SyncErrorsColumn::LocalPath.eq(local_path))
+            .filter(SyncErrorsColumn::RemotePath.eq(remote_path));
+
+        // General errors at the same path with different messages are still different
+        // errors.
+        if let SyncError::General(_, err) = self {
+            query = query.filter(SyncErrorsColumn::Message.eq(err.clone()));
+        }
+
+        util::await_future(query.one(db)).unwrap()
+    }
+
+    /// Get a message describing this error.
+    fn message(&self) -> String {
+        match self {
+            SyncError::General(file_path, err) => format!("{file_path}: {err}"),
+            SyncError::BothMoreCurrent(local_path, remote_path) => tr::tr!(
+                "Both '{}' and '{}' are more recent than at last sync.",
+                local_path,
+                remote_path
+            ),
+            SyncError::NameCollision(local_path, other_path) => tr::tr!(
+                "'{}' can't be synced alongside '{}', as their names only differ in case or Unicode normalization.",
+                local_path,
+                other_path
+            ),
+            SyncError::InvalidName(local_path, problem) => tr::tr!(
+                "'{}' can't be synced, as {}.",
+                local_path,
+                problem
+            ),
+        }
+    }
+
     fn generate_ui(&self) -> Box {
         let error_container = Box::builder()
             .orientation(Orientation::Vertical)
@@ -107,6 +259,41 @@ impl SyncError {
                     .build();
                 error_container.append(&err_label);
             }
+            SyncError::NameCollision(local_path, other_path) => {
+                let err_label = Label::builder()
+                    .label(&tr::tr!("'{}' collides with '{}'.", local_path, other_path))
+                    .halign(Align::Start)
+                    .ellipsize(EllipsizeMode::End)
+                    .build();
+                let help_label = Label::builder()
+                    .label(&tr::tr!(
+                        "Their names only differ in case or Unicode normalization. Click to rename it."
+                    ))
+                    .halign(Align::Start)
+                    .ellipsize(EllipsizeMode::End)
+                    .css_classes(vec!["caption".to_string(), "dim-label".to_string()])
+                    .build();
+                error_container.append(&err_label);
+                error_container.append(&help_label);
+            }
+            SyncError::InvalidName(local_path, problem) => {
+                let err_label = Label::builder()
+                    .label(local_path)
+                    .halign(Align::Start)
+                    .ellipsize(EllipsizeMode::End)
+                    .build();
+                let help_label = Label::builder()
+                    .label(&tr::tr!(
+                        "This item needs to be renamed, as {}. Click to rename it.",
+                        problem
+                    ))
+                    .halign(Align::Start)
+                    .ellipsize(EllipsizeMode::End)
+                    .css_classes(vec!["caption".to_string(), "dim-label".to_string()])
+                    .build();
+                error_container.append(&err_label);
+                error_container.append(&help_label);
+            }
         }
 
         error_container
@@ -132,6 +319,55 @@ struct SyncDir {
     error_items: HashMap<SyncError, Box>,
     /// A closure to update the UI error listing.
     update_error_ui: boxed::Box<dyn Fn()>,
+    /// The current sync state.
+    state: Cell<DirState>,
+    /// When the directory was last synced.
+    last_sync: Cell<Option<OffsetDateTime>>,
+    /// The progress of the current sync, if one is running.
+    progress: RefCell<Option<Progress>>,
+}
+
+impl SyncDir {
+    /// Set the sync state of this directory, and update its status icon to
+    /// match.
+    fn set_state(&self, state: DirState) {
+        let has_errors = !self.error_status_text.text().is_empty();
+
+        match state.icon_name(has_errors) {
+            Some(icon_name) => self.status_icon.set_child(Some(&get_image(icon_name))),
+            None => {
+                // The width/height and margins for this are based on those from
+                // `get_image()`, as they're placed at the same place in the UI.
+                let spinner = Spinner::builder()
+                    .spinning(true)
+                    .width_request(4)
+                    .height_request(4)
+                    .margin_start(3)
+                    .margin_end(3)
+                    .build();
+                self.status_icon.set_child(Some(&spinner));
+            }
+        }
+
+        if state == DirState::Synced {
+            self.last_sync.set(Some(OffsetDateTime::now_utc()));
+        }
+        if state != DirState::Syncing {
+            self.progress.replace(None);
+        }
+
+        self.state.set(state);
+        *(*STATUS_CHANGED).lock().unwrap() = true;
+    }
+
+    /// Record the progress of the current sync.
+    fn set_progress(&self, current_path: &str, items_checked: usize) {
+        self.progress.replace(Some(Progress {
+            current_path: current_path.to_owned(),
+            items_checked,
+        }));
+        *(*STATUS_CHANGED).lock().unwrap() = true;
+    }
 }
 
 lazy_static::lazy_static! {
@@ -139,6 +375,156 @@ lazy_static::lazy_static! {
     pub static ref CLOSE_REQUEST: Arc<Mutex<bool>> = Arc::new(Mutex::new(false));
     // A [`Mutex`] to keep track of open requests from the tray icon.
     pub static ref OPEN_REQUEST: Arc<Mutex<bool>> = Arc::new(Mutex::new(false));
+    // Whether a sync directory's state or progress has changed since the status
+    // was last written out (see [`write_status`]).
+    static ref STATUS_CHANGED: Mutex<bool> = Mutex::new(false);
+}
+
+/// How often changes to the status get written out. Progress changes with every
+/// item that gets checked, so writes are limited to this to keep the status
+/// file and D-Bus signals from being flooded.
+const STATUS_WRITE_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Set the state of a remote, and update its banner, sidebar entry, and sync
+/// directory statuses to match.
+fn set_remote_state(
+    stack: &Stack,
+    remote_map: &RemoteMap,
+    directory_map: &DirectoryMap,
+    remote_name: &str,
+    state: Option<RemoteState>,
+) {
+    let mut rmap = remote_map.get_mut_ref();
+    let remote_item = match rmap.get_mut(remote_name) {
+        Some(remote_item) => remote_item,
+        None => return,
+    };
+
+    if remote_item.state == state {
+        return;
+    }
+
+    let (title, status_text, dir_state) = match &state {
+        Some(RemoteState::NeedsSignIn) => {
+            remote_item.banner_label.set_label(&tr::tr!(
+                "The login details for this remote are no longer valid. Sign in again to continue syncing."
+            ));
+            remote_item.banner_button.set_label(&tr::tr!("Sign In"));
+            (
+                tr::tr!("{} (sign-in required)", remote_name),
+                tr::tr!("Syncing is paused until you sign in again."),
+                DirState::NeedsSignIn,
+            )
+        }
+        Some(RemoteState::Disconnected) => {
+            remote_item.banner_label.set_label(&tr::tr!(
+                "Unable to connect to this remote. Syncing will resume once it can be reached again."
+            ));
+            remote_item.banner_button.set_label(&tr::tr!("Retry Now"));
+            (
+                tr::tr!("{} (offline)", remote_name),
+                tr::tr!("Unable to connect to the remote. Waiting to try again..."),
+                DirState::Disconnected,
+            )
+        }
+        None => {
+            remote_item.retry_delay = RETRY_DELAY_MIN;
+            (
+                remote_name.to_owned(),
+                tr::tr!("Awaiting sync check..."),
+                DirState::Waiting,
+            )
+        }
+    };
+    remote_item.banner.set_visible(state.is_some());
+    remote_item.state = state;
+
+    if let Some(child) = stack.child_by_name(remote_name) {
+        stack.page(&child).set_title(Some(&title));
+    }
+
+    if let Some(dirs) = directory_map.get_ref().get(remote_name) {
+        for dir in dirs.values() {
+            dir.status_text.set_label(&status_text);
+            dir.set_state(dir_state);
+        }
+    }
+}
+
+/// Mark a remote as disconnected, and schedule the next attempt to reconnect to
+/// it. The delay between attempts doubles each time one fails, up to
+/// [`RETRY_DELAY_MAX`].
+fn set_remote_disconnected(
+    stack: &Stack,
+    remote_map: &RemoteMap,
+    directory_map: &DirectoryMap,
+    remote_name: &str,
+) {
+    {
+        let mut rmap = remote_map.get_mut_ref();
+        let remote_item = match rmap.get_mut(remote_name) {
+            Some(remote_item) => remote_item,
+            None => return,
+        };
+
+        if remote_item.state == Some(RemoteState::Disconnected) {
+            remote_item.retry_delay = (remote_item.retry_delay * 2).min(RETRY_DELAY_MAX);
+        } else {
+            remote_item.retry_delay = RETRY_DELAY_MIN;
+        }
+
+        remote_item.retry_at = Instant::now() + remote_item.retry_delay;
+    }
+
+    set_remote_state(
+        stack,
+        remote_map,
+        directory_map,
+        remote_name,
+        Some(RemoteState::Disconnected),
+    );
+}
+
+/// Write out the current status of each remote and sync directory, so the CLI
+/// and D-Bus interface can report on it.
+fn write_status(remote_map: &RemoteMap, directory_map: &DirectoryMap) {
+    let rmap = remote_map.get_ref();
+    let remotes = directory_map
+        .get_ref()
+        .iter()
+        .map(|(remote_name, dirs)| RemoteStatus {
+            name: remote_name.clone(),
+            connection: match rmap
+                .get(remote_name)
+                .and_then(|remote_item| remote_item.state.as_ref())
+            {
+                Some(RemoteState::NeedsSignIn) => RemoteConnection::NeedsSignIn,
+                Some(RemoteState::Disconnected) => RemoteConnection::Disconnected,
+                None => RemoteConnection::Connected,
+            },
+            dirs: dirs
+                .iter()
+                .map(|((local_path, remote_path), dir)| DirStatus {
+                    local_path: local_path.clone(),
+                    remote_path: remote_path.clone(),
+                    state: dir.state.get(),
+                    status: dir.status_text.text().to_string(),
+                    last_sync: dir.last_sync.get(),
+                    error_count: dir.error_items.len(),
+                    errors: dir
+                        .error_items
+                        .keys()
+                        .map(|error| error.message())
+                        .collect(),
+                    progress: dir.progress.borrow().clone(),
+                })
+                .collect(),
+        })
+        .collect();
+
+    let status = Status { remotes };
+    status.write();
+    dbus::set_status(&status);
 }
 
 /// Get an icon for use as the status icon for directory syncs.
@@ -151,48 +537,15 @@ fn get_image(icon_name: &str) -> Image {
 }
 
 pub fn launch(app: &Application, background: bool) {
-    // Create the configuration directory if it doesn't exist.
-    let config_path = util::get_config_dir();
-    if !config_path.exists()
-        && let Err(err) = fs::create_dir_all(&config_path)
-    {
-        gtk_util::show_error(
-            &tr::tr!("Unable to create Celeste's config directory [{}].", err),
-            None,
-        );
-        return;
-    }
-
-    // Create the database file if it doesn't exist.
-    let mut db_path = config_path;
-    db_path.push("celeste.db");
-    if !db_path.exists() {
-        if let Err(err) = fs::File::create(&db_path) {
-            gtk_util::show_error(
-                &tr::tr!("Unable to create Celeste's database file [{}].", err),
-                None,
-            );
+    // Open the database.
+    let db = match util::open_db() {
+        Ok(db) => db,
+        Err(err) => {
+            gtk_util::show_error(&err, None);
             return;
         }
     };
 
-    // Connect to the database.
-    let db = util::await_future(Database::connect(format!("sqlite://{}", db_path.display())));
-    if let Err(err) = &db {
-        gtk_util::show_error(&tr::tr!("Unable to connect to database [{}].", err), None);
-        return;
-    };
-    let db = db.unwrap();
-
-    // Run migrations.
-    if let Err(err) = util::await_future(Migrator::up(&db, None)) {
-        gtk_util::show_error(
-            &tr::tr!("Unable to run database migrations [{}]", err),
-            None,
-        );
-        return;
-    }
-
     // Get our remotes.
     let mut remotes = util::await_future(RemotesEntity::find().all(&db)).unwrap();
 
@@ -229,8 +582,32 @@ pub fn launch(app: &Application, background: bool) {
     // a queue so they can be processed when syncing is at a good point of stopping.
     let sync_dir_deletion_queue: SyncDirDeletionQueue = Rc::new(RefCell::new(vec![]));
 
+    // The sync state of each remote.
+    let remote_map: RemoteMap = Rc::new(RefCell::new(HashMap::new()));
+
+    // Write out status changes as they happen, so the CLI and D-Bus interface can
+    // follow along with syncs. This can run while syncing is in the middle of
+    // using the maps, in which case the write waits until the next check.
+    glib::timeout_add_local(
+        STATUS_WRITE_INTERVAL,
+        glib::clone!(@strong remote_map, @strong directory_map => move || {
+            let mut status_changed = (*STATUS_CHANGED).lock().unwrap();
+
+            if *status_changed
+                && remote_map.try_borrow().is_ok()
+                && directory_map.try_borrow().is_ok()
+            {
+                *status_changed = false;
+                drop(status_changed);
+                write_status(&remote_map, &directory_map);
+            }
+
+            glib::Continue(true)
+        }),
+    );
+
     // Add servers.
-    let gen_remote_window = glib::clone!(@strong window, @strong remote_deletion_queue, @strong sync_dir_deletion_queue, @strong directory_map, @strong db => move |remote: RemotesModel| {
+    let gen_remote_window = glib::clone!(@strong app, @strong window, @strong stack, @strong remote_map, @strong remote_deletion_queue, @strong sync_dir_deletion_queue, @strong directory_map, @strong db => move |remote: RemotesModel| {
         let remote_name = remote.name;
 
         // The stack containing the window of sync status', as well as extra information for each sync pair.
@@ -247,6 +624,59 @@ pub fn launch(app: &Application, background: bool) {
             .css_classes(vec!["background".to_string()])
             .build();
 
+        // The banner for reporting when this remote can't be synced.
+        let banner = Box::builder()
+            .orientation(Orientation::Horizontal)
+            .spacing(10)
+            .margin_bottom(10)
+            .visible(false)
+            .css_classes(vec!["card".to_string()])
+            .build();
+        let banner_label = Label::builder()
+            .halign(Align::Start)
+            .hexpand_set(true)
+            .hexpand(true)
+            .wrap(true)
+            .xalign(0.0)
+            .margin_top(10)
+            .margin_bottom(10)
+            .margin_start(10)
+            .build();
+        let banner_button = Button::builder()
+            .label(&tr::tr!("Sign In"))
+            .valign(Align::Center)
+            .margin_end(10)
+            .css_classes(vec!["suggested-action".to_string()])
+            .build();
+        banner_button.connect_clicked(glib::clone!(@weak app, @weak window, @weak stack, @strong remote_map, @strong directory_map, @strong remote_name => move |_| {
+            let state = match remote_map.get_ref().get(&remote_name) {
+                Some(remote_item) => remote_item.state.clone(),
+                None => return,
+            };
+
+            match state {
+                Some(RemoteState::NeedsSignIn) => {
+                    window.set_sensitive(false);
+
+                    if login::edit(&app, &remote_name) {
+                        set_remote_state(&stack, &remote_map, &directory_map, &remote_name, None);
+                    }
+
+                    window.set_sensitive(true);
+                }
+                // Try reconnecting on the next sync pass instead of waiting for the current delay to run out.
+                Some(RemoteState::Disconnected) => {
+                    if let Some(remote_item) = remote_map.get_mut_ref().get_mut(&remote_name) {
+                        remote_item.retry_at = Instant::now();
+                    }
+                }
+                None => (),
+            }
+        }));
+        banner.append(&banner_label);
+        banner.append(&banner_button);
+        page.append(&banner);
+
         // The list of directories to sync.
         let sync_dirs = ListBox::builder()
             .selection_mode(SelectionMode::None)
@@ -254,10 +684,11 @@ pub fn launch(app: &Application, background: bool) {
             .build();
 
         // Add a directory to the stack.
-        let add_dir = glib::clone!(@weak window, @weak sections, @weak page, @weak sync_dirs, @strong remote_name, @strong directory_map, @strong sync_dir_deletion_queue => move |
+        let add_dir = glib::clone!(@weak window, @weak sections, @weak page, @weak sync_dirs, @strong remote_name, @strong directory_map, @strong sync_dir_deletion_queue, @strong db => move |
             server_name: String,
             local_path: String,
             remote_path: String,
+            encrypted: bool,
         | {
             let server_name_owned = server_name.to_string();
             let formatted_local_path = util::fmt_home(&local_path);
@@ -279,6 +710,17 @@ pub fn launch(app: &Application, background: bool) {
                 sections.append(&local_label);
                 sections.append(&arrow);
                 sections.append(&remote_label);
+
+                if encrypted {
+                    let encrypted_icon = Image::builder()
+                        .icon_name("channel-secure-symbolic")
+                        .margin_start(5)
+                        .has_tooltip(true)
+                        .tooltip_text(&tr::tr!("Encrypted"))
+                        .build();
+                    sections.append(&encrypted_icon);
+                }
+
                 sections
             };
             let text_status_container = Box::builder().orientation(Orientation::Horizontal).build();
@@ -335,6 +777,9 @@ pub fn launch(app: &Application, background: bool) {
             let more_info_errors_list = ListBox::builder().selection_mode(SelectionMode::None).css_classes(vec!["boxed-list".to_string()]).margin_top(5).margin_end(5).margin_bottom(5).margin_start(5).build();
             let more_info_errors_list_scrolled = ScrolledWindow::builder().child(&more_info_errors_list).valign(Align::Start).visible(false).build();
 
+            // The size, age, and file type filters.
+            let more_info_filters = filters::section(&db, &local_path, &remote_path);
+
             // The exclusion list.
             let more_info_exclusions_header = Box::builder().orientation(Orientation::Horizontal).margin_top(20).margin_bottom(10).build();
             let more_info_exclusions_label = Label::builder()
@@ -349,96 +794,60 @@ pub fn launch(app: &Application, background: bool) {
                 .icon_name("list-add-symbolic")
                 .halign(Align::End)
                 .build();
+            let more_info_global_exclusions_button = Button::builder()
+                .icon_name("emblem-system-symbolic")
+                .has_tooltip(true)
+                .tooltip_text(&tr::tr!("Global exclusions"))
+                .halign(Align::End)
+                .margin_end(5)
+                .build();
+            more_info_global_exclusions_button.connect_clicked(|_| exclude::global_dialog());
             more_info_exclusions_header.append(&more_info_exclusions_label);
+            more_info_exclusions_header.append(&more_info_global_exclusions_button);
             more_info_exclusions_header.append(&more_info_exclusions_add_button);
-            let more_info_exclusions_list = ListBox::builder().selection_mode(SelectionMode::None).css_classes(vec!["boxed-list".to_string()]).valign(Align::Start).margin_top(5).margin_end(5).margin_bottom(5).margin_start(5).build();
-            let more_info_exclusions_list_scrolled = ScrolledWindow::builder().child(&more_info_exclusions_list).vexpand_set(true).vexpand(true).build();
-
-            // Read the ignore file to see if anything exists in it so far.
-            let file_ignore_path_string = format!("{local_path}/{FILE_IGNORE_NAME}");
-            let get_lock = glib::clone!(@strong file_ignore_path_string => move || {
-                // This will return an [`Err`] if the parent folder doesn't exist, so handle that case instead of `.unwrap`ing it.
-                FileLock::lock(&file_ignore_path_string, true, FileOptions::new().create(true).read(true).write(true).append(false))
-            });
-
-            let file_ignore_content = if get_lock().is_ok() {
-                Some(fs::read_to_string(&file_ignore_path_string).unwrap())
-            } else {
-                None
-            };
 
-            let ignore_rules: Rc<RefCell<IndexMap<EntryRow, String>>> = Rc::new(RefCell::new(IndexMap::new()));
-            let write_file = glib::clone!(@strong file_ignore_path_string, @strong ignore_rules, @strong get_lock => move || {
-                let ptr = ignore_rules.get_ref();
-                let strings: Vec<String> = ptr.values().map(|item| item.to_owned()).collect();
-
-                // First truncate the file.
-                OpenOptions::new().write(true).truncate(true).open(&file_ignore_path_string).unwrap();
-
-                // And then write to it.
-                if let Ok(mut lock) = get_lock() {
-                    lock.file.write_all(strings.join("\n").as_bytes()).unwrap()
-                };
-            });
-            let gen_ignore_row = glib::clone!(@strong get_lock, @strong write_file, @strong ignore_rules, @strong more_info_exclusions_list => move |content: Option<String>| {
-                let row = EntryRow::builder().css_classes(vec!["celeste-no-title".to_string()]).build();
-                if let Some(text) = content {
-                    row.set_text(&text);
-                } else {
-                    row.set_show_apply_button(true);
+            // Whether '.gitignore' files are honored for this directory.
+            let more_info_gitignore_row = Box::builder().orientation(Orientation::Horizontal).margin_start(5).margin_end(5).margin_bottom(5).build();
+            let more_info_gitignore_label = Label::builder()
+                .label(&tr::tr!("Honor .gitignore files"))
+                .halign(Align::Start)
+                .hexpand_set(true)
+                .hexpand(true)
+                .build();
+            let honor_gitignore = util::await_future(
+                SyncDirsEntity::find()
+                    .filter(SyncDirsColumn::LocalPath.eq(local_path.clone()))
+                    .filter(SyncDirsColumn::RemotePath.eq(remote_path.clone()))
+                    .one(&db)
+            ).unwrap().is_some_and(|sync_dir| sync_dir.honor_gitignore);
+            let more_info_gitignore_switch = Switch::builder().active(honor_gitignore).valign(Align::Center).build();
+            more_info_gitignore_switch.connect_state_set(glib::clone!(@strong db, @strong local_path, @strong remote_path => move |_, state| {
+                let sync_dir = util::await_future(
+                    SyncDirsEntity::find()
+                        .filter(SyncDirsColumn::LocalPath.eq(local_path.clone()))
+                        .filter(SyncDirsColumn::RemotePath.eq(remote_path.clone()))
+                        .one(&db)
+                ).unwrap();
+
+                // The changes get picked up on the next sync pass.
+                if let Some(sync_dir) = sync_dir {
+                    let mut active_model: SyncDirsActiveModel = sync_dir.into();
+                    active_model.honor_gitignore = ActiveValue::Set(state);
+                    util::await_future(active_model.update(&db)).unwrap();
                 }
-                let remove_button = Button::builder().icon_name("list-remove-symbolic").valign(Align::Center).css_classes(vec!["flat".to_string()]).build();
-                row.connect_apply(glib::clone!(@strong get_lock, @strong write_file, @strong ignore_rules => move |row| {
-                    // Make sure our ignore rules has the latest string for this item.
-                    let mut ptr = ignore_rules.get_mut_ref();
-                    ptr.insert(row.clone(), row.text().to_string());
-                    drop(ptr);
-
-                    // Write out all the current ignore rules to the file.
-                    write_file();
-                }));
-                remove_button.connect_clicked(glib::clone!(@strong get_lock, @strong write_file, @strong ignore_rules, @weak row, @weak more_info_exclusions_list => move |_| {
-                    row.set_sensitive(false);
-                    more_info_exclusions_list.remove(&row);
-
-                    // This returns [`None`] if the item hasn't been added via `row.connect_apply` above yet.
-                    let mut ptr = ignore_rules.get_mut_ref();
-                    if ptr.remove(&row).is_none() {
-                        return;
-                    }
 
-                    drop(ptr);
-                    write_file();
-                }));
-                row.connect_changed(|row| {
-                    let text = row.text().to_string();
-
-                    // If this row is valid, show the apply button. Otherwise, hide it.
-                    if let Err(err) = glob::Pattern::new(&text) {
-                        row.set_show_apply_button(false);
-                        row.add_css_class("error");
-                        row.set_tooltip_text(Some(&err.to_string()));
-                    } else {
-                        row.remove_css_class("error");
-                        row.set_tooltip_text(None);
-                        row.set_show_apply_button(true);
-                    }
-                });
-                row.add_suffix(&remove_button);
-                row
-            });
-            more_info_exclusions_add_button.connect_clicked(glib::clone!(@weak more_info_exclusions_list, @strong gen_ignore_row => move |_| {
-                more_info_exclusions_list.append(&gen_ignore_row(None));
+                Inhibit(false)
             }));
+            more_info_gitignore_row.append(&more_info_gitignore_label);
+            more_info_gitignore_row.append(&more_info_gitignore_switch);
+            let more_info_exclusions_list = ListBox::builder().selection_mode(SelectionMode::None).css_classes(vec!["boxed-list".to_string()]).valign(Align::Start).margin_top(5).margin_end(5).margin_bottom(5).margin_start(5).build();
+            let more_info_exclusions_list_scrolled = ScrolledWindow::builder().child(&more_info_exclusions_list).vexpand_set(true).vexpand(true).build();
 
-            if let Some(ignore_content) = file_ignore_content {
-                for line in ignore_content.lines() {
-                    let line_owned = line.to_owned();
-                    let row = gen_ignore_row(Some(line_owned.clone()));
-                    more_info_exclusions_list.append(&row);
-                    ignore_rules.get_mut_ref().insert(row, line_owned);
-                }
-            }
+            // Load the exclusion file into the list, saving any changes made to it.
+            let add_exclusion = exclude::editor(&format!("{local_path}/{FILE_IGNORE_NAME}"), &more_info_exclusions_list);
+            more_info_exclusions_add_button.connect_clicked(glib::clone!(@strong add_exclusion => move |_| {
+                add_exclusion(None);
+            }));
 
             // The back button to go back to the main page.
             let more_info_back_button = Button::builder()
@@ -461,13 +870,36 @@ pub fn launch(app: &Application, background: bool) {
                 .halign(Align::End)
                 .build();
 
+            let more_info_folders_button = Button::builder()
+                .icon_name("folder-symbolic")
+                .has_tooltip(true)
+                .tooltip_text(&tr::tr!("Choose which remote folders to sync"))
+                .halign(Align::End)
+                .margin_end(5)
+                .build();
+            more_info_folders_button.connect_clicked(glib::clone!(@strong db, @strong server_name, @strong local_path, @strong remote_path => move |_| {
+                let sync_dir = util::await_future(
+                    SyncDirsEntity::find()
+                        .filter(SyncDirsColumn::LocalPath.eq(local_path.clone()))
+                        .filter(SyncDirsColumn::RemotePath.eq(remote_path.clone()))
+                        .one(&db)
+                ).unwrap();
+
+                if let Some(sync_dir) = sync_dir {
+                    selective::dialog(&db, &sync_dir, &server_name);
+                }
+            }));
+
             // Store the pages element's in a vector. When the delete button is pressed and we confirm a deletion, we want the entire page to not be sensitive except for the back button, and we do that by only making the back button sensitive.
             let more_info_widgets: Vec<Widget> = vec![
                 more_info_errors_label.clone().into(),
                 more_info_errors_list_scrolled.clone().into(),
+                more_info_filters.clone().into(),
                 more_info_exclusions_header.clone().into(),
+                more_info_gitignore_row.clone().into(),
                 more_info_exclusions_list_scrolled.clone().into(),
                 more_info_back_button.clone().into(),
+                more_info_folders_button.clone().into(),
                 more_info_delete_button.clone().into(),
             ];
             more_info_delete_button.connect_clicked(glib::clone!(@strong sync_dir_deletion_queue, @strong server_name, @strong local_path, @strong remote_path, @strong formatted_local_path, @strong formatted_remote_path, @weak sections, @weak more_info_back_button, @weak more_info_delete_button, @strong more_info_widgets => move |_| {
@@ -498,11 +930,14 @@ pub fn launch(app: &Application, background: bool) {
                 dialog.show();
             }));
             more_info_header_buttons.append(&more_info_back_button);
+            more_info_header_buttons.append(&more_info_folders_button);
             more_info_header_buttons.append(&more_info_delete_button);
             more_info_page.append(&more_info_header_buttons);
             more_info_page.append(&more_info_errors_label);
             more_info_page.append(&more_info_errors_list_scrolled);
+            more_info_page.append(&more_info_filters);
             more_info_page.append(&more_info_exclusions_header);
+            more_info_page.append(&more_info_gitignore_row);
             more_info_page.append(&more_info_exclusions_list_scrolled);
 
             // Show the window upon click.
@@ -553,7 +988,10 @@ pub fn launch(app: &Application, background: bool) {
                     status_text: status,
                     error_list: more_info_errors_list,
                     error_items: HashMap::new(),
-                    update_error_ui: boxed::Box::new(update_error_list)
+                    update_error_ui: boxed::Box::new(update_error_list),
+                    state: Cell::new(DirState::Waiting),
+                    last_sync: Cell::new(None),
+                    progress: RefCell::new(None),
                 }
             );
 
@@ -561,6 +999,19 @@ pub fn launch(app: &Application, background: bool) {
             sections.add_named(&more_info_page, Some(&stack_child_name));
         });
 
+        remote_map.get_mut_ref().insert(
+            remote_name.clone(),
+            RemoteItem {
+                state: None,
+                banner,
+                banner_label,
+                banner_button,
+                retry_delay: RETRY_DELAY_MIN,
+                retry_at: Instant::now(),
+                add_dir: Rc::new(add_dir.clone()),
+            },
+        );
+
         // Create the remote in the database if it doesn't current exist.
         let db_remote = util::await_future(
                 RemotesEntity::find()
@@ -569,6 +1020,16 @@ pub fn launch(app: &Application, background: bool) {
             )
             .unwrap().unwrap();
 
+        // The activity history for this remote.
+        let (activity_page, reload_activity) = activity::page(&db, db_remote.id, glib::clone!(@weak sections => move || {
+            // Temporarily reverse the transition direction so it looks like we're going back a page.
+            let previous_transition_type = sections.transition_type();
+            sections.set_transition_type(StackTransitionType::OverRight);
+            sections.set_visible_child_name("main");
+            sections.set_transition_type(previous_transition_type);
+        }));
+        sections.add_named(&activity_page, Some("activity"));
+
         // The directory header, directory addition button, and remote deletion button.
         {
             let section = Box::builder().orientation(Orientation::Horizontal).build();
@@ -630,149 +1091,55 @@ pub fn launch(app: &Application, background: bool) {
                     dialog.show();
                 }));
 
-                // Get the remote folder to sync with, and add it.
-                // The entry completion code is largely inspired by https://github.com/gtk-rs/gtk4-rs/blob/master/examples/entry_completion/main.rs. I honestly have no clue what half the code for that is doing, I just know the current code is working well enough, and it can be fixed later if it breaks.
+                // Get the remote folder to sync with, and add it. The folder can either be typed
+                // in, or picked from the browser below it.
                 let remote_label = Label::builder().label(&tr::tr!("Remote folder:")).halign(Align::Start).css_classes(vec!["heading".to_string()]).build();
-                let entry_completion = EntryCompletion::new();
-                let store = ListStore::new(&[glib::Type::STRING]);
-
-                // The path that this store is currently valid on, excluding everything after the
-                // last `/` in the UI. We use this to detect when we need to obtain the list of
-                // directories from the remote again. The [`Vec`] of [`String`]s is a vector of
-                // rightmost dir items (i.e. it would contain `bar` instead of `/foo/bar`) because
-                // of how `update_options` is called below, so checks need to be done to make sure
-                // that the currently typed in path is the same as the one in the tuple's [`Path`]
-                // element.
-                let store_path: Rc<RefCell<(PathBuf, Vec<String>)>> = Rc::new(RefCell::new((Path::new("").to_owned(), vec![])));
-
-                entry_completion.set_text_column(0);
-                entry_completion.set_popup_completion(true);
-                entry_completion.set_model(Some(&store));
-                let remote_entry = Entry::builder().completion(&entry_completion).build();
+                let remote_entry = Entry::new();
                 remote_entry.insert_text("/", &mut -1);
-
-                // Get the current path, up to the last '/'.
-                let get_current_path = glib::clone!(@weak remote_entry => @default-panic, move || {
-                    let text = remote_entry.text().to_string();
-                    if text.ends_with('/') {
-                        Path::new(&text).to_path_buf()
-                    } else {
-                        Path::new(&text).parent().unwrap_or_else(|| Path::new("")).to_path_buf()
-                    }
-                });
-
-                // Update the UI completions against the list of stored directories.
-                let update_completions = glib::clone!(@weak entry_completion, @strong store, @weak remote_entry, @weak store, @strong store_path, @strong get_current_path => move || {
-
-                    // Get the current specified directory.
-                    let current_item_text = remote_entry.text();
-                    let current_item = Path::new(current_item_text.as_str()).file_name().map(|path| path.to_str().unwrap()).unwrap_or("");
-
-                    // Clear the current list of completions.
-                    store.clear();
-
-                    // See if any of the currently stored matches start with the same characters as
-                    // our path, and if they do, append them to the valid completions list.
-                    for item in &store_path.get_ref().1 {
-                        if item.starts_with(current_item) {
-                            store.set(&store.append(), &[(0, item)]);
-                        }
+                let (remote_browser, browse_to) = browser::remote_browser(&remote_name, glib::clone!(@weak remote_entry => move |path| {
+                    let text = format!("/{path}");
+                    if remote_entry.text() != text {
+                        remote_entry.set_text(&text);
+                        remote_entry.set_position(-1);
                     }
-                });
-
-                // The entry completion logic.
-                entry_completion.set_match_func(glib::clone!(@weak remote_entry => @default-panic, move |entry_completion, _entry_str, tree_iter| {
-                    let tree_model = entry_completion.model().unwrap();
-                    let text_column = entry_completion.text_column();
-                    let text_value = match tree_model.get_value(tree_iter, text_column).get::<String>() {
-                        // Not quite sure when this could fail, but it does sometimes, so return early when that's the case.
-                        Ok(value) => value,
-                        Err(_) => return false
-                    };
-
-                    // The last component of the directory specified by the user.
-                    let remote_entry_text = remote_entry.text().to_string();
-                    let entry_final_path_item = Path::new(&remote_entry_text).file_name().map(|path| path.to_str().unwrap()).unwrap_or("");
-                    text_value.starts_with(entry_final_path_item)
-                }));
-
-                entry_completion.connect_match_selected(glib::clone!(@weak remote_entry => @default-panic, move |_, model, iter| {
-                    let selected_entry = model.get::<String>(iter, 0);
-                    // The current text up to the last slash (i.e. 'hi' in '/foo/bar/hi').
-                    let up_to_slash_text = 'slash: {
-                        let current_text = remote_entry.text().to_string();
-
-                        // If the current text doesn't contain a slash, just return all the currently entered text.
-                        if !current_text.contains('/') {
-                            break 'slash current_text
-                        }
-
-                        // Otherwise return the text up to the last slash.
-                        break 'slash match current_text.rsplit_once('/') {
-                            Some((_, string)) => string.to_string(),
-                            None => String::new()
-                        }
-                    };
-
-                    // Get the text that we need to append.
-                    let mut to_append = selected_entry.strip_prefix(&up_to_slash_text).unwrap().to_string();
-                    to_append.push('/');
-
-                    // Append the text, and set the position to the end of the entry box.
-                    remote_entry.insert_text(&to_append, &mut -1);
-                    remote_entry.set_position(-1);
-
-                    // Stop the default matching behavior since we handled it here.
-                    Inhibit(true)
                 }));
-
-                // Update the stored list of autocompletions to the parent of those of the currently typed in directory.
-                let update_options = glib::clone!(@strong remote_name, @strong store_path, @weak remote_entry, @strong update_completions, @strong get_current_path => move || {
-                    let current_path = get_current_path();
-                    let current_path_string = current_path.as_os_str().to_owned().into_string().unwrap();
-
-                    let items = if let Ok(items) = rclone::sync::list(&remote_name, &current_path_string, false, RcloneListFilter::Dirs) {
-                        items.into_iter().map(|item| item.name).collect()
-                    } else {
-                        vec![]
-                    };
-
-                    // If the current parent path is still the same (i.e. after the file listing above has finished, which may have taken a bit), then update the completions to reflect the items we got.
-                    let mut store_path_ref = store_path.get_mut_ref();
-
-                    if store_path_ref.0 == current_path {
-                        store_path_ref.1 = items;
-                        // Drop `store_path_ref` so `update_completions` can get its own reference.
-                        drop(store_path_ref);
-                        update_completions();
-                    }
-                });
-
-                remote_entry.connect_cursor_position_notify(glib::clone!(@strong remote_name, @weak store_path, @strong update_completions, @strong update_options, @strong get_current_path => move |_| {
-                    // For some reason we have to clone the closure to pass the borrow checker, even though we clone it via the 'glib::clone!' above. Not sure why yet.
-                    let update_options = update_options.clone();
-
-                    let current_path = get_current_path();
-
-                    let mut store_path_ref = store_path.get_mut_ref();
-
-                    if store_path_ref.0 == current_path {
-                        // Drop our ref to `store_path_ref` so `update_completions` can get it's own.
-                        drop(store_path_ref);
-                        update_completions();
-                    } else {
-                        store_path_ref.0 = current_path;
-                        // Drop our ref to `store_path_ref` so `update_options` can get it's own.
-                        drop(store_path_ref);
-                        update_options();
-                    }
+                remote_entry.connect_activate(glib::clone!(@strong browse_to => move |remote_entry| {
+                    browse_to(remote_entry.text().as_str());
                 }));
+                browse_to("");
 
                 folder_sections.append(&local_label);
                 folder_sections.append(&local_entry);
                 folder_sections.append(&Separator::builder().orientation(Orientation::Vertical).css_classes(vec!["spacer".to_string()]).build());
                 folder_sections.append(&remote_label);
                 folder_sections.append(&remote_entry);
+                folder_sections.append(&remote_browser);
+
+                // Client-side encryption of the directory's files.
+                let encrypt_check = CheckButton::builder()
+                    .label(&tr::tr!("Encrypt files on the remote"))
+                    .margin_top(10)
+                    .build();
+                let password_entry = PasswordEntry::builder()
+                    .placeholder_text(&tr::tr!("Encryption password"))
+                    .show_peek_icon(true)
+                    .visible(false)
+                    .build();
+                let password_warning = Label::builder()
+                    .label(&tr::tr!("If this password is lost, the encrypted files can't be recovered. Make sure to keep a copy of it somewhere safe."))
+                    .halign(Align::Start)
+                    .wrap(true)
+                    .xalign(0.0)
+                    .visible(false)
+                    .css_classes(vec!["caption".to_string(), "warning".to_string()])
+                    .build();
+                encrypt_check.connect_toggled(glib::clone!(@weak password_entry, @weak password_warning => move |encrypt_check| {
+                    password_entry.set_visible(encrypt_check.is_active());
+                    password_warning.set_visible(encrypt_check.is_active());
+                }));
+                folder_sections.append(&encrypt_check);
+                folder_sections.append(&password_entry);
+                folder_sections.append(&password_warning);
                 let confirm_box = Box::builder().orientation(Orientation::Horizontal).spacing(10).halign(Align::End).build();
                 let cancel_button = Button::with_label(&tr::tr!("Cancel"));
                 let ok_button = Button::with_label(&tr::tr!("Ok"));
@@ -808,7 +1175,7 @@ pub fn launch(app: &Application, background: bool) {
                     folder_window.close();
                     window.set_sensitive(true);
                 }));
-                ok_button.connect_clicked(glib::clone!(@strong window, @weak sections, @weak folder_window, @weak sync_dirs, @weak local_entry, @weak remote_entry, @strong db_remote, @strong db, @weak directory_map, @strong remote_name, @strong add_dir => move |_| {
+                ok_button.connect_clicked(glib::clone!(@strong window, @weak sections, @weak folder_window, @weak sync_dirs, @weak local_entry, @weak remote_entry, @weak encrypt_check, @weak password_entry, @strong db_remote, @strong db, @weak directory_map, @strong remote_name, @strong add_dir => move |_| {
                     folder_window.set_sensitive(false);
 
                     // The local path needs to start with a slash, but not end with one. The remote
@@ -816,22 +1183,35 @@ pub fn launch(app: &Application, background: bool) {
                     let local_text = "/".to_string() + &util::strip_slashes(local_entry.text().as_str());
                     let remote_text = util::strip_slashes(remote_entry.text().as_str());
                     let local_path = Path::new(&local_text);
-                    match rclone::sync::stat(&remote_name, &remote_text) {
-                        Ok(path) => {
-                            if path.is_none() {
-                                gtk_util::show_error(&tr::tr!("The specified remote directory doesn't exist"), None);
-                                folder_window.set_sensitive(true);
-                                return;
-                            } else {
-                                path
-                            }
-                        },
-                        Err(err) => {
-                            gtk_util::show_error(&tr::tr!("Failed to check if the specified remote directory exists"), Some(&err.error));
+                    let encrypt = encrypt_check.is_active();
+                    let password = password_entry.text().to_string();
+
+                    // Encrypted directories get created through the crypt remote below, as
+                    // their names on the remote are encrypted as well.
+                    if encrypt {
+                        if password.is_empty() {
+                            gtk_util::show_error(&tr::tr!("A password is needed to encrypt the directory"), None);
                             folder_window.set_sensitive(true);
                             return;
                         }
-                    };
+                    } else {
+                        match rclone::sync::stat(&remote_name, &remote_text) {
+                            Ok(path) => {
+                                if path.is_none() {
+                                    gtk_util::show_error(&tr::tr!("The specified remote directory doesn't exist"), None);
+                                    folder_window.set_sensitive(true);
+                                    return;
+                                } else {
+                                    path
+                                }
+                            },
+                            Err(err) => {
+                                gtk_util::show_error(&tr::tr!("Failed to check if the specified remote directory exists"), Some(&err.error));
+                                folder_window.set_sensitive(true);
+                                return;
+                            }
+                        };
+                    }
 
                     let sync_dir = util::await_future(
                         SyncDirsEntity::find().filter(SyncDirsColumn::LocalPath.eq(local_text.clone())).filter(SyncDirsColumn::RemotePath.eq(remote_text.clone())).one(&db)
@@ -850,22 +1230,58 @@ pub fn launch(app: &Application, background: bool) {
                         gtk_util::show_error(&tr::tr!("The specified local directory needs to be an absolute path"), None);
                         folder_window.set_sensitive(true);
                     } else {
-                        util::await_future(
-                            SyncDirsActiveModel {
-                                remote_id: ActiveValue::Set(db_remote.id),
-                                local_path: ActiveValue::Set(local_text.clone()),
-                                remote_path: ActiveValue::Set(remote_text.clone()),
-                                ..Default::default()
-                            }.insert(&db)
-                        ).unwrap();
-                        add_dir(remote_name.clone(), local_text, remote_text);
-                        folder_window.close();
-                    }
+                        let crypt_remote = if encrypt {
+                            let crypt_remote = match rclone::create_crypt_remote(&remote_name, &password) {
+                                Ok(crypt_remote) => crypt_remote,
+                                Err(err) => {
+                                    gtk_util::show_error(&tr::tr!("Failed to set up encryption for the directory"), Some(&err.error));
+                                    folder_window.set_sensitive(true);
+                                    return;
+                                }
+                            };
+
+                            if let Err(err) = rclone::sync::mkdir(&crypt_remote, &remote_text) {
+                                gtk_util::show_error(&tr::tr!("Failed to create the encrypted directory on the remote"), Some(&err.error));
+                                rclone::sync::delete_config(&crypt_remote).unwrap();
+                                folder_window.set_sensitive(true);
+                                return;
+                            }
+
+                            Some(crypt_remote)
+                        } else {
+                            None
+                        };
+
+                        util::await_future(
+                            SyncDirsActiveModel {
+                                remote_id: ActiveValue::Set(db_remote.id),
+                                local_path: ActiveValue::Set(local_text.clone()),
+                                remote_path: ActiveValue::Set(remote_text.clone()),
+                                crypt_remote: ActiveValue::Set(crypt_remote),
+                                ..Default::default()
+                            }.insert(&db)
+                        ).unwrap();
+                        add_dir(remote_name.clone(), local_text, remote_text, encrypt);
+                        folder_window.close();
+                    }
                 }));
 
                 folder_window.set_content(Some(&folder_sections));
                 folder_window.show();
             }));
+            let edit_remote_button = Button::builder()
+                .icon_name("document-edit-symbolic")
+                .has_tooltip(true)
+                .tooltip_text(&tr::tr!("Edit login details"))
+                .halign(Align::End)
+                .valign(Align::Start)
+                .margin_start(10)
+                .build();
+            edit_remote_button.connect_clicked(glib::clone!(@weak app, @weak window, @strong remote_name => move |_| {
+                window.set_sensitive(false);
+                login::edit(&app, &remote_name);
+                window.set_sensitive(true);
+            }));
             let delete_remote_button = Button::builder()
                 .icon_name("user-trash-symbolic")
                 .halign(Align::End)
@@ -894,8 +1310,22 @@ pub fn launch(app: &Application, background: bool) {
                 }));
                 dialog.show();
             }));
+            let activity_button = Button::builder()
+                .icon_name("document-open-recent-symbolic")
+                .has_tooltip(true)
+                .tooltip_text(&tr::tr!("Activity"))
+                .halign(Align::End)
+                .valign(Align::Start)
+                .margin_start(10)
+                .build();
+            activity_button.connect_clicked(glib::clone!(@weak sections, @strong reload_activity => move |_| {
+                reload_activity();
+                sections.set_visible_child_name("activity");
+            }));
             section.append(&label);
             section.append(&new_folder_button);
+            section.append(&activity_button);
+            section.append(&edit_remote_button);
             section.append(&delete_remote_button);
             page.append(&section);
         }
@@ -915,6 +1345,7 @@ pub fn launch(app: &Application, background: bool) {
                     db_remote.name.clone(),
                     dir.local_path.clone(),
                     dir.remote_path.clone(),
+                    dir.crypt_remote.is_some(),
                 );
             }
         }
@@ -961,6 +1392,23 @@ pub fn launch(app: &Application, background: bool) {
         .child(&sidebar_menu_popover_sections)
         .position(PositionType::Bottom)
         .build();
+    let sidebar_menu_import_button = Button::builder()
+        .label(&tr::tr!("Import from Rclone config"))
+        .css_classes(vec!["flat".to_string()])
+        .build();
+    sidebar_menu_import_button.connect_clicked(
+        glib::clone!(@weak app, @weak window, @weak stack, @weak sidebar_menu_popover, @strong gen_remote_window, @strong db => move |_| {
+            sidebar_menu_popover.popdown();
+            window.set_sensitive(false);
+
+            for remote in login::import::import_remotes(&app, &db) {
+                let window = gen_remote_window(remote.clone());
+                stack.add_titled(&window, Some(&remote.name), &remote.name);
+            }
+
+            window.set_sensitive(true);
+        }),
+    );
     let sidebar_menu_about_button = Button::builder()
         .label("About")
         .css_classes(vec!["flat".to_string()])
@@ -979,6 +1427,7 @@ pub fn launch(app: &Application, background: bool) {
         sidebar_menu_popover.popdown();
         *(*CLOSE_REQUEST).lock().unwrap() = true;
     }));
+    sidebar_menu_popover_sections.append(&sidebar_menu_import_button);
     sidebar_menu_popover_sections.append(&sidebar_menu_about_button);
     sidebar_menu_popover_sections.append(&sidebar_menu_quit_button);
     sidebar_menu_popover.set_parent(&sidebar_menu_button);
@@ -1063,6 +1512,92 @@ pub fn launch(app: &Application, background: bool) {
     let handle = service.handle();
     service.spawn();
 
+    // Actions for the CLI to control this instance with. Removals get added to
+    // the deletion queues like they would from the UI, while anything else is
+    // picked up at the start of the next sync pass.
+    let reload_request = Rc::new(Cell::new(false));
+    let sync_now_request = Rc::new(Cell::new(false));
+
+    let reload_action = gio::SimpleAction::new("reload", None);
+    reload_action.connect_activate(glib::clone!(@strong reload_request => move |_, _| {
+        reload_request.set(true);
+    }));
+    app.add_action(&reload_action);
+
+    let request_sync_now = glib::clone!(@strong sync_now_request, @strong remote_map => move || {
+        sync_now_request.set(true);
+
+        for remote_item in remote_map.get_mut_ref().values_mut() {
+            remote_item.retry_at = Instant::now();
+        }
+    });
+
+    let sync_now_action = gio::SimpleAction::new("sync-now", None);
+    sync_now_action.connect_activate(glib::clone!(@strong request_sync_now => move |_, _| {
+        request_sync_now();
+    }));
+    app.add_action(&sync_now_action);
+
+    let remove_remote_action =
+        gio::SimpleAction::new("remove-remote", Some(glib::VariantTy::STRING));
+    remove_remote_action.connect_activate(
+        glib::clone!(@strong remote_map, @strong remote_deletion_queue => move |_, param| {
+            let remote_name: String = param.unwrap().get().unwrap();
+
+            if remote_map.get_ref().contains_key(&remote_name)
+                && !remote_deletion_queue.get_ref().contains(&remote_name)
+            {
+                remote_deletion_queue.get_mut_ref().push(remote_name);
+            }
+        }),
+    );
+    app.add_action(&remove_remote_action);
+
+    let remove_dir_action =
+        gio::SimpleAction::new("remove-dir", Some(glib::VariantTy::new("(sss)").unwrap()));
+    remove_dir_action.connect_activate(
+        glib::clone!(@strong directory_map, @strong sync_dir_deletion_queue => move |_, param| {
+            let data: (String, String, String) = param.unwrap().get().unwrap();
+            let dir_pair = (data.1.clone(), data.2.clone());
+            let exists = directory_map
+                .get_ref()
+                .get(&data.0)
+                .is_some_and(|dirs| dirs.contains_key(&dir_pair));
+
+            if exists && !sync_dir_deletion_queue.get_ref().contains(&data) {
+                sync_dir_deletion_queue.get_mut_ref().push(data);
+            }
+        }),
+    );
+    app.add_action(&remove_dir_action);
+
+    // Export the D-Bus interface. Syncing still works without it, so just report
+    // it if it can't be set up.
+    if let Some(conn) = app.dbus_connection()
+        && let Err(err) = dbus::register(&conn, &db)
+    {
+        hw_msg::warningln!(
+            "{}",
+            tr::tr!("Unable to export the D-Bus interface [{}].", err)
+        );
+    }
+
+    // When the network comes back up, retry any disconnected remotes straight away
+    // instead of waiting for their next reconnection attempt.
+    gio::NetworkMonitor::default().connect_network_changed(
+        glib::clone!(@strong remote_map => move |_, network_available| {
+            if !network_available {
+                return;
+            }
+
+            for remote_item in remote_map.get_mut_ref().values_mut() {
+                if remote_item.state == Some(RemoteState::Disconnected) {
+                    remote_item.retry_at = Instant::now();
+                }
+            }
+        }),
+    );
+
     let sync_errors_count = glib::clone!(@strong directory_map => move || {
         let dmap = directory_map.get_ref();
         let mut error_count = 0;
@@ -1078,6 +1613,275 @@ pub fn launch(app: &Application, background: bool) {
         error_count
     });
 
+    // Show an error in the UI. `is_new_error` is whether the error was just found,
+    // as opposed to being loaded from the database.
+    let show_error = glib::clone!(@strong db, @strong directory_map, @strong remote_map, @strong sync_errors_count, @strong handle => move |remote: &RemotesModel, sync_dir: &SyncDirsModel, error: SyncError, is_new_error: bool| {
+        let remote = remote.clone();
+        let sync_dir = sync_dir.clone();
+        let path_pair = (sync_dir.local_path.clone(), sync_dir.remote_path.clone());
+        let please_resolve_msg = " ".to_owned() + &tr::tr!("Please resolve the reported syncing issues.");
+
+        // If the error is already being reported, there's nothing else to do.
+        let already_reported = directory_map
+            .get_ref()
+            .get(&remote.name)
+            .unwrap()
+            .get(&path_pair)
+            .unwrap()
+            .error_items
+            .contains_key(&error);
+        if already_reported {
+            return;
+        }
+
+        // Errors loaded from the database were already recorded when they were first
+        // found.
+        if is_new_error {
+            let (error_local_path, error_remote_path, _) = error.db_fields();
+            activity::record(&db, &sync_dir, ActivityKind::Error, &error_local_path, &error_remote_path, None, &error.message());
+        }
+
+        let ui_item = error.generate_ui();
+        let ui_item_listbox = ListBoxRow::builder().child(&ui_item).build();
+
+        // Generate the callback.
+        let gesture = GestureClick::new();
+        gesture.connect_released(glib::clone!(@strong directory_map, @strong remote, @strong path_pair, @strong db, @strong error, @weak ui_item, @weak ui_item_listbox, @strong please_resolve_msg => move |_, _, _, _| {
+            ui_item.set_sensitive(false);
+            let remove_ui_item = glib::clone!(@strong db, @strong sync_dir, @strong directory_map, @strong remote, @strong path_pair, @strong error, @weak ui_item_listbox, @strong please_resolve_msg => move || {
+                let mut ptr = directory_map.get_mut_ref();
+                let item = ptr.get_mut(&remote.name).unwrap().get_mut(&path_pair).unwrap();
+
+                // Update the sync dir's page and our code.
+                item.error_items.remove(&error).unwrap();
+                item.error_list.remove(&ui_item_listbox);
+
+                // Update the error brief on the main page.
+                let new_num_errors = item.error_items.len();
+                if new_num_errors == 0 {
+                    item.error_status_text.set_label("");
+                    let label_text = match item.status_text.text().as_str().strip_suffix(&please_resolve_msg) {
+                        Some(text) => text.to_string(),
+                        None => item.status_text.text().to_string()
+                    };
+                    item.status_text.set_label(&label_text);
+
+                } else {
+                    let error_string = tr::tr!("{} errors found. ", new_num_errors);
+                    item.error_status_text.set_label(&error_string);
+                }
+
+                (item.update_error_ui)();
+                drop(ptr);
+
+                // The error has been resolved, so it no longer needs to be remembered.
+                if let Some(db_error) = error.find_model(&db, sync_dir.id) {
+                    util::await_future(db_error.delete(&db)).unwrap();
+                }
+            });
+
+            match &error {
+                SyncError::General(_, _) => {
+                    let dialog = MessageDialog::builder()
+                        .text(&tr::tr!("Would you like to dismiss this error?"))
+                        .buttons(ButtonsType::YesNo)
+                        .build();
+                    dialog.connect_close_request(glib::clone!(@strong ui_item => move |_| {
+                        ui_item.set_sensitive(true);
+                        Inhibit(false)
+                    }));
+                    dialog.connect_response(glib::clone!(@strong directory_map, @strong remote, @strong path_pair, @weak ui_item, @strong error, @strong remove_ui_item => move |dialog, resp| {
+                        match resp {
+                            ResponseType::Yes => {
+                                remove_ui_item();
+                            },
+                            ResponseType::No => {
+                                ui_item.set_sensitive(true);
+                            },
+                            _ => return,
+                        }
+
+                        dialog.close();
+                    }));
+                    dialog.show();
+                },
+                SyncError::BothMoreCurrent(local_item, remote_item) => {
+                    let local_item_formatted = util::fmt_home(local_item);
+                    let local_path = Path::new(&local_item);
+                    let sync_local_to_remote = glib::clone!(@strong db, @strong sync_dir, @strong remote, @strong local_item_formatted, @strong local_item, @strong remote_item => move || {
+                        if let Err(err) = rclone::sync::copy_to_remote(&local_item, &sync_dir.rclone_remote(&remote.name), &remote_item) {
+                            gtk_util::show_error(&tr::tr!("Failed to sync '{}' to '{}' on remote.", local_item_formatted, remote_item), Some(&err.error));
+                            Err(())
+                        } else {
+                            let size = Path::new(&local_item).metadata().ok().map(|metadata| metadata.len());
+                            activity::record(&db, &sync_dir, ActivityKind::ConflictResolved, &local_item, &remote_item, size, &tr::tr!("Kept the local item."));
+                            Ok(())
+                        }
+                    });
+                    let sync_remote_to_local = glib::clone!(@strong db, @strong sync_dir, @strong remote, @strong local_item_formatted, @strong local_item, @strong remote_item => move |mode: Option<u32>| {
+                        if let Err(err) = versions::save(&db, &sync_dir, &local_item) {
+                            gtk_util::show_error(&tr::tr!("Failed to sync '{}' on remote to '{}'.", remote_item, local_item_formatted), Some(&err));
+                            Err(())
+                        } else if let Err(err) = rclone::sync::copy_to_local(&local_item, &sync_dir.rclone_remote(&remote.name), &remote_item, mode) {
+                            gtk_util::show_error(&tr::tr!("Failed to sync '{}' on remote to '{}'.", remote_item, local_item_formatted), Some(&err.error));
+                            Err(())
+                        } else {
+                            let size = Path::new(&local_item).metadata().ok().map(|metadata| metadata.len());
+                            activity::record(&db, &sync_dir, ActivityKind::ConflictResolved, &local_item, &remote_item, size, &tr::tr!("Kept the remote item."));
+                            Ok(())
+                        }
+                    });
+                    let local_item = local_item.clone();
+                    let update_db_item = glib::clone!(@strong sync_dir, @strong db, @strong remote, @strong local_item, @strong remote_item => move || {
+                        let local_metadata = Path::new(&local_item).metadata().unwrap();
+                        let local_timestamp = local_metadata.modified().unwrap().duration_since(SystemTime::UNIX_EPOCH).unwrap().as_secs();
+                        let rclone_item = rclone::sync::stat(&sync_dir.rclone_remote(&remote.name), &remote_item).unwrap().unwrap();
+                        let remote_timestamp = rclone_item.mod_time.unix_timestamp();
+                        let mut active_model: SyncItemsActiveModel = util::await_future(SyncItemsEntity::find()
+                            .filter(SyncItemsColumn::LocalPath.eq(local_item.clone()))
+                            .filter(SyncItemsColumn::RemotePath.eq(remote_item.clone()))
+                            .one(&db)
+                        ).unwrap()
+                        .unwrap()
+                        .into();
+                        active_model.last_local_timestamp = ActiveValue::set(local_timestamp.try_into().unwrap());
+                        active_model.last_remote_timestamp = ActiveValue::Set(remote_timestamp.try_into().unwrap());
+                        active_model.local_inode = ActiveValue::Set(Some(moves::inode(&local_metadata)));
+                        active_model.remote_id = ActiveValue::Set(rclone_item.id);
+                        util::await_future(active_model.update(&db)).unwrap();
+                    });
+                    let rclone_remote_item = match rclone::sync::stat(&sync_dir.rclone_remote(&remote.name), remote_item) {
+                        Ok(item) => item,
+                        Err(err) => {
+                            gtk_util::show_error(
+                                &tr::tr!("Unable to fetch data for '{}' from the remote.", remote_item),
+                                Some(&err.error)
+                            );
+                            return;
+                        }
+                    };
+
+                    // If neither the local item or the remote item exist anymore, this error is no longer relevant.
+                    if !local_path.exists() && rclone_remote_item.is_none() {
+                        gtk_util::show_error(&tr::tr!("File Update"), Some(&tr::tr!("Neither the local item or remote item exists anymore. This error will now be removed.")));
+                        remove_ui_item();
+                        return;
+                    // Otherwise if only the local exists, use that.
+                    } else if local_path.exists() && rclone_remote_item.is_none() {
+                        gtk_util::show_error(&tr::tr!("File Update"), Some(&tr::tr!("Only the local item exists now, so it will be synced to the remote.")));
+                        if sync_local_to_remote().is_ok() {
+                            update_db_item();
+                            remove_ui_item();
+                            return;
+                        }
+                    // Otherwise if only the remote exists, use that.
+                    } else if !local_path.exists() && rclone_remote_item.is_some() {
+                        gtk_util::show_error(&tr::tr!("File Update"), Some(&tr::tr!("Only the remote item exists now, so it will be synced to the local machine.")));
+                        if sync_remote_to_local(rclone_remote_item.as_ref().and_then(|item| item.mode())).is_ok() {
+                            update_db_item();
+                            remove_ui_item();
+                            return;
+                        }
+                    }
+
+                    let dialog = MessageDialog::builder()
+                        .text(
+                            &tr::tr!("Both the local item '{}' and remote item '{}' have been updated since the last sync.", local_item_formatted, remote_item)
+                        )
+                        .secondary_text(&tr::tr!("Which item would you like to keep?"))
+                        .build();
+                    dialog.add_button(&tr::tr!("Local"), ResponseType::Other(0));
+                    dialog.add_button(&tr::tr!("Remote"), ResponseType::Other(1));
+                    dialog.connect_close_request(glib::clone!(@strong ui_item => move |_| {
+                        ui_item.set_sensitive(true);
+                        Inhibit(false)
+                    }));
+                    dialog.connect_response(glib::clone!(@strong directory_map, @strong remote, @strong path_pair, @weak ui_item, @strong error, @strong local_item, @strong remote_item, @strong local_path, @strong rclone_remote_item, @strong sync_local_to_remote, @strong sync_remote_to_local => move |dialog, resp| {
+                        match resp {
+                            ResponseType::Other(0) => {
+                                if sync_local_to_remote().is_ok() {
+                                    update_db_item();
+                                    remove_ui_item();
+                                }
+                            },
+                            ResponseType::Other(1) => {
+                                if sync_remote_to_local(rclone_remote_item.as_ref().and_then(|item| item.mode())).is_ok() {
+                                    update_db_item();
+                                    remove_ui_item();
+                                }
+                            },
+                            ResponseType::Other(_) => unreachable!(),
+                            _ => return
+                        }
+
+                        dialog.close();
+                    }));
+
+                    dialog.show();
+                },
+                SyncError::NameCollision(local_item, other_item) => {
+                    collisions::rename_dialog(
+                        local_item,
+                        other_item,
+                        glib::clone!(@strong remove_ui_item => move || remove_ui_item()),
+                        glib::clone!(@weak ui_item => move || ui_item.set_sensitive(true)),
+                    );
+                },
+                SyncError::InvalidName(local_item, problem) => {
+                    let limits = NameLimits::for_sync_dir(&remote.name, &sync_dir);
+                    let name = Path::new(local_item).file_name().unwrap().to_string_lossy().to_string();
+                    names::rename_dialog(
+                        local_item,
+                        &tr::tr!("'{}' can't be synced, as {}. Enter a new name for it.", util::fmt_home(local_item), problem),
+                        &limits.suggest(&name),
+                        glib::clone!(@strong limits => move |new_name| limits.problem(new_name).is_none()),
+                        glib::clone!(@strong remove_ui_item => move || remove_ui_item()),
+                        glib::clone!(@weak ui_item => move || ui_item.set_sensitive(true)),
+                    );
+                }
+            }
+        }));
+        ui_item.add_controller(&gesture);
+
+        // If we have zero errors now, remove the warning icon.
+        if sync_errors_count() == 0 {
+            handle.update(|tray| tray.set_syncing());
+        }
+
+        // Report the brief on the number of errors.
+        let mut ptr = directory_map.get_mut_ref();
+        let item = ptr
+            .get_mut(&remote.name)
+            .unwrap()
+            .get_mut(&path_pair)
+            .unwrap();
+
+        let new_num_errors = item.error_items.len() + 1;
+
+        let error_string = if new_num_errors == 1 {
+            tr::tr!("1 error found.")
+        } else {
+            tr::tr!("{} errors found.", new_num_errors)
+        };
+        item.error_status_text.set_label(&(error_string + " "));
+
+        // Add the error to the UI.
+        let error_message = error.message();
+        item.error_list.append(&ui_item_listbox);
+        item.error_items.insert(error, ui_item);
+        (item.update_error_ui)();
+        drop(ptr);
+        write_status(&remote_map, &directory_map);
+        dbus::error_added(&remote.name, &path_pair.0, &path_pair.1, &error_message);
+
+        // Set the tray icon to show the warning icon.
+        handle.update(|tray| tray.set_warning());
+    });
+
+    // The IDs of the sync directories whose errors have been loaded from the
+    // database.
+    let mut loaded_error_dirs = HashSet::new();
+
     'main: loop {
         // Break the loop if the user requested to quit the application.
         if *(*CLOSE_REQUEST).lock().unwrap() {
@@ -1112,7 +1916,123 @@ pub fn launch(app: &Application, background: bool) {
 
         util::run_in_background(|| thread::sleep(Duration::from_millis(500)));
 
-        for remote in remotes {
+        // Pick up any remotes and directories that were added from outside of the UI
+        // (i.e. from the CLI).
+        if reload_request.replace(false) {
+            for remote in &remotes {
+                let add_dir = remote_map
+                    .get_ref()
+                    .get(&remote.name)
+                    .map(|remote_item| remote_item.add_dir.clone());
+
+                // New remotes get all of their directories added when the remote's window
+                // is generated.
+                let add_dir = match add_dir {
+                    Some(add_dir) => add_dir,
+                    None => {
+                        let remote_window = gen_remote_window(remote.clone());
+                        stack.add_titled(&remote_window, Some(&remote.name), &remote.name);
+                        continue;
+                    }
+                };
+
+                let sync_dirs = util::await_future(
+                    SyncDirsEntity::find()
+                        .filter(SyncDirsColumn::RemoteId.eq(remote.id))
+                        .all(&db),
+                )
+                .unwrap();
+
+                for sync_dir in sync_dirs {
+                    let dir_pair = (sync_dir.local_path.clone(), sync_dir.remote_path.clone());
+                    let exists = directory_map
+                        .get_ref()
+                        .get(&remote.name)
+                        .is_some_and(|dirs| dirs.contains_key(&dir_pair));
+
+                    if !exists {
+                        add_dir(
+                            remote.name.clone(),
+                            sync_dir.local_path,
+                            sync_dir.remote_path,
+                            sync_dir.crypt_remote.is_some(),
+                        );
+                    }
+                }
+            }
+        }
+
+        // Process any requests made over D-Bus.
+        let dbus_requests: Vec<dbus::Request> =
+            (*dbus::REQUESTS).lock().unwrap().drain(..).collect();
+
+        for request in dbus_requests {
+            let (local_path, remote_path, paused) = match request {
+                dbus::Request::SyncNow => {
+                    request_sync_now();
+                    continue;
+                }
+                dbus::Request::Pause(local_path, remote_path) => (local_path, remote_path, true),
+                dbus::Request::Resume(local_path, remote_path) => (local_path, remote_path, false),
+            };
+            let sync_dir = util::await_future(
+                SyncDirsEntity::find()
+                    .filter(SyncDirsColumn::LocalPath.eq(local_path))
+                    .filter(SyncDirsColumn::RemotePath.eq(remote_path))
+                    .one(&db),
+            )
+            .unwrap();
+
+            if let Some(sync_dir) = sync_dir {
+                let mut active_model: SyncDirsActiveModel = sync_dir.into();
+                active_model.paused = ActiveValue::Set(paused);
+                util::await_future(active_model.update(&db)).unwrap();
+            }
+        }
+
+        // Show any errors that were found before Celeste was last closed. This covers
+        // every directory, including those on remotes that can't currently be synced,
+        // and the errors are shown as they were recorded instead of being recorded
+        // again.
+        for remote in &remotes {
+            let sync_dirs = util::await_future(
+                SyncDirsEntity::find()
+                    .filter(SyncDirsColumn::RemoteId.eq(remote.id))
+                    .all(&db),
+            )
+            .unwrap();
+
+            for sync_dir in sync_dirs {
+                let dir_pair = (sync_dir.local_path.clone(), sync_dir.remote_path.clone());
+                let exists = directory_map
+                    .get_ref()
+                    .get(&remote.name)
+                    .is_some_and(|dirs| dirs.contains_key(&dir_pair));
+
+                if !exists || !loaded_error_dirs.insert(sync_dir.id) {
+                    continue;
+                }
+
+                let db_errors = util::await_future(
+                    SyncErrorsEntity::find()
+                        .filter(SyncErrorsColumn::SyncDirId.eq(sync_dir.id))
+                        .all(&db),
+                )
+                .unwrap();
+
+                for db_error in db_errors {
+                    if let Some(error) = SyncError::from_model(&db_error) {
+                        show_error(remote, &sync_dir, error, false);
+                    }
+                }
+            }
+        }
+
+        // Sync every directory on this pass if it was requested, regardless of whether
+        // any changes were found.
+        let sync_now = sync_now_request.replace(false);
+
+        'remotes: for remote in remotes {
             // Process any remote deletion requests.
             {
                 let mut remote_queue = remote_deletion_queue.get_mut_ref();
@@ -1123,38 +2043,50 @@ pub fn launch(app: &Application, background: bool) {
                     // Remove the item from the UI.
                     let child = stack.child_by_name(&remote_name).unwrap();
                     stack.remove(&child);
+                    remote_map.get_mut_ref().remove(&remote_name);
 
                     // Delete all related database entries.
-                    util::await_future(async {
-                        let db_remote = RemotesEntity::find()
+                    let db_remote = util::await_future(
+                        RemotesEntity::find()
                             .filter(RemotesColumn::Name.eq(remote_name.clone()))
-                            .one(&db)
-                            .await
-                            .unwrap()
-                            .unwrap();
-                        let sync_dirs = SyncDirsEntity::find()
+                            .one(&db),
+                    )
+                    .unwrap()
+                    .unwrap();
+                    let sync_dirs = util::await_future(
+                        SyncDirsEntity::find()
                             .filter(SyncDirsColumn::RemoteId.eq(db_remote.id))
-                            .all(&db)
-                            .await
-                            .unwrap();
-
-                        for sync_dir in sync_dirs {
-                            SyncItemsEntity::delete_many()
-                                .filter(SyncItemsColumn::SyncDirId.eq(sync_dir.id))
-                                .exec(&db)
-                                .await
-                                .unwrap();
-                            sync_dir.delete(&db).await.unwrap();
-                        }
-
-                        db_remote.delete(&db).await.unwrap();
-                    });
+                            .all(&db),
+                    )
+                    .unwrap();
 
-                    // Delete the Rclone config.
+                    for sync_dir in sync_dirs {
+                        util::delete_sync_dir(&db, sync_dir);
+                    }
+                    util::await_future(db_remote.delete(&db)).unwrap();
                     rclone::sync::delete_config(&remote_name).unwrap();
                 }
             }
 
+            // Don't sync this remote if something's currently preventing it from being
+            // synced, or if it hasn't been added to the UI yet.
+            match remote_map.get_ref().get(&remote.name) {
+                Some(remote_item) => match remote_item.state {
+                    Some(RemoteState::NeedsSignIn) => continue,
+                    // Wait until the next reconnection attempt is due before trying the remote
+                    // again.
+                    Some(RemoteState::Disconnected) if Instant::now() < remote_item.retry_at => {
+                        continue
+                    }
+                    _ => (),
+                },
+                None => continue,
+            }
+
+            // Move any new credentials (i.e. from logging in or from Rclone refreshing an
+            // OAuth token) out of the config file and into the keyring.
+            rclone::store_secrets(&remote.name);
+
             let sync_dirs = util::await_future(
                 SyncDirsEntity::find()
                     .filter(SyncDirsColumn::RemoteId.eq(remote.id))
@@ -1163,10 +2095,64 @@ pub fn launch(app: &Application, background: bool) {
             .unwrap();
 
             for sync_dir in sync_dirs {
+                // Skip any directories that haven't been added to the UI yet.
+                let dir_pair = (sync_dir.local_path.clone(), sync_dir.remote_path.clone());
+                let exists = directory_map
+                    .get_ref()
+                    .get(&remote.name)
+                    .is_some_and(|dirs| dirs.contains_key(&dir_pair));
+
+                if !exists {
+                    continue;
+                }
+
+                // Add an error for reporting in the UI.
+                let please_resolve_msg_tr = tr::tr!("Please resolve the reported syncing issues.");
+                let please_resolve_msg = " ".to_owned() + &please_resolve_msg_tr;
+                let add_error = glib::clone!(@strong db, @strong remote, @strong sync_dir, @strong show_error => move |error: SyncError| {
+                    // Record the error in the database, so it's still reported after Celeste gets
+                    // restarted.
+                    let now = OffsetDateTime::now_utc().unix_timestamp();
+                    let (db_local_path, db_remote_path, db_message) = error.db_fields();
+                    let is_new_error = match error.find_model(&db, sync_dir.id) {
+                        Some(db_error) => {
+                            let mut active_model: SyncErrorsActiveModel = db_error.into();
+                            active_model.message = ActiveValue::Set(db_message);
+                            active_model.last_seen = ActiveValue::Set(now);
+                            util::await_future(active_model.update(&db)).unwrap();
+                            false
+                        }
+                        None => {
+                            util::await_future(SyncErrorsActiveModel {
+                                sync_dir_id: ActiveValue::Set(sync_dir.id),
+                                kind: ActiveValue::Set(error.kind().to_owned()),
+                                local_path: ActiveValue::Set(db_local_path),
+                                remote_path: ActiveValue::Set(db_remote_path),
+                                message: ActiveValue::Set(db_message),
+                                first_seen: ActiveValue::Set(now),
+                                last_seen: ActiveValue::Set(now),
+                                ..Default::default()
+                            }.insert(&db)).unwrap();
+                            true
+                        }
+                    };
+
+                    show_error(&remote, &sync_dir, error, is_new_error);
+                });
+
+                // Skip any directories that have been paused.
+                if sync_dir.paused {
+                    let ptr = directory_map.get_ref();
+                    let item = ptr.get(&remote.name).unwrap().get(&dir_pair).unwrap();
+                    item.status_text.set_label(&tr::tr!("Syncing is paused."));
+                    item.set_state(DirState::Paused);
+                    continue;
+                }
+
                 // Get the list of local and remote files for this sync
                 // directory, and if they don't match the last sync state
                 // in the database, then continue with syncing.
-                let mut should_sync = false;
+                let mut should_sync = sync_now;
 
                 // Local file checks.
                 let local_glob = format!("{}/**/*", sync_dir.local_path);
@@ -1220,13 +2206,65 @@ pub fn launch(app: &Application, background: bool) {
                 }
 
                 // Remote file checks.
-                if let Ok(paths) = rclone::sync::list(
-                    &remote.name,
+                let remote_paths = rclone::sync::list_filtered(
+                    &sync_dir.rclone_remote(&remote.name),
                     &sync_dir.remote_path,
                     true,
                     RcloneListFilter::All,
-                ) {
+                    &Filters::from_sync_dir(&sync_dir).rclone_filter(),
+                );
+
+                // If the remote's login details are no longer valid, every other request to
+                // it is going to fail too, so stop syncing it until the user signs in again.
+                if let Err(err) = &remote_paths
+                    && err.kind == RcloneErrorKind::Auth
+                {
+                    set_remote_state(
+                        &stack,
+                        &remote_map,
+                        &directory_map,
+                        &remote.name,
+                        Some(RemoteState::NeedsSignIn),
+                    );
+                    continue 'remotes;
+                }
+
+                // Likewise if we can't reach the remote at all, so wait a bit before trying
+                // it again.
+                if let Err(err) = &remote_paths
+                    && err.kind == RcloneErrorKind::Network
+                {
+                    set_remote_disconnected(&stack, &remote_map, &directory_map, &remote.name);
+                    continue 'remotes;
+                }
+
+                // If the remote was previously disconnected, it's reachable again now.
+                if remote_paths.is_ok() {
+                    set_remote_state(&stack, &remote_map, &directory_map, &remote.name, None);
+                }
+
+                if let Ok(paths) = &remote_paths {
+                    let selection = Selection::load(&db, sync_dir.id);
+                    let symlink_policy = SymlinkPolicy::from_sync_dir(&sync_dir);
+
                     for path in paths {
+                        // Changes in unselected folders don't get synced.
+                        if selection.is_unselected(&util::strip_slashes(
+                            path.path
+                                .strip_prefix(&sync_dir.remote_path)
+                                .unwrap_or(&path.path),
+                        )) {
+                            continue;
+                        }
+
+                        // Neither do links that are being skipped.
+                        if symlink_policy == SymlinkPolicy::Skip
+                            && !path.is_dir
+                            && path.name.ends_with(rclone::LINK_SUFFIX)
+                        {
+                            continue;
+                        }
+
                         let stripped_path = match path.name.contains('/') {
                             true => path
                                 .name
@@ -1256,7 +2294,8 @@ pub fn launch(app: &Application, background: bool) {
                         }
                     }
                 } else {
-                    // TODO: We should show the disconnected icon instead of trying to sync again.
+                    // We couldn't list the remote for some other reason, so try syncing it to
+                    // find out what's wrong.
                     should_sync = true;
                 }
 
@@ -1270,22 +2309,19 @@ pub fn launch(app: &Application, background: bool) {
                 .unwrap();
 
                 for sync_item in sync_items {
-                    let remote_path = if !sync_dir.remote_path.is_empty() {
-                        format!("{}/{}", sync_dir.remote_path, sync_item.remote_path)
-                    } else {
-                        sync_item.remote_path.clone()
-                    };
-                    let maybe_remote_timestamp: Option<i32> =
-                        rclone::sync::stat(&remote.name, &remote_path)
-                            .ok()
-                            .flatten()
-                            .map(|remote_item| {
-                                remote_item.mod_time.unix_timestamp().try_into().unwrap()
-                            });
+                    // Remote paths are stored from the root of the remote, so they can be
+                    // checked as-is.
+                    let maybe_remote_timestamp: Option<i32> = rclone::sync::stat(
+                        &sync_dir.rclone_remote(&remote.name),
+                        &sync_item.remote_path,
+                    )
+                    .ok()
+                    .flatten()
+                    .map(|remote_item| remote_item.mod_time.unix_timestamp().try_into().unwrap());
 
                     // If the path doesn't exist both locally and on the remote, then we need to
-                    // delete the DB entry.
-                    if !Path::new(&sync_item.local_path).exists()
+                    // delete the DB entry. Links count as existing even if their target doesn't.
+                    if fs::symlink_metadata(&sync_item.local_path).is_err()
                         && maybe_remote_timestamp.is_none()
                     {
                         util::await_future(async {
@@ -1342,8 +2378,7 @@ pub fn launch(app: &Application, background: bool) {
 
                 if !should_sync {
                     item.status_text.set_label(&tr::tr!("Files are synced."));
-                    item.status_icon
-                        .set_child(Some(&get_image("object-select-symbolic")));
+                    item.set_state(DirState::Synced);
                     continue;
                 }
 
@@ -1361,16 +2396,7 @@ pub fn launch(app: &Application, background: bool) {
                 }
 
                 // Set up the UI for notifying the user that this directory is being synced.
-                // The width/height and margins for this are based on those from `get_image()`
-                // at the top of this file, as they're placed at the same place in the UI.
-                let spinner = Spinner::builder()
-                    .spinning(true)
-                    .width_request(4)
-                    .height_request(4)
-                    .margin_start(3)
-                    .margin_end(3)
-                    .build();
-                item.status_icon.set_child(Some(&spinner));
+                item.set_state(DirState::Syncing);
                 item.status_text
                     .set_label(&tr::tr!("Checking for changes..."));
                 // Dropping this is important, otherwise the pointer borrow might last a lot
@@ -1378,209 +2404,6 @@ pub fn launch(app: &Application, background: bool) {
                 // directory indexmap.
                 drop(item_ptr);
 
-                // Add an error for reporting in the UI.
-                let please_resolve_msg_tr = tr::tr!("Please resolve the reported syncing issues.");
-                let please_resolve_msg = " ".to_owned() + &please_resolve_msg_tr;
-                let add_error = glib::clone!(@strong db, @strong directory_map, @strong remote, @strong sync_dir, @strong sync_errors_count, @strong please_resolve_msg, @strong handle => move |error: SyncError| {
-                    let path_pair = (sync_dir.local_path.clone(), sync_dir.remote_path.clone());
-                    let ui_item = error.generate_ui();
-                    let ui_item_listbox = ListBoxRow::builder().child(&ui_item).build();
-
-                    // Generate the callback.
-                    let gesture = GestureClick::new();
-                    gesture.connect_released(glib::clone!(@strong directory_map, @strong remote, @strong path_pair, @strong db, @strong error, @weak ui_item, @weak ui_item_listbox, @strong please_resolve_msg => move |_, _, _, _| {
-                        ui_item.set_sensitive(false);
-                        let remove_ui_item = glib::clone!(@strong directory_map, @strong remote, @strong path_pair, @strong error, @weak ui_item_listbox, @strong please_resolve_msg => move || {
-                            let mut ptr = directory_map.get_mut_ref();
-                            let item = ptr.get_mut(&remote.name).unwrap().get_mut(&path_pair).unwrap();
-
-                            // Update the error brief on the main page.
-                            let error_text = item.error_status_text.text().to_string();
-                            let new_num_errors = error_text.split_whitespace().next().unwrap_or("0").parse::<i32>().unwrap() - 1;
-                            if new_num_errors == 0 {
-                                item.error_status_text.set_label("");
-                                let label_text = match item.status_text.text().as_str().strip_suffix(&please_resolve_msg) {
-                                    Some(text) => text.to_string(),
-                                    None => item.status_text.text().to_string()
-                                };
-                                item.status_text.set_label(&label_text);
-
-                            } else {
-                                let error_string = tr::tr!("{} errors found. ", new_num_errors);
-                                item.error_status_text.set_label(&error_string);
-                            }
-
-                            (item.update_error_ui)();
-
-                            // Update the sync dir's page and our code.
-                            item.error_items.remove(&error).unwrap();
-                            item.error_list.remove(&ui_item_listbox);
-                        });
-
-                        match &error {
-                            SyncError::General(_, _) => {
-                                let dialog = MessageDialog::builder()
-                                    .text(&tr::tr!("Would you like to dismiss this error?"))
-                                    .buttons(ButtonsType::YesNo)
-                                    .build();
-                                dialog.connect_close_request(glib::clone!(@strong ui_item => move |_| {
-                                    ui_item.set_sensitive(true);
-                                    Inhibit(false)
-                                }));
-                                dialog.connect_response(glib::clone!(@strong directory_map, @strong remote, @strong path_pair, @weak ui_item, @strong error, @strong remove_ui_item => move |dialog, resp| {
-                                    match resp {
-                                        ResponseType::Yes => {
-                                            remove_ui_item();
-                                        },
-                                        ResponseType::No => {
-                                            ui_item.set_sensitive(true);
-                                        },
-                                        _ => return,
-                                    }
-
-                                    dialog.close();
-                                }));
-                                dialog.show();
-                            },
-                            SyncError::BothMoreCurrent(local_item, remote_item) => {
-                                let local_item_formatted = util::fmt_home(local_item);
-                                let local_path = Path::new(&local_item);
-                                let sync_local_to_remote = glib::clone!(@strong remote, @strong local_item_formatted, @strong local_item, @strong remote_item => move || {
-                                    if let Err(err) = rclone::sync::copy_to_remote(&local_item, &remote.name, &remote_item) {
-                                        gtk_util::show_error(&tr::tr!("Failed to sync '{}' to '{}' on remote.", local_item_formatted, remote_item), Some(&err.error));
-                                        Err(())
-                                    } else {
-                                        Ok(())
-                                    }
-                                });
-                                let sync_remote_to_local = glib::clone!(@strong remote, @strong local_item_formatted, @strong local_item, @strong remote_item => move || {
-                                    if let Err(err) = rclone::sync::copy_to_local(&local_item, &remote.name, &remote_item) {
-                                        gtk_util::show_error(&tr::tr!("Failed to sync '{}' on remote to '{}'.", remote_item, local_item_formatted), Some(&err.error));
-                                        Err(())
-                                    } else {
-                                        Ok(())
-                                    }
-                                });
-                                let local_item = local_item.clone();
-                                let update_db_item = glib::clone!(@strong db, @strong remote, @strong local_item, @strong remote_item => move || {
-                                    let local_timestamp = Path::new(&local_item).metadata().unwrap().modified().unwrap().duration_since(SystemTime::UNIX_EPOCH).unwrap().as_secs();
-                                    let remote_timestamp = rclone::sync::stat(&remote.name, &remote_item).unwrap().unwrap().mod_time.unix_timestamp();
-                                    let mut active_model: SyncItemsActiveModel = util::await_future(SyncItemsEntity::find()
-                                        .filter(SyncItemsColumn::LocalPath.eq(local_item.clone()))
-                                        .filter(SyncItemsColumn::RemotePath.eq(remote_item.clone()))
-                                        .one(&db)
-                                    ).unwrap()
-                                    .unwrap()
-                                    .into();
-                                    active_model.last_local_timestamp = ActiveValue::set(local_timestamp.try_into().unwrap());
-                                    active_model.last_remote_timestamp = ActiveValue::Set(remote_timestamp.try_into().unwrap());
-                                    util::await_future(active_model.update(&db)).unwrap();
-                                });
-                                let rclone_remote_item = match rclone::sync::stat(&remote.name, remote_item) {
-                                    Ok(item) => item,
-                                    Err(err) => {
-                                        gtk_util::show_error(
-                                            &tr::tr!("Unable to fetch data for '{}' from the remote.", remote_item),
-                                            Some(&err.error)
-                                        );
-                                        return;
-                                    }
-                                };
-
-                                // If neither the local item or the remote item exist anymore, this error is no longer relevant.
-                                if !local_path.exists() && rclone_remote_item.is_none() {
-                                    gtk_util::show_error(&tr::tr!("File Update"), Some(&tr::tr!("Neither the local item or remote item exists anymore. This error will now be removed.")));
-                                    remove_ui_item();
-                                    return;
-                                // Otherwise if only the local exists, use that.
-                                } else if local_path.exists() && rclone_remote_item.is_none() {
-                                    gtk_util::show_error(&tr::tr!("File Update"), Some(&tr::tr!("Only the local item exists now, so it will be synced to the remote.")));
-                                    if sync_local_to_remote().is_ok() {
-                                        update_db_item();
-                                        remove_ui_item();
-                                        return;
-                                    }
-                                // Otherwise if only the remote exists, use that.
-                                } else if !local_path.exists() && rclone_remote_item.is_some() {
-                                    gtk_util::show_error(&tr::tr!("File Update"), Some(&tr::tr!("Only the remote item exists now, so it will be synced to the local machine.")));
-                                    if sync_remote_to_local().is_ok() {
-                                        update_db_item();
-                                        remove_ui_item();
-                                        return;
-                                    }
-                                }
-
-                                let dialog = MessageDialog::builder()
-                                    .text(
-                                        &tr::tr!("Both the local item '{}' and remote item '{}' have been updated since the last sync.", local_item_formatted, remote_item)
-                                    )
-                                    .secondary_text(&tr::tr!("Which item would you like to keep?"))
-                                    .build();
-                                dialog.add_button(&tr::tr!("Local"), ResponseType::Other(0));
-                                dialog.add_button(&tr::tr!("Remote"), ResponseType::Other(1));
-                                dialog.connect_close_request(glib::clone!(@strong ui_item => move |_| {
-                                    ui_item.set_sensitive(true);
-                                    Inhibit(false)
-                                }));
-                                dialog.connect_response(glib::clone!(@strong directory_map, @strong remote, @strong path_pair, @weak ui_item, @strong error, @strong local_item, @strong remote_item, @strong local_path, @strong rclone_remote_item, @strong sync_local_to_remote, @strong sync_remote_to_local => move |dialog, resp| {
-                                    match resp {
-                                        ResponseType::Other(0) => {
-                                            if sync_local_to_remote().is_ok() {
-                                                update_db_item();
-                                                remove_ui_item();
-                                            }
-                                        },
-                                        ResponseType::Other(1) => {
-                                            if sync_remote_to_local().is_ok() {
-                                                update_db_item();
-                                                remove_ui_item();
-                                            }
-                                        },
-                                        ResponseType::Other(_) => unreachable!(),
-                                        _ => return
-                                    }
-
-                                    dialog.close();
-                                }));
-
-                                dialog.show();
-                            }
-                        }
-                    }));
-                    ui_item.add_controller(&gesture);
-
-                    // If we have zero errors now, remove the warning icon.
-                    if sync_errors_count() == 0 {
-                        handle.update(|tray| tray.set_syncing());
-                    }
-
-                    // Report the brief on the number of errors.
-                    let mut ptr = directory_map.get_mut_ref();
-                    let item = ptr
-                        .get_mut(&remote.name)
-                        .unwrap()
-                        .get_mut(&path_pair)
-                        .unwrap();
-
-                    let error_text = item.error_status_text.text().to_string();
-                    let new_num_errors = error_text.split_whitespace().next().unwrap_or("0").parse::<i32>().unwrap() + 1;
-
-                    let error_string = if new_num_errors == 1 {
-                        tr::tr!("1 error found.")
-                    } else {
-                        tr::tr!("{} errors found.", new_num_errors)
-                    };
-                    item.error_status_text.set_label(&(error_string + " "));
-
-                    // Add the error to the UI.
-                    item.error_list.append(&ui_item_listbox);
-                    item.error_items.insert(error, ui_item);
-                    (item.update_error_ui)();
-
-                    // Set the tray icon to show the warning icon.
-                    handle.update(|tray| tray.set_warning());
-                });
-
                 // A vector of local/remote sync item pairs to make sure we don't sync anything
                 // twice between 'sync_local_directory' and 'sync_remote_directory' below. It
                 // also prevents errors from showing up twice when they occur. We have to wrap
@@ -1589,7 +2412,7 @@ pub fn launch(app: &Application, background: bool) {
                 let synced_items: RefCell<Vec<(String, String)>> = RefCell::new(vec![]);
 
                 // Get any pending deletion requests and process them.
-                let process_deletion_requests = glib::clone!(@strong db, @weak stack, @strong directory_map, @strong remote_deletion_queue, @strong sync_dir_deletion_queue => move || {
+                let process_deletion_requests = glib::clone!(@strong db, @weak stack, @strong remote_map, @strong directory_map, @strong remote_deletion_queue, @strong sync_dir_deletion_queue => move || {
                     let mut dmap = directory_map.get_mut_ref();
                     let mut remote_queue = remote_deletion_queue.get_mut_ref();
                     let mut dir_queue = sync_dir_deletion_queue.get_mut_ref();
@@ -1607,22 +2430,15 @@ pub fn launch(app: &Application, background: bool) {
                         dmap.get_mut(&queue_item.0).unwrap().remove(&dir_pair).unwrap();
 
                         // Remove the item from the database.
-                        util::await_future(async {
-                            let sync_dir = SyncDirsEntity::find()
+                        let sync_dir = util::await_future(
+                            SyncDirsEntity::find()
                                 .filter(SyncDirsColumn::LocalPath.eq(queue_item.1.clone()))
                                 .filter(SyncDirsColumn::RemotePath.eq(queue_item.2.clone()))
-                                .one(&db)
-                                .await
-                                .unwrap()
-                                .unwrap();
-
-                            SyncItemsEntity::delete_many()
-                                .filter(SyncItemsColumn::SyncDirId.eq(sync_dir.id))
-                                .exec(&db)
-                                .await
-                                .unwrap();
-                            sync_dir.delete(&db).await.unwrap();
-                        });
+                                .one(&db),
+                        )
+                        .unwrap()
+                        .unwrap();
+                        util::delete_sync_dir(&db, sync_dir);
                     }
 
                     // Process remote deletions.
@@ -1632,34 +2448,27 @@ pub fn launch(app: &Application, background: bool) {
                         // Remove the item from the UI.
                         let child = stack.child_by_name(&remote_name).unwrap();
                         stack.remove(&child);
+                        remote_map.get_mut_ref().remove(&remote_name);
 
                         // Delete all related database entries.
-                        util::await_future(async {
-                            let db_remote = RemotesEntity::find()
+                        let db_remote = util::await_future(
+                            RemotesEntity::find()
                                 .filter(RemotesColumn::Name.eq(remote_name.clone()))
-                                .one(&db)
-                                .await
-                                .unwrap()
-                                .unwrap();
-                            let sync_dirs = SyncDirsEntity::find()
+                                .one(&db),
+                        )
+                        .unwrap()
+                        .unwrap();
+                        let sync_dirs = util::await_future(
+                            SyncDirsEntity::find()
                                 .filter(SyncDirsColumn::RemoteId.eq(db_remote.id))
-                                .all(&db)
-                                .await
-                                .unwrap();
-
-                            for sync_dir in sync_dirs {
-                                SyncItemsEntity::delete_many()
-                                    .filter(SyncItemsColumn::SyncDirId.eq(sync_dir.id))
-                                    .exec(&db)
-                                    .await
-                                    .unwrap();
-                                sync_dir.delete(&db).await.unwrap();
-                            }
-
-                            db_remote.delete(&db).await.unwrap();
-                        });
+                                .all(&db),
+                        )
+                        .unwrap();
 
-                        // Delete the Rclone config.
+                        for sync_dir in sync_dirs {
+                            util::delete_sync_dir(&db, sync_dir);
+                        }
+                        util::await_future(db_remote.delete(&db)).unwrap();
                         rclone::sync::delete_config(&remote_name).unwrap();
                     }
                 });
@@ -1682,6 +2491,9 @@ pub fn launch(app: &Application, background: bool) {
                     db: &DatabaseConnection,
                     directory_map: &DirectoryMap,
                     synced_items: &RefCell<Vec<(String, String)>>,
+                    parent_exclusions: &exclude::Exclusions,
+                    name_rules: NameRules,
+                    name_limits: &NameLimits,
                     add_error: F1,
                     check_open_requests: F2,
                     process_deletion_requests: F3,
@@ -1702,6 +2514,7 @@ pub fn launch(app: &Application, background: bool) {
                         let status_string =
                             tr::tr!("Checking '{}' for changes...", util::fmt_home(dir));
                         item.status_text.set_label(&status_string);
+                        item.set_progress(dir, synced_items.borrow().len());
                     };
                     update_ui_progress(&dir_string);
                     let directory = match fs::read_dir(local_dir) {
@@ -1712,30 +2525,53 @@ pub fn launch(app: &Application, background: bool) {
                         }
                     };
 
-                    // Get the list of ignore globs.
-                    let ignore_file_string =
-                        format!("{}/{}", sync_dir.local_path, FILE_IGNORE_NAME);
-                    let ignore_file_path = Path::new(&ignore_file_string);
-                    let ignore_globs = if ignore_file_path.exists() {
-                        let _lock = FileLock::lock(
-                            &ignore_file_string,
-                            true,
-                            FileOptions::new().write(true).read(true),
-                        )
-                        .unwrap();
-                        let file_content = fs::read_to_string(ignore_file_path).unwrap();
-                        let mut globs = vec![];
-
-                        for line in file_content.lines() {
-                            if let Ok(pattern) = glob::Pattern::new(line) {
-                                globs.push(pattern);
-                            }
+                    // Get the exclusions that apply to this directory.
+                    let exclusions = parent_exclusions.for_dir(
+                        sync_dir,
+                        dir_string
+                            .strip_prefix(&sync_dir.local_path)
+                            .unwrap_or_default(),
+                    );
+                    for invalid in exclusions.invalid() {
+                        add_error(SyncError::General(invalid.file.clone(), invalid.message()));
+                    }
+                    let filters = Filters::from_sync_dir(sync_dir);
+                    let selection = Selection::load(db, sync_dir.id);
+                    let symlink_policy = SymlinkPolicy::from_sync_dir(sync_dir);
+
+                    // Items whose names collide under the remote's rules would overwrite each
+                    // other there, so none of them get synced until they're renamed.
+                    let directory: Vec<_> = directory.collect();
+                    let names: Vec<String> = directory
+                        .iter()
+                        .filter_map(|item| item.as_ref().ok())
+                        .filter(|item| {
+                            let local_path = item.path().to_string_lossy().to_string();
+                            let relative_path = local_path
+                                .strip_prefix(&format!("{}/", sync_dir.local_path))
+                                .unwrap_or_default();
+                            let is_dir = item.file_type().is_ok_and(|file_type| file_type.is_dir());
+                            !exclusions.is_excluded(relative_path, is_dir)
+                        })
+                        .map(|item| item.file_name().to_string_lossy().to_string())
+                        .collect();
+                    let mut collided_names = HashSet::new();
+                    for group in name_rules
+                        .group(names.iter().map(String::as_str))
+                        .into_values()
+                    {
+                        if group.len() < 2 {
+                            continue;
                         }
 
-                        globs
-                    } else {
-                        vec![]
-                    };
+                        for name in &group[1..] {
+                            add_error(SyncError::NameCollision(
+                                format!("{dir_string}/{name}"),
+                                format!("{dir_string}/{}", group[0]),
+                            ));
+                        }
+                        collided_names.extend(group);
+                    }
 
                     for item in directory {
                         // If a close request was sent in, stop syncing this remote so we can quit
@@ -1753,14 +2589,58 @@ pub fn launch(app: &Application, background: bool) {
                             break;
                         }
 
-                        if let Err(err) = item {
-                            add_error(SyncError::General(dir_string.clone(), err.to_string()));
+                        if let Err(err) = item {
+                            add_error(SyncError::General(dir_string.clone(), err.to_string()));
+                            continue;
+                        }
+                        let item = item.unwrap();
+                        let local_path = item.path().to_str().unwrap().to_owned();
+
+                        if collided_names.contains(item.file_name().to_string_lossy().as_ref()) {
+                            continue;
+                        }
+
+                        // Handle symbolic links according to the directory's policy.
+                        let is_symlink = item
+                            .file_type()
+                            .is_ok_and(|file_type| file_type.is_symlink());
+                        let follow_symlink = is_symlink && symlink_policy == SymlinkPolicy::Follow;
+                        let store_symlink = is_symlink && symlink_policy == SymlinkPolicy::Links;
+                        if is_symlink && symlink_policy == SymlinkPolicy::Skip {
                             continue;
                         }
-                        let item = item.unwrap();
-                        let local_path = item.path().to_str().unwrap().to_owned();
+                        if follow_symlink {
+                            match fs::metadata(item.path()) {
+                                Ok(metadata) => {
+                                    if metadata.is_dir() && symlinks::creates_loop(&item.path()) {
+                                        add_error(SyncError::General(
+                                            local_path.clone(),
+                                            tr::tr!("This symbolic link loops back to one of its parent folders, so it can't be followed."),
+                                        ));
+                                        continue;
+                                    }
+                                }
+                                Err(err) => {
+                                    add_error(SyncError::General(
+                                        local_path.clone(),
+                                        tr::tr!("Unable to follow symbolic link [{}].", err),
+                                    ));
+                                    continue;
+                                }
+                            }
+                        }
+                        // The metadata of the item, which is that of the link's target when it's
+                        // being followed.
+                        let item_metadata = || {
+                            if follow_symlink {
+                                fs::metadata(item.path())
+                            } else {
+                                item.metadata()
+                            }
+                        };
 
-                        // The path from the root of the remote.
+                        // The path from the root of the remote. Links that are being stored get
+                        // [`rclone::LINK_SUFFIX`] added, the same as Rclone does.
                         let remote_path = {
                             let local_path_stripped = local_path
                                 .strip_prefix(&format!("{}/", sync_dir.local_path))
@@ -1770,40 +2650,103 @@ pub fn launch(app: &Application, background: bool) {
                                 None => local_path_stripped,
                             };
 
-                            if sync_dir.remote_path.is_empty() {
+                            let remote_path = if sync_dir.remote_path.is_empty() {
                                 stripped_path.to_owned()
                             } else {
                                 sync_dir.remote_path.clone() + "/" + stripped_path
+                            };
+
+                            if store_symlink {
+                                remote_path + rclone::LINK_SUFFIX
+                            } else {
+                                remote_path
                             }
                         };
-                        // The above path, with `sync_dir.remote_path` stripped from it.
-                        let stripped_remote_path =
-                            if remote_path.contains('/') && sync_dir.remote_path.contains('/') {
-                                remote_path
-                                    .strip_prefix(&format!("{}/", sync_dir.remote_path))
-                                    .unwrap()
-                                    .to_owned()
-                            } else {
-                                remote_path.clone()
-                            };
 
                         update_ui_progress(&local_path);
-                        // If this item matches the ignore list, don't sync it.
-                        if ignore_globs
-                            .iter()
-                            .filter(|pattern| pattern.matches(&stripped_remote_path))
-                            .count()
-                            > 0
-                        {
+                        // If this item matches the exclusions, don't sync it.
+                        let relative_path = local_path
+                            .strip_prefix(&format!("{}/", sync_dir.local_path))
+                            .unwrap();
+                        let is_dir = if follow_symlink {
+                            item.path().is_dir()
+                        } else {
+                            item.file_type().is_ok_and(|file_type| file_type.is_dir())
+                        };
+                        if exclusions.is_excluded(relative_path, is_dir) {
+                            continue;
+                        }
+
+                        // Likewise if it doesn't pass the directory's filters.
+                        if let Ok(metadata) = item_metadata() {
+                            let mod_time = metadata
+                                .modified()
+                                .ok()
+                                .and_then(|time| time.duration_since(SystemTime::UNIX_EPOCH).ok())
+                                .map_or(0, |duration| duration.as_secs() as i64);
+
+                            if filters.is_filtered(
+                                &item.file_name().to_string_lossy(),
+                                is_dir,
+                                metadata.len(),
+                                mod_time,
+                            ) {
+                                continue;
+                            }
+                        }
+
+                        // Items whose names aren't accepted by the remote need to be renamed
+                        // before they can be synced. The name is checked as it'll be stored on
+                        // the remote, which is different for links that are being stored.
+                        let stored_name = remote_path.rsplit('/').next().unwrap();
+                        if let Some(problem) = name_limits.problem(stored_name) {
+                            add_error(SyncError::InvalidName(local_path.clone(), problem));
                             continue;
                         }
 
+                        // Items in unselected folders can still be uploaded, but never get
+                        // downloaded.
+                        let unselected = selection.is_unselected(relative_path);
+
                         synced_items
                             .borrow_mut()
                             .push((local_path.clone(), remote_path.clone()));
 
+                        let db_item = util::await_future(
+                            SyncItemsEntity::find()
+                                .filter(SyncItemsColumn::LocalPath.eq(local_path.clone()))
+                                .filter(SyncItemsColumn::RemotePath.eq(remote_path.clone()))
+                                .one(db),
+                        )
+                        .unwrap();
+
+                        // Items in unselected folders that were already synced are left alone, as
+                        // their local copies aren't kept up to date and would otherwise overwrite
+                        // (or bring back) what's on the remote. Anything newly created inside of
+                        // them still gets uploaded though.
+                        if unselected && db_item.is_some() {
+                            if is_dir {
+                                sync_local_directory(
+                                    &item.path(),
+                                    remote,
+                                    sync_dir,
+                                    db,
+                                    directory_map,
+                                    synced_items,
+                                    &exclusions,
+                                    name_rules,
+                                    name_limits,
+                                    add_error.clone(),
+                                    check_open_requests.clone(),
+                                    process_deletion_requests.clone(),
+                                );
+                                update_ui_progress(&local_path);
+                            }
+                            continue;
+                        }
+
                         let get_local_file_timestamp = || {
-                            item.metadata()
+                            item_metadata()
                                 .unwrap()
                                 .modified()
                                 .unwrap()
@@ -1812,7 +2755,10 @@ pub fn launch(app: &Application, background: bool) {
                                 .as_secs()
                         };
                         let local_utc_timestamp = get_local_file_timestamp();
-                        let remote_item = match rclone::sync::stat(&remote.name, &remote_path) {
+                        let remote_item = match rclone::sync::stat(
+                            &sync_dir.rclone_remote(&remote.name),
+                            &remote_path,
+                        ) {
                             Ok(item) => item,
                             Err(err) => {
                                 add_error(SyncError::General(remote_path.clone(), err.error));
@@ -1822,28 +2768,20 @@ pub fn launch(app: &Application, background: bool) {
                         let remote_utc_timestamp = remote_item
                             .as_ref()
                             .map(|item| item.mod_time.unix_timestamp());
-                        let db_item = util::await_future(
-                            SyncItemsEntity::find()
-                                .filter(SyncItemsColumn::LocalPath.eq(local_path.clone()))
-                                .filter(SyncItemsColumn::RemotePath.eq(remote_path.clone()))
-                                .one(db),
-                        )
-                        .unwrap();
 
                         // Push the item to the remote. Returns the
                         // [`crate::rclone::sync::RcloneRemoteItem`] of the item on the remote, or
                         // an [`Err<()>`] if an issue occurred (all errors are automatically added
                         // via `add_errors`).
                         let push_local_to_remote = || -> Result<rclone::RcloneRemoteItem, ()> {
-                            let file_type = item.file_type().unwrap();
-
                             if let Some(rclone_item) = &remote_item {
-                                let same_type = file_type.is_dir() && rclone_item.is_dir;
+                                let same_type = is_dir && rclone_item.is_dir;
 
                                 if !same_type {
-                                    if let Err(err) =
-                                        rclone::sync::purge(&remote.name, &remote_path)
-                                    {
+                                    if let Err(err) = rclone::sync::purge(
+                                        &sync_dir.rclone_remote(&remote.name),
+                                        &remote_path,
+                                    ) {
                                         add_error(SyncError::General(
                                             remote_path.clone(),
                                             err.error,
@@ -1853,8 +2791,11 @@ pub fn launch(app: &Application, background: bool) {
                                 }
                             }
 
-                            if file_type.is_dir() {
-                                if let Err(err) = rclone::sync::mkdir(&remote.name, &remote_path) {
+                            if is_dir {
+                                if let Err(err) = rclone::sync::mkdir(
+                                    &sync_dir.rclone_remote(&remote.name),
+                                    &remote_path,
+                                ) {
                                     add_error(SyncError::General(remote_path.clone(), err.error));
                                     return Err(());
                                 }
@@ -1865,34 +2806,78 @@ pub fn launch(app: &Application, background: bool) {
                                     db,
                                     directory_map,
                                     synced_items,
+                                    &exclusions,
+                                    name_rules,
+                                    name_limits,
                                     add_error.clone(),
                                     check_open_requests.clone(),
                                     process_deletion_requests.clone(),
                                 );
                                 update_ui_progress(&local_path);
-                            } else if let Err(err) = rclone::sync::copy_to_remote(
-                                &local_path,
-                                &remote.name,
-                                &remote_path,
-                            ) {
+                            } else if let Err(err) = if store_symlink {
+                                rclone::sync::copy_link_to_remote(
+                                    &local_path,
+                                    &sync_dir.rclone_remote(&remote.name),
+                                    &remote_path,
+                                )
+                            } else {
+                                rclone::sync::copy_to_remote(
+                                    &local_path,
+                                    &sync_dir.rclone_remote(&remote.name),
+                                    &remote_path,
+                                )
+                            } {
                                 add_error(SyncError::General(local_path.clone(), err.error));
                                 return Err(());
+                            } else {
+                                activity::record(
+                                    db,
+                                    sync_dir,
+                                    ActivityKind::Push,
+                                    &local_path,
+                                    &remote_path,
+                                    item_metadata().ok().map(|metadata| metadata.len()),
+                                    "",
+                                );
                             }
 
-                            Ok(rclone::sync::stat(&remote.name, &remote_path)
-                                .unwrap()
-                                .unwrap())
+                            Ok(rclone::sync::stat(
+                                &sync_dir.rclone_remote(&remote.name),
+                                &remote_path,
+                            )
+                            .unwrap()
+                            .unwrap())
                         };
                         // Pull the item from the remote.
                         let pull_remote_to_local = || -> Result<(), ()> {
-                            let file_type = item.file_type().unwrap();
-                            let same_type =
-                                file_type.is_dir() && remote_item.as_ref().unwrap().is_dir;
+                            let same_type = is_dir && remote_item.as_ref().unwrap().is_dir;
+
+                            // Nothing gets downloaded into unselected folders, but anything
+                            // created locally inside of them still needs to be uploaded.
+                            if unselected {
+                                if same_type {
+                                    sync_local_directory(
+                                        &item.path(),
+                                        remote,
+                                        sync_dir,
+                                        db,
+                                        directory_map,
+                                        synced_items,
+                                        &exclusions,
+                                        name_rules,
+                                        name_limits,
+                                        add_error.clone(),
+                                        check_open_requests.clone(),
+                                        process_deletion_requests.clone(),
+                                    );
+                                    update_ui_progress(&local_path);
+                                }
+
+                                return Err(());
+                            }
 
                             if !same_type {
-                                if file_type.is_dir()
-                                    && let Err(err) = fs::remove_dir_all(item.path())
-                                {
+                                if is_dir && let Err(err) = fs::remove_dir_all(item.path()) {
                                     add_error(SyncError::General(
                                         local_path.clone(),
                                         err.to_string(),
@@ -1907,7 +2892,7 @@ pub fn launch(app: &Application, background: bool) {
                                 }
                             }
 
-                            if file_type.is_dir() {
+                            if is_dir {
                                 sync_local_directory(
                                     &item.path(),
                                     remote,
@@ -1915,16 +2900,45 @@ pub fn launch(app: &Application, background: bool) {
                                     db,
                                     directory_map,
                                     synced_items,
+                                    &exclusions,
+                                    name_rules,
+                                    name_limits,
                                     add_error.clone(),
                                     check_open_requests.clone(),
                                     process_deletion_requests.clone(),
                                 );
                                 update_ui_progress(&local_path);
-                            } else if let Err(err) =
-                                rclone::sync::copy_to_local(&local_path, &remote.name, &remote_path)
-                            {
+                            } else if let Err(err) = versions::save(db, sync_dir, &local_path) {
+                                add_error(SyncError::General(remote_path.clone(), err));
+                                return Err(());
+                            } else if let Err(err) = if store_symlink {
+                                rclone::sync::copy_link_to_local(
+                                    &local_path,
+                                    &sync_dir.rclone_remote(&remote.name),
+                                    &remote_path,
+                                )
+                            } else {
+                                rclone::sync::copy_to_local(
+                                    &local_path,
+                                    &sync_dir.rclone_remote(&remote.name),
+                                    &remote_path,
+                                    remote_item.as_ref().and_then(|item| item.mode()),
+                                )
+                            } {
                                 add_error(SyncError::General(remote_path.clone(), err.error));
                                 return Err(());
+                            } else {
+                                activity::record(
+                                    db,
+                                    sync_dir,
+                                    ActivityKind::Pull,
+                                    &local_path,
+                                    &remote_path,
+                                    remote_item
+                                        .as_ref()
+                                        .and_then(|item| item.size.try_into().ok()),
+                                    "",
+                                );
                             }
 
                             Ok(())
@@ -1949,13 +2963,17 @@ pub fn launch(app: &Application, background: bool) {
                         // If we have a record of the last sync, use that to aid in timestamp
                         // checks.
                         if let Some(db_model) = db_item {
-                            let update_db_item = |local_timestamp, remote_timestamp| {
+                            let update_db_item = |local_timestamp, remote_timestamp, remote_id| {
                                 let mut active_model: SyncItemsActiveModel =
                                     db_model.clone().into();
                                 active_model.last_local_timestamp =
                                     ActiveValue::Set(local_timestamp);
                                 active_model.last_remote_timestamp =
                                     ActiveValue::Set(remote_timestamp);
+                                active_model.local_inode = ActiveValue::Set(
+                                    item_metadata().ok().map(|metadata| moves::inode(&metadata)),
+                                );
+                                active_model.remote_id = ActiveValue::Set(remote_id);
                                 util::await_future(active_model.update(db)).unwrap();
                             };
 
@@ -1972,7 +2990,7 @@ pub fn launch(app: &Application, background: bool) {
                                 // it's probably because one of the items in the directory got
                                 // updated anyway.
                                 if let Some(r_item) = remote_item
-                                    && (!item.path().is_dir() || !r_item.is_dir)
+                                    && (!is_dir || !r_item.is_dir)
                                 {
                                     add_error(SyncError::BothMoreCurrent(
                                         local_path.clone(),
@@ -1985,6 +3003,7 @@ pub fn launch(app: &Application, background: bool) {
                                     update_db_item(
                                         get_local_file_timestamp().try_into().unwrap(),
                                         rclone_item.mod_time.unix_timestamp().try_into().unwrap(),
+                                        rclone_item.id,
                                     );
                                     continue;
                                 } else {
@@ -2000,6 +3019,7 @@ pub fn launch(app: &Application, background: bool) {
                                     update_db_item(
                                         get_local_file_timestamp().try_into().unwrap(),
                                         remote_timestamp.try_into().unwrap(),
+                                        remote_item.as_ref().and_then(|item| item.id.clone()),
                                     );
                                 }
                             // The item is missing from the remote, but the last
@@ -2009,7 +3029,7 @@ pub fn launch(app: &Application, background: bool) {
                             } else if remote_item.is_none()
                                 && local_utc_timestamp == db_model.last_local_timestamp as u64
                             {
-                                if item.path().is_dir() {
+                                if is_dir {
                                     if let Err(err) = fs::remove_dir_all(&local_path) {
                                         add_error(SyncError::General(
                                             local_path.clone(),
@@ -2025,6 +3045,15 @@ pub fn launch(app: &Application, background: bool) {
                                     continue;
                                 }
 
+                                activity::record(
+                                    db,
+                                    sync_dir,
+                                    ActivityKind::DeleteLocal,
+                                    &local_path,
+                                    &remote_path,
+                                    None,
+                                    "",
+                                );
                                 delete_db_entry();
                                 continue;
                             // Both the local and remote item remain unchanged -
@@ -2053,15 +3082,36 @@ pub fn launch(app: &Application, background: bool) {
                                 } else if pull_remote_to_local().is_err() {
                                     continue;
                                 }
-                            // Otherwise the remote item didn't exist, so just
-                            // sync our local copy.
-                            } else if push_local_to_remote().is_err() {
-                                continue;
-                            }
-
-                            // The remote item is now guaranteed to exist, so fetch it.
-                            let remote_item_safe =
-                                match rclone::sync::stat(&remote.name, &remote_path) {
+                            // If the remote item didn't exist because the local
+                            // item was moved from somewhere that's already been
+                            // synced, move the remote item to match instead of
+                            // uploading it again.
+                            } else if let Some(moved) = item_metadata().ok().and_then(|metadata| {
+                                moves::find_local_move(
+                                    db,
+                                    sync_dir,
+                                    &sync_dir.rclone_remote(&remote.name),
+                                    &local_path,
+                                    &metadata,
+                                )
+                            }) {
+                                let rclone_remote = sync_dir.rclone_remote(&remote.name);
+                                let move_result = if is_dir {
+                                    rclone::sync::move_dir(
+                                        &rclone_remote,
+                                        &moved.remote_path,
+                                        &remote_path,
+                                    )
+                                } else {
+                                    rclone::sync::move_file(
+                                        &rclone_remote,
+                                        &moved.remote_path,
+                                        &remote_path,
+                                    )
+                                };
+                                let moved_item = match move_result
+                                    .and_then(|_| rclone::sync::stat(&rclone_remote, &remote_path))
+                                {
                                     Ok(item) => item.unwrap(),
                                     Err(err) => {
                                         add_error(SyncError::General(
@@ -2071,7 +3121,65 @@ pub fn launch(app: &Application, background: bool) {
                                         continue;
                                     }
                                 };
-                            match rclone::sync::stat(&remote.name, &remote_path) {
+
+                                moves::update_paths(
+                                    db,
+                                    &moved,
+                                    &local_path,
+                                    &remote_path,
+                                    &moved_item,
+                                );
+                                activity::record(
+                                    db,
+                                    sync_dir,
+                                    ActivityKind::MoveRemote,
+                                    &local_path,
+                                    &remote_path,
+                                    None,
+                                    &tr::tr!("Moved from '/{}'.", moved.remote_path),
+                                );
+
+                                // Anything inside of a moved folder still needs checking for
+                                // changes.
+                                if is_dir {
+                                    sync_local_directory(
+                                        &item.path(),
+                                        remote,
+                                        sync_dir,
+                                        db,
+                                        directory_map,
+                                        synced_items,
+                                        &exclusions,
+                                        name_rules,
+                                        name_limits,
+                                        add_error.clone(),
+                                        check_open_requests.clone(),
+                                        process_deletion_requests.clone(),
+                                    );
+                                    update_ui_progress(&local_path);
+                                }
+                                continue;
+                            // Otherwise the remote item didn't exist, so just
+                            // sync our local copy.
+                            } else if push_local_to_remote().is_err() {
+                                continue;
+                            }
+
+                            // The remote item is now guaranteed to exist, so fetch it.
+                            let remote_item_safe = match rclone::sync::stat(
+                                &sync_dir.rclone_remote(&remote.name),
+                                &remote_path,
+                            ) {
+                                Ok(item) => item.unwrap(),
+                                Err(err) => {
+                                    add_error(SyncError::General(remote_path.clone(), err.error));
+                                    continue;
+                                }
+                            };
+                            match rclone::sync::stat(
+                                &sync_dir.rclone_remote(&remote.name),
+                                &remote_path,
+                            ) {
                                 Ok(item) => item.unwrap(),
                                 Err(err) => {
                                     add_error(SyncError::General(remote_path.clone(), err.error));
@@ -2095,6 +3203,12 @@ pub fn launch(app: &Application, background: bool) {
                                             .try_into()
                                             .unwrap(),
                                     ),
+                                    local_inode: ActiveValue::Set(
+                                        item_metadata()
+                                            .ok()
+                                            .map(|metadata| moves::inode(&metadata)),
+                                    ),
+                                    remote_id: ActiveValue::Set(remote_item_safe.id),
                                     ..Default::default()
                                 }
                                 .insert(db),
@@ -2120,35 +3234,51 @@ pub fn launch(app: &Application, background: bool) {
                     db: &DatabaseConnection,
                     directory_map: &DirectoryMap,
                     synced_items: &RefCell<Vec<(String, String)>>,
+                    parent_exclusions: &exclude::Exclusions,
+                    name_rules: NameRules,
                     add_error: F1,
                     check_open_requests: F2,
                     process_deletion_requests: F3,
                 ) {
                     process_deletion_requests();
 
-                    let ignore_file_string =
-                        format!("{}/{}", sync_dir.local_path, FILE_IGNORE_NAME);
-                    let ignore_file_path = Path::new(&ignore_file_string);
-                    let ignore_globs = if ignore_file_path.exists() {
-                        let _lock = FileLock::lock(
-                            ignore_file_path,
-                            true,
-                            FileOptions::new().write(true).read(true),
-                        )
-                        .unwrap();
-                        let file_content = fs::read_to_string(ignore_file_path).unwrap();
-                        let mut globs = vec![];
-
-                        for line in file_content.lines() {
-                            if let Ok(pattern) = glob::Pattern::new(line) {
-                                globs.push(pattern);
-                            }
+                    let exclusions = parent_exclusions.for_dir(
+                        sync_dir,
+                        remote_dir
+                            .strip_prefix(&sync_dir.remote_path)
+                            .unwrap_or_default(),
+                    );
+                    for invalid in exclusions.invalid() {
+                        add_error(SyncError::General(invalid.file.clone(), invalid.message()));
+                    }
+                    let filters = Filters::from_sync_dir(sync_dir);
+                    let selection = Selection::load(db, sync_dir.id);
+                    let symlink_policy = SymlinkPolicy::from_sync_dir(sync_dir);
+
+                    // The names of the items in the matching local directory, grouped under the
+                    // remote's rules for comparing names.
+                    let local_dir = {
+                        let relative_dir = util::strip_slashes(
+                            remote_dir
+                                .strip_prefix(&sync_dir.remote_path)
+                                .unwrap_or_default(),
+                        );
+                        if relative_dir.is_empty() {
+                            sync_dir.local_path.clone()
+                        } else {
+                            format!("{}/{relative_dir}", sync_dir.local_path)
                         }
-
-                        globs
-                    } else {
-                        vec![]
                     };
+                    let local_names: Vec<String> = fs::read_dir(&local_dir)
+                        .map(|directory| {
+                            directory
+                                .filter_map(Result::ok)
+                                .map(|item| item.file_name().to_string_lossy().to_string())
+                                .collect()
+                        })
+                        .unwrap_or_default();
+                    let local_groups = name_rules.group(local_names.iter().map(String::as_str));
+
                     let update_ui_progress = |dir: &str| {
                         // If this directory no longer exists in the database (i.e. from being
                         // deleted from the `sync_dir_deletion_queue`, do nothing).
@@ -2161,13 +3291,15 @@ pub fn launch(app: &Application, background: bool) {
                         let item = ptr.get(&remote.name).unwrap().get(&dir_pair).unwrap();
                         let status_string = tr::tr!("Checking '{}' on remote for changes...", dir);
                         item.status_text.set_label(&status_string);
+                        item.set_progress(dir, synced_items.borrow().len());
                     };
                     update_ui_progress(remote_dir);
-                    let items = match rclone::sync::list(
-                        &remote.name,
+                    let items = match rclone::sync::list_filtered(
+                        &sync_dir.rclone_remote(&remote.name),
                         remote_dir,
                         false,
                         RcloneListFilter::All,
+                        &filters.rclone_filter(),
                     ) {
                         Ok(ok_items) => ok_items,
                         Err(err) => {
@@ -2192,24 +3324,73 @@ pub fn launch(app: &Application, background: bool) {
                             break;
                         }
 
-                        // If this item matches the ignore filter, don't sync it.
-                        if ignore_globs
-                            .iter()
-                            .filter(|pattern| pattern.matches(&item.path))
-                            .count()
-                            > 0
-                        {
+                        // If this item matches the exclusions, don't sync it.
+                        let relative_path = util::strip_slashes(
+                            item.path.strip_prefix(&sync_dir.remote_path).unwrap(),
+                        );
+                        if exclusions.is_excluded(&relative_path, item.is_dir) {
                             continue;
                         }
 
+                        // Likewise if it doesn't pass the directory's filters. Rclone will have
+                        // already left out files that are too big or too old.
+                        if filters.is_filtered(
+                            &item.name,
+                            item.is_dir,
+                            item.size.try_into().unwrap_or(0),
+                            item.mod_time.unix_timestamp(),
+                        ) {
+                            continue;
+                        }
+
+                        // Items in unselected folders never get downloaded, and anything in them
+                        // that exists locally has already been handled by the local pass.
+                        if selection.is_unselected(&relative_path) {
+                            continue;
+                        }
+
+                        // Files that were stored from symbolic links are only recreated as links
+                        // when the directory's policy is to store them, and are otherwise synced
+                        // as regular files.
+                        let is_link = !item.is_dir && item.name.ends_with(rclone::LINK_SUFFIX);
+                        if is_link && symlink_policy == SymlinkPolicy::Skip {
+                            continue;
+                        }
+                        let store_symlink = is_link && symlink_policy == SymlinkPolicy::Links;
+
                         let remote_path_string = item.path.clone();
-                        let local_path_string = format!(
-                            "{}/{}",
-                            sync_dir.local_path,
-                            item.path.strip_prefix(&sync_dir.remote_path).unwrap()
-                        );
+                        let local_path_string = {
+                            let relative_path =
+                                item.path.strip_prefix(&sync_dir.remote_path).unwrap();
+                            let relative_path = if store_symlink {
+                                relative_path.strip_suffix(rclone::LINK_SUFFIX).unwrap()
+                            } else {
+                                relative_path
+                            };
+                            format!("{}/{relative_path}", sync_dir.local_path)
+                        };
                         update_ui_progress(&remote_path_string);
 
+                        // Skip items whose names collide with a different local item under the
+                        // remote's rules. Collisions between local items have already been
+                        // reported by `fn sync_local_directory`.
+                        let local_name = Path::new(&local_path_string)
+                            .file_name()
+                            .unwrap()
+                            .to_string_lossy()
+                            .to_string();
+                        if let Some(group) = local_groups.get(&name_rules.key(&local_name)) {
+                            if group.len() > 1 {
+                                continue;
+                            } else if group[0] != local_name {
+                                add_error(SyncError::NameCollision(
+                                    format!("{local_dir}/{}", group[0]),
+                                    remote_path_string.clone(),
+                                ));
+                                continue;
+                            }
+                        }
+
                         // If we've already synced this directory from `fn sync_local_directory`
                         // above, don't sync it again.
                         if synced_items
@@ -2220,9 +3401,25 @@ pub fn launch(app: &Application, background: bool) {
                         }
 
                         let local_path = Path::new(&local_path_string);
+
+                        // Local links that are being skipped were left alone by
+                        // `fn sync_local_directory`, so make sure they don't get overwritten here.
+                        if symlink_policy == SymlinkPolicy::Skip && local_path.is_symlink() {
+                            continue;
+                        }
+
                         let remote_timestamp = item.mod_time.unix_timestamp();
+                        // The metadata of the local item, which is that of the link itself when
+                        // links are being stored.
+                        let local_metadata = || {
+                            if store_symlink {
+                                local_path.symlink_metadata()
+                            } else {
+                                local_path.metadata()
+                            }
+                        };
                         let get_local_file_timestamp = || {
-                            local_path.metadata().ok().map(|metadata| {
+                            local_metadata().ok().map(|metadata| {
                                 metadata
                                     .modified()
                                     .unwrap()
@@ -2248,9 +3445,10 @@ pub fn launch(app: &Application, background: bool) {
                         let push_local_to_remote = || {
                             if local_path.is_dir() {
                                 if !item.is_dir {
-                                    if let Err(err) =
-                                        rclone::sync::delete(&remote.name, &remote_path_string)
-                                    {
+                                    if let Err(err) = rclone::sync::delete(
+                                        &sync_dir.rclone_remote(&remote.name),
+                                        &remote_path_string,
+                                    ) {
                                         add_error(SyncError::General(
                                             remote_path_string.clone(),
                                             err.error,
@@ -2258,9 +3456,10 @@ pub fn launch(app: &Application, background: bool) {
                                         return Err(());
                                     }
 
-                                    if let Err(err) =
-                                        rclone::sync::mkdir(&remote.name, &remote_path_string)
-                                    {
+                                    if let Err(err) = rclone::sync::mkdir(
+                                        &sync_dir.rclone_remote(&remote.name),
+                                        &remote_path_string,
+                                    ) {
                                         add_error(SyncError::General(
                                             remote_path_string.clone(),
                                             err.error,
@@ -2276,6 +3475,8 @@ pub fn launch(app: &Application, background: bool) {
                                     db,
                                     directory_map,
                                     synced_items,
+                                    &exclusions,
+                                    name_rules,
                                     add_error.clone(),
                                     check_open_requests.clone(),
                                     process_deletion_requests.clone(),
@@ -2283,9 +3484,10 @@ pub fn launch(app: &Application, background: bool) {
                                 update_ui_progress(&remote_path_string);
                             } else {
                                 if item.is_dir {
-                                    if let Err(err) =
-                                        rclone::sync::purge(&remote.name, &remote_path_string)
-                                    {
+                                    if let Err(err) = rclone::sync::purge(
+                                        &sync_dir.rclone_remote(&remote.name),
+                                        &remote_path_string,
+                                    ) {
                                         add_error(SyncError::General(
                                             remote_path_string.clone(),
                                             err.error,
@@ -2294,22 +3496,43 @@ pub fn launch(app: &Application, background: bool) {
                                     }
                                 }
 
-                                if let Err(err) = rclone::sync::copy_to_remote(
-                                    &local_path_string,
-                                    &remote.name,
-                                    &remote_path_string,
-                                ) {
+                                if let Err(err) = if store_symlink {
+                                    rclone::sync::copy_link_to_remote(
+                                        &local_path_string,
+                                        &sync_dir.rclone_remote(&remote.name),
+                                        &remote_path_string,
+                                    )
+                                } else {
+                                    rclone::sync::copy_to_remote(
+                                        &local_path_string,
+                                        &sync_dir.rclone_remote(&remote.name),
+                                        &remote_path_string,
+                                    )
+                                } {
                                     add_error(SyncError::General(
                                         remote_path_string.clone(),
                                         err.error,
                                     ));
                                     return Err(());
                                 }
+
+                                activity::record(
+                                    db,
+                                    sync_dir,
+                                    ActivityKind::Push,
+                                    &local_path_string,
+                                    &remote_path_string,
+                                    local_metadata().ok().map(|metadata| metadata.len()),
+                                    "",
+                                );
                             }
 
-                            Ok(rclone::sync::stat(&remote.name, &remote_path_string)
-                                .unwrap()
-                                .unwrap())
+                            Ok(rclone::sync::stat(
+                                &sync_dir.rclone_remote(&remote.name),
+                                &remote_path_string,
+                            )
+                            .unwrap()
+                            .unwrap())
                         };
 
                         // Pull the item from the remote to the local machine.
@@ -2361,21 +3584,47 @@ pub fn launch(app: &Application, background: bool) {
                                     db,
                                     directory_map,
                                     synced_items,
+                                    &exclusions,
+                                    name_rules,
                                     add_error.clone(),
                                     check_open_requests.clone(),
                                     process_deletion_requests.clone(),
                                 );
                                 update_ui_progress(&remote_path_string);
-                            } else if let Err(err) = rclone::sync::copy_to_local(
-                                &local_path_string,
-                                &remote.name,
-                                &remote_path_string,
-                            ) {
+                            } else if let Err(err) =
+                                versions::save(db, sync_dir, &local_path_string)
+                            {
+                                add_error(SyncError::General(remote_path_string.clone(), err));
+                                return Err(());
+                            } else if let Err(err) = if store_symlink {
+                                rclone::sync::copy_link_to_local(
+                                    &local_path_string,
+                                    &sync_dir.rclone_remote(&remote.name),
+                                    &remote_path_string,
+                                )
+                            } else {
+                                rclone::sync::copy_to_local(
+                                    &local_path_string,
+                                    &sync_dir.rclone_remote(&remote.name),
+                                    &remote_path_string,
+                                    item.mode(),
+                                )
+                            } {
                                 add_error(SyncError::General(
                                     remote_path_string.clone(),
                                     err.error,
                                 ));
                                 return Err(());
+                            } else {
+                                activity::record(
+                                    db,
+                                    sync_dir,
+                                    ActivityKind::Pull,
+                                    &local_path_string,
+                                    &remote_path_string,
+                                    item.size.try_into().ok(),
+                                    "",
+                                );
                             }
 
                             Ok(())
@@ -2403,13 +3652,19 @@ pub fn launch(app: &Application, background: bool) {
 
                         // If we have a database record, use that in checks.
                         if let Some(db_model) = db_item {
-                            let update_db_item = |local_timestamp, remote_timestamp| {
+                            let update_db_item = |local_timestamp, remote_timestamp, remote_id| {
                                 let mut active_model: SyncItemsActiveModel =
                                     db_model.clone().into();
                                 active_model.last_local_timestamp =
                                     ActiveValue::Set(local_timestamp);
                                 active_model.last_remote_timestamp =
                                     ActiveValue::Set(remote_timestamp);
+                                active_model.local_inode = ActiveValue::Set(
+                                    local_metadata()
+                                        .ok()
+                                        .map(|metadata| moves::inode(&metadata)),
+                                );
+                                active_model.remote_id = ActiveValue::Set(remote_id);
                                 util::await_future(active_model.update(db)).unwrap();
                             };
 
@@ -2437,6 +3692,7 @@ pub fn launch(app: &Application, background: bool) {
                                     update_db_item(
                                         get_local_file_timestamp().unwrap().try_into().unwrap(),
                                         rclone_item.mod_time.unix_timestamp().try_into().unwrap(),
+                                        rclone_item.id,
                                     );
                                     continue;
                                 } else {
@@ -2451,6 +3707,7 @@ pub fn launch(app: &Application, background: bool) {
                                     update_db_item(
                                         get_local_file_timestamp().unwrap().try_into().unwrap(),
                                         remote_timestamp.try_into().unwrap(),
+                                        item.id.clone(),
                                     );
                                 }
 
@@ -2462,9 +3719,10 @@ pub fn launch(app: &Application, background: bool) {
                             } else if !local_path.exists()
                                 && remote_timestamp == db_model.last_remote_timestamp as i64
                             {
-                                if let Err(err) =
-                                    rclone::sync::purge(&remote.name, &remote_path_string)
-                                {
+                                if let Err(err) = rclone::sync::purge(
+                                    &sync_dir.rclone_remote(&remote.name),
+                                    &remote_path_string,
+                                ) {
                                     add_error(SyncError::General(
                                         remote_path_string.clone(),
                                         err.error,
@@ -2472,6 +3730,15 @@ pub fn launch(app: &Application, background: bool) {
                                     delete_db_entry();
                                     continue;
                                 } else {
+                                    activity::record(
+                                        db,
+                                        sync_dir,
+                                        ActivityKind::DeleteRemote,
+                                        &local_path_string,
+                                        &remote_path_string,
+                                        None,
+                                        "",
+                                    );
                                     continue;
                                 }
 
@@ -2513,17 +3780,19 @@ pub fn launch(app: &Application, background: bool) {
                         // The local item is now guaranteed to exist. Also fetch the remote's
                         // timestamp in case it got updated above.
                         let l_timestamp = get_local_file_timestamp().unwrap();
-                        let r_timestamp =
-                            match rclone::sync::stat(&remote.name, &remote_path_string) {
-                                Ok(item) => item.unwrap().mod_time.unix_timestamp(),
-                                Err(err) => {
-                                    add_error(SyncError::General(
-                                        remote_path_string.clone(),
-                                        err.error,
-                                    ));
-                                    continue;
-                                }
-                            };
+                        let r_item = match rclone::sync::stat(
+                            &sync_dir.rclone_remote(&remote.name),
+                            &remote_path_string,
+                        ) {
+                            Ok(item) => item.unwrap(),
+                            Err(err) => {
+                                add_error(SyncError::General(
+                                    remote_path_string.clone(),
+                                    err.error,
+                                ));
+                                continue;
+                            }
+                        };
 
                         // Record the current transaction's timestamps in the database.
                         util::await_future(
@@ -2535,8 +3804,14 @@ pub fn launch(app: &Application, background: bool) {
                                     l_timestamp.try_into().unwrap(),
                                 ),
                                 last_remote_timestamp: ActiveValue::Set(
-                                    r_timestamp.try_into().unwrap(),
+                                    r_item.mod_time.unix_timestamp().try_into().unwrap(),
                                 ),
+                                local_inode: ActiveValue::Set(
+                                    local_metadata()
+                                        .ok()
+                                        .map(|metadata| moves::inode(&metadata)),
+                                ),
+                                remote_id: ActiveValue::Set(r_item.id),
                                 ..Default::default()
                             }
                             .insert(db),
@@ -2545,6 +3820,27 @@ pub fn launch(app: &Application, background: bool) {
                     }
                 }
 
+                // Items that were moved on the remote get moved locally first, so the
+                // checks below don't see them as having been deleted from their old paths.
+                if let Ok(listing) = &remote_paths {
+                    for (path, err) in moves::apply_remote_moves(&db, &sync_dir, listing) {
+                        add_error(SyncError::General(path, err));
+                    }
+                }
+
+                // The global exclusion file is read once for the whole pass over this
+                // directory, and each directory's own exclusion files get added on top of
+                // their parent directory's rules while walking down.
+                let global_exclusions = exclude::Exclusions::global();
+                for invalid in global_exclusions.invalid() {
+                    add_error(SyncError::General(invalid.file.clone(), invalid.message()));
+                }
+
+                // The remote's rules for names only need to be looked up once for the whole
+                // pass over this directory.
+                let name_rules = NameRules::for_sync_dir(&remote.name, &sync_dir);
+                let name_limits = NameLimits::for_sync_dir(&remote.name, &sync_dir);
+
                 sync_local_directory(
                     Path::new(&sync_dir.local_path),
                     &remote,
@@ -2552,6 +3848,9 @@ pub fn launch(app: &Application, background: bool) {
                     &db,
                     &directory_map,
                     &synced_items,
+                    &global_exclusions,
+                    name_rules,
+                    &name_limits,
                     &add_error,
                     &check_open_requests,
                     &process_deletion_requests,
@@ -2563,6 +3862,8 @@ pub fn launch(app: &Application, background: bool) {
                     &db,
                     &directory_map,
                     &synced_items,
+                    &global_exclusions,
+                    name_rules,
                     &add_error,
                     &check_open_requests,
                     &process_deletion_requests,
@@ -2587,26 +3888,48 @@ pub fn launch(app: &Application, background: bool) {
                     .unwrap()
                     .get(&(sync_dir.local_path.clone(), sync_dir.remote_path.clone()))
                     .unwrap();
-                item.status_icon
-                    .set_child(Some(&get_image("object-select-symbolic")));
                 let mut finished_text = tr::tr!("Files are synced.");
                 if item.error_status_text.text().len() != 0 {
                     finished_text += &please_resolve_msg;
-                    item.status_icon
-                        .set_child(Some(&get_image("dialog-warning-symbolic")));
-                } else {
-                    item.status_icon
-                        .set_child(Some(&get_image("object-select-symbolic")));
                 }
+                item.set_state(DirState::Synced);
                 item.status_text.set_label(&finished_text);
                 drop(item_ptr);
             }
         }
 
+        // Drop any activity that's past the retention limits, now that everything from
+        // this pass has been recorded.
+        activity::prune(&db);
+
         // Notify that we've finished checking all remotes for changes.
+        write_status(&remote_map, &directory_map);
         let error_count = sync_errors_count();
-
-        if error_count != 0 {
+        let remotes_with_state = |state: RemoteState| -> Vec<String> {
+            remote_map
+                .get_ref()
+                .iter()
+                .filter(|(_, remote_item)| remote_item.state.as_ref() == Some(&state))
+                .map(|(remote_name, _)| remote_name.clone())
+                .collect()
+        };
+        let signed_out_remotes = remotes_with_state(RemoteState::NeedsSignIn);
+        let disconnected_remotes = remotes_with_state(RemoteState::Disconnected);
+
+        if !signed_out_remotes.is_empty() {
+            let sign_in_msg = tr::tr!("Sign-in required for {}.", signed_out_remotes.join(", "));
+            handle.update(|tray| {
+                tray.set_msg(sign_in_msg);
+                tray.set_disconnected();
+            });
+        } else if !disconnected_remotes.is_empty() {
+            let disconnected_msg =
+                tr::tr!("Unable to connect to {}.", disconnected_remotes.join(", "));
+            handle.update(|tray| {
+                tray.set_msg(disconnected_msg);
+                tray.set_disconnected();
+            });
+        } else if error_count != 0 {
             let error_msg = if error_count == 1 {
                 "Finished sync checks with 1 error.".to_string()
             } else {
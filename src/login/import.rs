@@ -0,0 +1,282 @@
+//! Importing of remotes from an existing Rclone config file.
+use crate::{
+    entities::{RemotesActiveModel, RemotesModel},
+    gtk_util, mpsc, rclone,
+    traits::prelude::*,
+    util,
+};
+use adw::{
+    glib,
+    gtk::{
+        Align, Box, Button, CheckButton, Entry, FileChooserAction, FileChooserDialog, Inhibit,
+        Label, ListBox, Orientation, ResponseType, ScrolledWindow, SelectionMode,
+    },
+    prelude::*,
+    ActionRow, Application, ApplicationWindow, HeaderBar,
+};
+use indexmap::IndexMap;
+use sea_orm::{entity::prelude::*, ActiveValue, DatabaseConnection};
+use serde_json::json;
+use std::{cell::RefCell, collections::HashMap, fs, rc::Rc};
+
+/// The config parameters for each remote, keyed by the remote's name.
+type RemoteConfigs = IndexMap<String, IndexMap<String, String>>;
+
+/// Get the path to the config file Rclone uses by default.
+fn default_config_path() -> String {
+    let mut config_path = glib::user_config_dir();
+    config_path.push("rclone");
+    config_path.push("rclone.conf");
+    config_path.into_os_string().into_string().unwrap()
+}
+
+/// Import remotes from an existing Rclone config file. Returns the remotes that
+/// were successfully imported, which will be empty if the window was closed
+/// before anything was imported.
+pub fn import_remotes(app: &Application, db: &DatabaseConnection) -> Vec<RemotesModel> {
+    // The mpsc sender/receiver to get the selected remotes from the window.
+    let (sender, mut receiver) = mpsc::channel::<Option<RemoteConfigs>>();
+
+    let window = ApplicationWindow::builder()
+        .application(app)
+        .title(&util::get_title!("Import Remotes"))
+        .width_request(400)
+        .build();
+    window.add_css_class("celeste-global-padding");
+    window.connect_close_request(glib::clone!(@strong sender => move |_| {
+        sender.send(None);
+        Inhibit(false)
+    }));
+
+    let sections = Box::builder().orientation(Orientation::Vertical).build();
+    sections.append(&HeaderBar::new());
+
+    // The config file to import from.
+    let config_label = Label::builder()
+        .label(&tr::tr!("Config file:"))
+        .halign(Align::Start)
+        .css_classes(vec!["heading".to_string()])
+        .build();
+    let config_entry = Entry::builder()
+        .secondary_icon_activatable(true)
+        .secondary_icon_name("document-open-symbolic")
+        .secondary_icon_sensitive(true)
+        .build();
+    config_entry.connect_icon_press(glib::clone!(@weak window => move |config_entry, _| {
+        window.set_sensitive(false);
+        let dialog = FileChooserDialog::builder()
+            .title(&util::get_title!("Config File Picker"))
+            .action(FileChooserAction::Open)
+            .select_multiple(false)
+            .build();
+        let cancel_button = Button::with_label(&tr::tr!("Cancel"));
+        let ok_button = Button::with_label(&tr::tr!("Ok"));
+        dialog.add_action_widget(&cancel_button, ResponseType::Cancel);
+        dialog.add_action_widget(&ok_button, ResponseType::Ok);
+        dialog.connect_close_request(glib::clone!(@weak window => @default-return Inhibit(false), move |_| {
+            window.set_sensitive(true);
+            Inhibit(false)
+        }));
+        cancel_button.connect_clicked(glib::clone!(@weak dialog => move |_| {
+            dialog.close();
+        }));
+        ok_button.connect_clicked(glib::clone!(@weak config_entry, @weak dialog => move |_| {
+            if let Some(path) = dialog.file().and_then(|file| file.path()) {
+                config_entry.set_text(&path.into_os_string().into_string().unwrap());
+            }
+            dialog.close();
+        }));
+        dialog.show();
+    }));
+
+    // The remotes found in the config file.
+    let remotes_label = Label::builder()
+        .label(&tr::tr!("Remotes:"))
+        .halign(Align::Start)
+        .margin_top(10)
+        .css_classes(vec!["heading".to_string()])
+        .build();
+    let remotes_list = ListBox::builder()
+        .selection_mode(SelectionMode::None)
+        .css_classes(vec!["boxed-list".to_string()])
+        .valign(Align::Start)
+        .build();
+    let remotes_list_scrolled = ScrolledWindow::builder()
+        .child(&remotes_list)
+        .min_content_height(200)
+        .vexpand_set(true)
+        .vexpand(true)
+        .build();
+
+    let confirm_box = Box::builder()
+        .orientation(Orientation::Horizontal)
+        .spacing(10)
+        .halign(Align::End)
+        .margin_top(10)
+        .build();
+    let cancel_button = Button::with_label(&tr::tr!("Cancel"));
+    let import_button = Button::with_label(&tr::tr!("Import"));
+    import_button.set_sensitive(false);
+    confirm_box.append(&cancel_button);
+    confirm_box.append(&import_button);
+
+    // The configs from the currently selected file, and the check buttons for the
+    // ones that can be imported.
+    let configs: Rc<RefCell<RemoteConfigs>> = Rc::new(RefCell::new(IndexMap::new()));
+    let checks: Rc<RefCell<Vec<(String, CheckButton)>>> = Rc::new(RefCell::new(vec![]));
+
+    let update_import_button = glib::clone!(@weak import_button, @strong checks => move || {
+        let any_selected = checks.get_ref().iter().any(|(_, check)| check.is_active());
+        import_button.set_sensitive(any_selected);
+    });
+
+    // Reload the list of remotes from the file in the config entry.
+    let load_remotes = glib::clone!(@weak config_entry, @weak remotes_list, @strong configs, @strong checks, @strong update_import_button => move || {
+        while let Some(row) = remotes_list.first_child() {
+            remotes_list.remove(&row);
+        }
+        checks.get_mut_ref().clear();
+        configs.get_mut_ref().clear();
+        config_entry.remove_css_class("error");
+        config_entry.set_tooltip_text(None);
+
+        let content = match fs::read_to_string(config_entry.text().as_str()) {
+            Ok(content) => content,
+            Err(err) => {
+                config_entry.add_css_class("error");
+                config_entry.set_tooltip_text(Some(&err.to_string()));
+                update_import_button();
+                return;
+            }
+        };
+        let file_configs = match rclone::parse_config(&content) {
+            Ok(file_configs) => file_configs,
+            Err(err) => {
+                config_entry.add_css_class("error");
+                config_entry.set_tooltip_text(Some(&err));
+                update_import_button();
+                return;
+            }
+        };
+
+        // Get a list of already existing config names.
+        let existing_remotes = rclone::get_remote_names();
+
+        for (name, config) in &file_configs {
+            let check = CheckButton::new();
+            let row = ActionRow::builder().title(name).build();
+            row.add_prefix(&check);
+            row.set_activatable_widget(Some(&check));
+
+            if existing_remotes.contains(name) {
+                row.set_subtitle(&tr::tr!("A remote with this name already exists."));
+                row.set_sensitive(false);
+            } else if let Some(problem) = rclone::config_problem(config) {
+                row.set_subtitle(&problem);
+                row.set_sensitive(false);
+            } else {
+                row.set_subtitle(&rclone::config_type_name(config).unwrap());
+                check.connect_toggled(glib::clone!(@strong update_import_button => move |_| update_import_button()));
+                checks.get_mut_ref().push((name.clone(), check));
+            }
+
+            remotes_list.append(&row);
+        }
+
+        *configs.get_mut_ref() = file_configs;
+        update_import_button();
+    });
+    config_entry.connect_changed(glib::clone!(@strong load_remotes => move |_| load_remotes()));
+    config_entry.set_text(&default_config_path());
+
+    cancel_button.connect_clicked(glib::clone!(@weak window => move |_| {
+        window.close();
+    }));
+    import_button.connect_clicked(
+        glib::clone!(@strong sender, @strong configs, @strong checks => move |_| {
+            let configs_ptr = configs.get_ref();
+            let selected = checks
+                .get_ref()
+                .iter()
+                .filter(|(_, check)| check.is_active())
+                .map(|(name, _)| (name.clone(), configs_ptr[name].clone()))
+                .collect();
+            sender.send(Some(selected));
+        }),
+    );
+
+    sections.append(&config_label);
+    sections.append(&config_entry);
+    sections.append(&remotes_label);
+    sections.append(&remotes_list_scrolled);
+    sections.append(&confirm_box);
+    window.set_content(Some(&sections));
+    window.show();
+
+    let mut imported = vec![];
+
+    // Keep receiving selections until everything selected has been imported, or
+    // the user closes the window.
+    loop {
+        let selected = match receiver.recv() {
+            Some(selected) => selected,
+            None => return imported,
+        };
+        window.set_sensitive(false);
+        let mut all_imported = true;
+
+        for (name, mut config) in selected {
+            let remote_type = config.shift_remove("type").unwrap();
+
+            // Values in the config file are already obscured, so make sure Rclone doesn't
+            // obscure them again.
+            let parameters: HashMap<String, String> = config.into_iter().collect();
+            let config_query = json!({
+                "name": name,
+                "parameters": parameters,
+                "type": remote_type,
+                "opt": {
+                    "nonInteractive": true,
+                    "noObscure": true
+                }
+            });
+            let create_result = util::run_in_background(move || {
+                librclone::rpc("config/create", config_query.to_string())
+            });
+
+            if let Err(err) = create_result {
+                gtk_util::show_codeblock_error(&tr::tr!("Unable to import '{}'", name), &err);
+                all_imported = false;
+                continue;
+            }
+
+            if !super::can_login(app, &name) {
+                util::run_in_background(glib::clone!(@strong name => move || {
+                    librclone::rpc("config/delete", json!({ "name": name }).to_string()).unwrap()
+                }));
+                all_imported = false;
+                continue;
+            }
+
+            let model = util::await_future(
+                RemotesActiveModel {
+                    name: ActiveValue::Set(name),
+                    ..Default::default()
+                }
+                .insert(db),
+            )
+            .unwrap();
+            imported.push(model);
+        }
+
+        if all_imported {
+            window.close();
+            return imported;
+        }
+
+        // Reload the list so that anything we just imported shows up as already
+        // existing.
+        load_remotes();
+        window.set_sensitive(true);
+    }
+}
@@ -3,12 +3,13 @@ use crate::{
     entities::{RemotesActiveModel, RemotesModel},
     gtk_util,
     mpsc::{self, Sender},
-    rclone,
+    rclone::{self, RcloneErrorKind},
     traits::prelude::*,
     util,
 };
 mod dropbox;
 mod gdrive;
+pub mod import;
 pub mod login_util;
 mod nextcloud;
 mod owncloud;
@@ -69,19 +70,129 @@ impl ToString for ServerType {
     }
 }
 
+impl ServerType {
+    /// Get the name of the server, as entered by the user.
+    fn server_name(&self) -> String {
+        match self {
+            Self::Dropbox(config) => config.server_name.clone(),
+            Self::GDrive(config) => config.server_name.clone(),
+            Self::Nextcloud(config) => config.server_name.clone(),
+            Self::Owncloud(config) => config.server_name.clone(),
+            Self::PCloud(config) => config.server_name.clone(),
+            Self::ProtonDrive(config) => config.server_name.clone(),
+            Self::WebDav(config) => config.server_name.clone(),
+        }
+    }
+
+    /// Get the query to pass to Rclone's `config/create` and `config/update`
+    /// commands for this server.
+    fn config_query(&self) -> serde_json::Value {
+        match self {
+            Self::Dropbox(config) => json!({
+                "name": self.server_name(),
+                "parameters": {
+                    "client_id": config.client_id,
+                    "client_secret": config.client_secret,
+                    "token": config.auth_json,
+                    "config_refresh_token": false
+                },
+                "type": "dropbox"
+            }),
+            Self::GDrive(config) => json!({
+                "name": self.server_name(),
+                "parameters": {
+                    "client_id": config.client_id,
+                    "client_secret": config.client_secret,
+                    "token": config.auth_json,
+                    "config_refresh_token": false
+                },
+                "type": "drive"
+            }),
+            Self::Nextcloud(config) => json!({
+                "name": self.server_name(),
+                "parameters": {
+                    "url": config.server_url,
+                    "vendor": "nextcloud",
+                    "user": config.username,
+                    "pass": config.password
+                },
+                "type": "webdav",
+                "opt": {
+                    "obscure": true
+                }
+            }),
+            Self::Owncloud(config) => json!({
+                "name": self.server_name(),
+                "parameters": {
+                    "url": config.server_url,
+                    "vendor": "owncloud",
+                    "user": config.username,
+                    "pass": config.password
+                },
+                "type": "webdav",
+                "opt": {
+                    "obscure": true
+                }
+            }),
+            Self::PCloud(config) => json!({
+                "name": self.server_name(),
+                "parameters": {
+                    "client_id": config.client_id,
+                    "client_secret": config.client_secret,
+                    "token": config.auth_json,
+                    "config_refresh_token": false
+                },
+                "type": "pcloud",
+                "opt": {
+                    "obscure": true
+                }
+            }),
+            Self::ProtonDrive(config) => json!({
+                "name": self.server_name(),
+                "parameters": {
+                    "username": config.username,
+                    "password": config.password,
+                    "2fa": config.totp
+                },
+                "type": "protondrive",
+                "opt": {
+                    "obscure": true
+                }
+            }),
+            Self::WebDav(config) => json!({
+                "name": self.server_name(),
+                "parameters": {
+                    "url": config.server_url,
+                    "vendor": "webdav",
+                    "user": config.username,
+                    "pass": config.password
+                },
+                "type": "webdav",
+                "opt": {
+                    "obscure": true
+                }
+            }),
+        }
+    }
+}
+
 // Verify if a specific config can log in to a server.
 pub fn can_login(_app: &Application, config_name: &str) -> bool {
     if let Err(err) = rclone::sync::stat(config_name, "/") {
-        let err_msg = if err.error.contains("Temporary failure in name resolution") {
-            tr::tr!(
-                "Unable to connect to the server. Check your internet connection and try again."
-            )
-        } else if err.error.contains("this account requires a 2FA code") {
+        let err_msg = if err.needs_2fa_code() {
             tr::tr!("A 2FA code is required to log in to this account. Provide one and try again.")
         } else {
-            tr::tr!(
-                "Unable to authenticate to the server. Check your login credentials and try again."
-            )
+            match err.kind {
+                RcloneErrorKind::Network => tr::tr!(
+                    "Unable to connect to the server. Check your internet connection and try again."
+                ),
+                RcloneErrorKind::RateLimited => tr::tr!(
+                    "The server is receiving too many requests. Wait a bit and try again."
+                ),
+                _ => tr::tr!(
+                    "Unable to authenticate to the server. Check your login credentials and try again."
+                ),
+            }
         };
 
         gtk_util::show_error(&tr::tr!("Unable to log in"), Some(&err_msg));
@@ -218,102 +329,8 @@ pub fn login(app: &Application, db: &DatabaseConnection) -> Option<RemotesModel>
         window.set_sensitive(false);
 
         // Create a new config with the requested name.
-        let config_name = match &server {
-            ServerType::Dropbox(config) => config.server_name.clone(),
-            ServerType::GDrive(config) => config.server_name.clone(),
-            ServerType::Nextcloud(config) => config.server_name.clone(),
-            ServerType::Owncloud(config) => config.server_name.clone(),
-            ServerType::PCloud(config) => config.server_name.clone(),
-            ServerType::ProtonDrive(config) => config.server_name.clone(),
-            ServerType::WebDav(config) => config.server_name.clone(),
-        };
-
-        let config_query = match &server {
-            ServerType::Dropbox(config) => json!({
-                "name": config_name,
-                "parameters": {
-                    "client_id": config.client_id,
-                    "client_secret": config.client_secret,
-                    "token": config.auth_json,
-                    "config_refresh_token": false
-                },
-                "type": "dropbox"
-            }),
-            ServerType::GDrive(config) => json!({
-                "name": config_name,
-                "parameters": {
-                    "client_id": config.client_id,
-                    "client_secret": config.client_secret,
-                    "token": config.auth_json,
-                    "config_refresh_token": false
-                },
-                "type": "drive"
-            }),
-            ServerType::Nextcloud(config) => json!({
-                "name": config_name,
-                "parameters": {
-                    "url": config.server_url,
-                    "vendor": "nextcloud",
-                    "user": config.username,
-                    "pass": config.password
-                },
-                "type": "webdav",
-                "opt": {
-                    "obscure": true
-                }
-            }),
-            ServerType::Owncloud(config) => json!({
-                "name": config_name,
-                "parameters": {
-                    "url": config.server_url,
-                    "vendor": "owncloud",
-                    "user": config.username,
-                    "pass": config.password
-                },
-                "type": "webdav",
-                "opt": {
-                    "obscure": true
-                }
-            }),
-            ServerType::PCloud(config) => json!({
-                "name": config_name,
-                "parameters": {
-                    "client_id": config.client_id,
-                    "client_secret": config.client_secret,
-                    "token": config.auth_json,
-                    "config_refresh_token": false
-                },
-                "type": "pcloud",
-                "opt": {
-                    "obscure": true
-                }
-            }),
-            ServerType::ProtonDrive(config) => json!({
-                "name": config_name,
-                "parameters": {
-                    "username": config.username,
-                    "password": config.password,
-                    "2fa": config.totp
-                },
-                "type": "protondrive",
-                "opt": {
-                    "obscure": true
-                }
-            }),
-            ServerType::WebDav(config) => json!({
-                "name": config_name,
-                "parameters": {
-                    "url": config.server_url,
-                    "vendor": "webdav",
-                    "user": config.username,
-                    "pass": config.password
-                },
-                "type": "webdav",
-                "opt": {
-                    "obscure": true
-                }
-            }),
-        };
+        let config_name = server.server_name();
+        let config_query = server.config_query();
 
         util::run_in_background(move || {
             librclone::rpc("config/create", config_query.to_string()).unwrap()
@@ -343,3 +360,142 @@ pub fn login(app: &Application, db: &DatabaseConnection) -> Option<RemotesModel>
         }
     }
 }
+
+/// Update the login details for an existing remote, such as after a password
+/// change or a revoked token. The remote's sync directories are kept as-is, as
+/// only the Rclone config gets modified. Returns `true` if the remote was
+/// updated, and `false` if the window was closed before that happened.
+pub fn edit(app: &Application, remote_name: &str) -> bool {
+    let remote = match rclone::get_remote(remote_name) {
+        Some(remote) => remote,
+        None => return false,
+    };
+
+    // Keep a copy of the current config so we can put it back if the new details
+    // don't work.
+    let old_config: serde_json::Map<String, serde_json::Value> = {
+        let remote_name = remote_name.to_owned();
+        let config_str = util::run_in_background(move || {
+            librclone::rpc("config/get", json!({ "name": remote_name }).to_string()).unwrap()
+        });
+        let mut config: serde_json::Map<String, serde_json::Value> =
+            serde_json::from_str(&config_str).unwrap();
+        config.remove("type");
+        config
+    };
+
+    // The mspc sender/receiver to get data from fields.
+    let (sender, mut receiver) = mpsc::channel::<Option<ServerType>>();
+
+    // The window.
+    let window = ApplicationWindow::builder()
+        .application(app)
+        .title(&util::get_title!("Edit Login"))
+        .width_request(400)
+        .build();
+    window.add_css_class("celeste-global-padding");
+    window.connect_close_request(glib::clone!(@strong sender => move |_| {
+        sender.send(None);
+        Inhibit(false)
+    }));
+
+    // Get the form for the remote's server type.
+    let (rows, submit_button) = match &remote {
+        rclone::Remote::Dropbox(_) => DropboxConfig::get_sections(&window, sender),
+        rclone::Remote::GDrive(_) => GDriveConfig::get_sections(&window, sender),
+        rclone::Remote::PCloud(_) => PCloudConfig::get_sections(&window, sender),
+        rclone::Remote::ProtonDrive(_) => ProtonDriveConfig::get_sections(&window, sender),
+        rclone::Remote::WebDav(config) => match config.vendor {
+            rclone::WebDavVendors::Nextcloud => NextcloudConfig::get_sections(&window, sender),
+            rclone::WebDavVendors::Owncloud => OwncloudConfig::get_sections(&window, sender),
+            _ => WebDavConfig::get_sections(&window, sender),
+        },
+    };
+
+    // The name can't be changed, as the database refers to the remote by it. The
+    // row has to be made uneditable before the text is set so that the submit
+    // button doesn't count the "Name already exists" error against it.
+    let name_row = &rows[0];
+    name_row.set_editable(false);
+    name_row.set_text(remote_name);
+    name_row.remove_css_class("error");
+    name_row.set_tooltip_text(None);
+
+    // Fill in the rest of the fields we know about. Passwords are stored obscured
+    // in the config, so those need to be entered again.
+    match &remote {
+        rclone::Remote::ProtonDrive(config) => rows[1].set_text(&config.username),
+        rclone::Remote::WebDav(config) => {
+            let server_url = match config.vendor {
+                rclone::WebDavVendors::Nextcloud | rclone::WebDavVendors::Owncloud => {
+                    config.url.split("/remote.php/").next().unwrap().to_owned()
+                }
+                _ => config.url.clone(),
+            };
+            rows[1].set_text(&server_url);
+            rows[2].set_text(&config.user);
+        }
+        _ => (),
+    }
+
+    let input_sections = ListBox::builder()
+        .selection_mode(SelectionMode::None)
+        .css_classes(vec!["boxed-list".to_string()])
+        .build();
+    for row in &rows {
+        input_sections.append(row);
+    }
+
+    let container = Box::builder().orientation(Orientation::Vertical).build();
+    container.append(&HeaderBar::new());
+    container.append(&input_sections);
+    container.append(&submit_button);
+    window.set_content(Some(&container));
+    window.show();
+
+    // Remotes using OAuth don't have anything else to fill in, so start the
+    // authorization flow straight away.
+    if let rclone::Remote::Dropbox(_) | rclone::Remote::GDrive(_) | rclone::Remote::PCloud(_) =
+        remote
+    {
+        submit_button.emit_clicked();
+    }
+
+    // Keep receiving values from the window until the new details work.
+    loop {
+        // If the user clicks the 'X' button on the window we get a [`None`] value.
+        let server = match receiver.recv() {
+            Some(server) => server,
+            None => return false,
+        };
+        window.set_sensitive(false);
+
+        // The type of a remote can't be changed, so it isn't needed when updating.
+        let mut config_query = server.config_query();
+        config_query.as_object_mut().unwrap().remove("type");
+        util::run_in_background(move || {
+            librclone::rpc("config/update", config_query.to_string()).unwrap()
+        });
+        rclone::clear_secret_params();
+
+        if can_login(app, remote_name) {
+            window.close();
+            return true;
+        }
+
+        // The new details didn't work, so put the old config back in place. Its values
+        // are already obscured, so make sure Rclone doesn't obscure them again.
+        let restore_query = json!({
+            "name": remote_name,
+            "parameters": old_config,
+            "opt": {
+                "noObscure": true
+            }
+        });
+        util::run_in_background(move || {
+            librclone::rpc("config/update", restore_query.to_string()).unwrap()
+        });
+        rclone::clear_secret_params();
+        window.set_sensitive(true);
+    }
+}
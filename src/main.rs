@@ -6,16 +6,30 @@
 #![feature(exit_status_error)]
 
 pub mod about;
+pub mod activity;
+pub mod browser;
+pub mod cli;
+pub mod collisions;
+pub mod dbus;
 pub mod entities;
+pub mod exclude;
+pub mod filters;
 pub mod gtk_util;
+pub mod keyring;
 pub mod launch;
 pub mod login;
 pub mod migrations;
+pub mod moves;
 pub mod mpsc;
+pub mod names;
 pub mod rclone;
+pub mod selective;
+pub mod status;
+pub mod symlinks;
 pub mod traits;
 pub mod tray;
 pub mod util;
+pub mod versions;
 
 use adw::{
     gtk::{self, gdk::Display, Align, Box, CssProvider, Label, Orientation, StyleContext},
@@ -27,7 +41,7 @@ use serde_json::json;
 use std::{
     env,
     io::{BufRead, BufReader},
-    process::{Command, Stdio},
+    process::{self, Command, Stdio},
     thread,
 };
 
@@ -49,11 +63,12 @@ enum Commands {
         #[arg(long)]
         background: bool,
     },
+    #[command(flatten)]
+    Cli(cli::Commands),
 }
 
 fn main() {
-    // Initialize GTK.
-    gtk::init().unwrap();
+    let cli = Cli::parse();
 
     // Configure Rclone.
     let mut config = util::get_config_dir();
@@ -61,6 +76,19 @@ fn main() {
     librclone::initialize();
     librclone::rpc("config/setpath", json!({ "path": config }).to_string()).unwrap();
 
+    // CLI commands don't need GTK, so run them before we try to initialize it.
+    if let Some(Commands::Cli(cmd)) = cli.command {
+        if let Err(err) = cli::run(cmd) {
+            eprintln!("{err}");
+            process::exit(1);
+        }
+
+        process::exit(0);
+    }
+
+    // Initialize GTK.
+    gtk::init().unwrap();
+
     // Load our CSS.
     let provider = CssProvider::new();
     provider.load_from_data(include_bytes!(concat!(env!("OUT_DIR"), "/style.css")));
@@ -79,7 +107,6 @@ fn main() {
     // observed). Panics would like to be captured when they're encountered though,
     // so we relaunch this program in a subprocess and capture any errors from
     // there.
-    let cli = Cli::parse();
     if let Some(cmd) = cli.command {
         match cmd {
             Commands::RunGui { background } => {
@@ -100,6 +127,7 @@ fn main() {
 
                 app.run_with_args::<&str>(&[]);
             }
+            Commands::Cli(_) => unreachable!(),
         }
     } else {
         // Set `RUST_BACKTRACE` so we get a better backtrace for reporting.
@@ -0,0 +1,30 @@
+use sea_orm::{ConnectionTrait, Statement};
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let sql = r#"
+            ALTER TABLE sync_dirs ADD COLUMN max_size INTEGER;
+            ALTER TABLE sync_dirs ADD COLUMN max_age INTEGER;
+            ALTER TABLE sync_dirs ADD COLUMN file_types TEXT NOT NULL DEFAULT '';
+            ALTER TABLE sync_dirs ADD COLUMN skip_hidden BOOLEAN NOT NULL DEFAULT 0;
+        "#;
+        let stmt = Statement::from_string(manager.get_database_backend(), sql.to_owned());
+        manager.get_connection().execute(stmt).await.map(|_| ())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let sql = r#"
+            ALTER TABLE sync_dirs DROP COLUMN max_size;
+            ALTER TABLE sync_dirs DROP COLUMN max_age;
+            ALTER TABLE sync_dirs DROP COLUMN file_types;
+            ALTER TABLE sync_dirs DROP COLUMN skip_hidden;
+        "#;
+        let stmt = Statement::from_string(manager.get_database_backend(), sql.to_owned());
+        manager.get_connection().execute(stmt).await.map(|_| ())
+    }
+}
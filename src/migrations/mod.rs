@@ -3,6 +3,16 @@ pub use sea_orm_migration::prelude::*;
 mod m20220101_000001_create_table;
 mod m20230207_204909_sync_dirs_remove_slash_suffix;
 mod m20230220_215840_remote_sync_items_fix;
+mod m20230312_183502_sync_dirs_crypt_remote;
+mod m20230318_141127_sync_dirs_paused;
+mod m20230325_102418_create_sync_errors;
+mod m20230401_163052_create_activity;
+mod m20230408_112944_create_file_versions;
+mod m20230415_094126_sync_dirs_honor_gitignore;
+mod m20230422_153318_sync_dirs_filters;
+mod m20230429_101547_create_unselected_folders;
+mod m20230506_141205_sync_dirs_symlinks;
+mod m20230513_160734_sync_items_move_tracking;
 
 pub struct Migrator;
 
@@ -13,6 +23,16 @@ impl MigratorTrait for Migrator {
             Box::new(m20220101_000001_create_table::Migration),
             Box::new(m20230207_204909_sync_dirs_remove_slash_suffix::Migration),
             Box::new(m20230220_215840_remote_sync_items_fix::Migration),
+            Box::new(m20230312_183502_sync_dirs_crypt_remote::Migration),
+            Box::new(m20230318_141127_sync_dirs_paused::Migration),
+            Box::new(m20230325_102418_create_sync_errors::Migration),
+            Box::new(m20230401_163052_create_activity::Migration),
+            Box::new(m20230408_112944_create_file_versions::Migration),
+            Box::new(m20230415_094126_sync_dirs_honor_gitignore::Migration),
+            Box::new(m20230422_153318_sync_dirs_filters::Migration),
+            Box::new(m20230429_101547_create_unselected_folders::Migration),
+            Box::new(m20230506_141205_sync_dirs_symlinks::Migration),
+            Box::new(m20230513_160734_sync_items_move_tracking::Migration),
         ]
     }
 }
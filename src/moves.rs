@@ -0,0 +1,247 @@
+//! Detection of items that were moved or renamed since they were last synced.
+//! Moves are made on the other side as well, instead of the item being
+//! deleted and copied over again under its new name.
+//!
+//! Local items are recognized by their inode, and remote items by the ID the
+//! remote gives them (on remotes that have IDs).
+use crate::{
+    activity::{self, ActivityKind},
+    entities::{
+        SyncDirsModel, SyncItemsActiveModel, SyncItemsColumn, SyncItemsEntity, SyncItemsModel,
+    },
+    rclone::{self, RcloneRemoteItem},
+    selective::Selection,
+    util,
+};
+use sea_orm::{entity::prelude::*, ActiveValue, DatabaseConnection};
+use std::{
+    collections::HashSet,
+    fs::{self, Metadata},
+    os::unix::fs::MetadataExt,
+    path::Path,
+    time::SystemTime,
+};
+
+/// Get the inode of a local item, in the form it's stored in the database.
+pub fn inode(metadata: &Metadata) -> i64 {
+    metadata.ino() as i64
+}
+
+/// Get the modification time of a local item as a UNIX timestamp.
+fn local_timestamp(metadata: &Metadata) -> Option<u64> {
+    metadata
+        .modified()
+        .ok()
+        .and_then(|time| time.duration_since(SystemTime::UNIX_EPOCH).ok())
+        .map(|duration| duration.as_secs())
+}
+
+/// Find the synced item that the local item at `local_path` was moved from,
+/// if it was moved since it was last synced. The item only counts as moved if
+/// nothing exists at its old local path anymore, and the remote item at its
+/// old remote path hasn't changed since it was last synced.
+pub fn find_local_move(
+    db: &DatabaseConnection,
+    sync_dir: &SyncDirsModel,
+    rclone_remote: &str,
+    local_path: &str,
+    metadata: &Metadata,
+) -> Option<SyncItemsModel> {
+    let candidates = util::await_future(
+        SyncItemsEntity::find()
+            .filter(SyncItemsColumn::SyncDirId.eq(sync_dir.id))
+            .filter(SyncItemsColumn::LocalInode.eq(inode(metadata)))
+            .all(db),
+    )
+    .unwrap();
+
+    candidates.into_iter().find(|candidate| {
+        if candidate.local_path == local_path || fs::symlink_metadata(&candidate.local_path).is_ok()
+        {
+            return false;
+        }
+
+        // Inodes get reused once items are deleted, so make sure files are
+        // otherwise unchanged too. Moving a file doesn't change its timestamp.
+        if !metadata.is_dir()
+            && local_timestamp(metadata) != u64::try_from(candidate.last_local_timestamp).ok()
+        {
+            return false;
+        }
+
+        match rclone::sync::stat(rclone_remote, &candidate.remote_path) {
+            Ok(Some(item)) => {
+                item.is_dir == metadata.is_dir()
+                    && (item.is_dir
+                        || item.mod_time.unix_timestamp()
+                            == i64::from(candidate.last_remote_timestamp))
+            }
+            _ => false,
+        }
+    })
+}
+
+/// Point the database records for a moved item, and those for anything inside
+/// of it, at the item's new paths. `remote_item` is the item at its new path on
+/// the remote.
+pub fn update_paths(
+    db: &DatabaseConnection,
+    moved: &SyncItemsModel,
+    local_path: &str,
+    remote_path: &str,
+    remote_item: &RcloneRemoteItem,
+) {
+    util::await_future(async {
+        let children = SyncItemsEntity::find()
+            .filter(SyncItemsColumn::SyncDirId.eq(moved.sync_dir_id))
+            .filter(util::starts_with(
+                SyncItemsColumn::LocalPath,
+                &format!("{}/", moved.local_path),
+            ))
+            .all(db)
+            .await
+            .unwrap();
+
+        for child in children {
+            let child_local_path = match child.local_path.strip_prefix(&moved.local_path) {
+                Some(rest) if rest.starts_with('/') => format!("{local_path}{rest}"),
+                _ => continue,
+            };
+            let child_remote_path = match child.remote_path.strip_prefix(&moved.remote_path) {
+                Some(rest) if rest.starts_with('/') => format!("{remote_path}{rest}"),
+                _ => child.remote_path.clone(),
+            };
+            let mut active_model: SyncItemsActiveModel = child.into();
+            active_model.local_path = ActiveValue::Set(child_local_path);
+            active_model.remote_path = ActiveValue::Set(child_remote_path);
+            active_model.update(db).await.unwrap();
+        }
+
+        let mut active_model: SyncItemsActiveModel = moved.clone().into();
+        active_model.local_path = ActiveValue::Set(local_path.to_owned());
+        active_model.remote_path = ActiveValue::Set(remote_path.to_owned());
+        active_model.last_remote_timestamp =
+            ActiveValue::Set(remote_item.mod_time.unix_timestamp().try_into().unwrap());
+        if remote_item.id.is_some() {
+            active_model.remote_id = ActiveValue::Set(remote_item.id.clone());
+        }
+        active_model.update(db).await.unwrap();
+    });
+}
+
+/// Find items that were moved on the remote since they were last synced, and
+/// move the matching local items to match. `listing` is a recursive listing of
+/// the sync directory on the remote.
+///
+/// Local items that changed since they were last synced are left alone, so
+/// that the usual sync checks can handle them. Returns the local paths that
+/// couldn't be moved, along with why.
+pub fn apply_remote_moves(
+    db: &DatabaseConnection,
+    sync_dir: &SyncDirsModel,
+    listing: &[RcloneRemoteItem],
+) -> Vec<(String, String)> {
+    let selection = Selection::load(db, sync_dir.id);
+    let remote_paths: HashSet<&str> = listing.iter().map(|item| item.path.as_str()).collect();
+    let mut errors = vec![];
+
+    // Handle folders before anything inside of them, so that items that only
+    // moved along with their folder don't get moved again.
+    let mut items: Vec<&RcloneRemoteItem> =
+        listing.iter().filter(|item| item.id.is_some()).collect();
+    items.sort_by_key(|item| item.path.len());
+    let mut moved_dirs: Vec<String> = vec![];
+
+    for item in items {
+        if moved_dirs
+            .iter()
+            .any(|dir| item.path.starts_with(&format!("{dir}/")))
+        {
+            continue;
+        }
+
+        let relative_path = util::strip_slashes(
+            item.path
+                .strip_prefix(&sync_dir.remote_path)
+                .unwrap_or(&item.path),
+        );
+        if selection.is_unselected(&relative_path) {
+            continue;
+        }
+
+        let (already_synced, moved) = util::await_future(async {
+            let already_synced = SyncItemsEntity::find()
+                .filter(SyncItemsColumn::SyncDirId.eq(sync_dir.id))
+                .filter(SyncItemsColumn::RemotePath.eq(item.path.clone()))
+                .one(db)
+                .await
+                .unwrap()
+                .is_some();
+            let moved = SyncItemsEntity::find()
+                .filter(SyncItemsColumn::SyncDirId.eq(sync_dir.id))
+                .filter(SyncItemsColumn::RemoteId.eq(item.id.clone()))
+                .all(db)
+                .await
+                .unwrap()
+                .into_iter()
+                .find(|candidate| !remote_paths.contains(candidate.remote_path.as_str()));
+            (already_synced, moved)
+        });
+        let moved = match moved {
+            Some(moved) if !already_synced => moved,
+            _ => continue,
+        };
+
+        // Links that are stored on the remote have a suffix that the local link
+        // doesn't.
+        let mut local_path = format!("{}/{relative_path}", sync_dir.local_path);
+        if moved.remote_path.ends_with(rclone::LINK_SUFFIX)
+            && !moved.local_path.ends_with(rclone::LINK_SUFFIX)
+            && let Some(stripped) = local_path.strip_suffix(rclone::LINK_SUFFIX)
+        {
+            local_path = stripped.to_owned();
+        }
+
+        // Only move the local item if it's unchanged since it was last synced, and
+        // if it wouldn't replace anything.
+        let metadata = match fs::symlink_metadata(&moved.local_path) {
+            Ok(metadata) => metadata,
+            Err(_) => continue,
+        };
+        if metadata.is_dir() != item.is_dir
+            || (!item.is_dir
+                && local_timestamp(&metadata) != u64::try_from(moved.last_local_timestamp).ok())
+            || fs::symlink_metadata(&local_path).is_ok()
+        {
+            continue;
+        }
+
+        if let Some(parent) = Path::new(&local_path).parent()
+            && let Err(err) = fs::create_dir_all(parent)
+        {
+            errors.push((local_path, err.to_string()));
+            continue;
+        }
+        if let Err(err) = fs::rename(&moved.local_path, &local_path) {
+            errors.push((moved.local_path.clone(), err.to_string()));
+            continue;
+        }
+
+        update_paths(db, &moved, &local_path, &item.path, item);
+        activity::record(
+            db,
+            sync_dir,
+            ActivityKind::MoveLocal,
+            &local_path,
+            &item.path,
+            None,
+            &tr::tr!("Moved from '{}'.", util::fmt_home(&moved.local_path)),
+        );
+
+        if item.is_dir {
+            moved_dirs.push(item.path.clone());
+        }
+    }
+
+    errors
+}
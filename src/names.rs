@@ -0,0 +1,311 @@
+//! Checks for item names that a remote won't accept, along with the dialog for
+//! renaming local items that can't be synced because of their names.
+use crate::{
+    entities::SyncDirsModel,
+    gtk_util,
+    rclone::{self, Remote, WebDavVendors},
+    util,
+};
+use adw::{glib, gtk::Entry, prelude::*, MessageDialog};
+use std::{fs, path::Path};
+
+/// The longest names most remotes accept, in bytes.
+const MAX_NAME_BYTES: usize = 255;
+
+/// The longest names that can be stored in an encrypted directory, in bytes.
+/// Encrypting a name makes it longer, so this is what fits in
+/// [`MAX_NAME_BYTES`] once encrypted.
+const MAX_CRYPT_NAME_BYTES: usize = 143;
+
+/// The limits a remote places on item names. Rclone already encodes most
+/// characters that remotes don't accept (i.e. by swapping them for lookalike
+/// characters), so these only cover what it leaves alone.
+#[derive(Clone, Debug, Default)]
+pub struct NameLimits {
+    /// The longest names the remote accepts, in bytes.
+    max_bytes: Option<usize>,
+    /// Names that the remote refuses to store.
+    forbidden_names: &'static [&'static str],
+    /// Endings that the remote refuses to store names with.
+    forbidden_suffixes: &'static [&'static str],
+    /// Characters that the remote doesn't allow in names.
+    forbidden_chars: &'static [char],
+    /// Whether names can't start or end with whitespace.
+    no_edge_whitespace: bool,
+}
+
+/// The limits Nextcloud and ownCloud place on names. They refuse to store some
+/// names, as they use them for their own files.
+const NEXTCLOUD_LIMITS: NameLimits = NameLimits {
+    max_bytes: Some(MAX_NAME_BYTES),
+    forbidden_names: &[".htaccess"],
+    forbidden_suffixes: &[".part"],
+    forbidden_chars: &['\\'],
+    no_edge_whitespace: true,
+};
+
+impl NameLimits {
+    /// Get the limits for the remote a sync directory is synced with. This
+    /// reads the remote's config, so it should only be called once per sync
+    /// directory on each pass.
+    pub fn for_sync_dir(remote_name: &str, sync_dir: &SyncDirsModel) -> Self {
+        // Encrypted names don't contain anything the remote could object to, but
+        // are longer than the names they were encrypted from.
+        if sync_dir.crypt_remote.is_some() {
+            return Self {
+                max_bytes: Some(MAX_CRYPT_NAME_BYTES),
+                ..Default::default()
+            };
+        }
+
+        match rclone::get_remote(remote_name) {
+            // Google Drive doesn't have any limits of its own that matter here.
+            Some(Remote::GDrive(_)) => Self::default(),
+            Some(Remote::WebDav(remote))
+                if matches!(
+                    remote.vendor,
+                    WebDavVendors::Nextcloud | WebDavVendors::Owncloud
+                ) =>
+            {
+                NEXTCLOUD_LIMITS
+            }
+            _ => Self {
+                max_bytes: Some(MAX_NAME_BYTES),
+                ..Default::default()
+            },
+        }
+    }
+
+    /// Get why a name isn't accepted by the remote, or [`None`] if it is.
+    pub fn problem(&self, name: &str) -> Option<String> {
+        if let Some(max_bytes) = self.max_bytes
+            && name.len() > max_bytes
+        {
+            return Some(tr::tr!(
+                "the name is longer than the remote allows ({} bytes)",
+                max_bytes
+            ));
+        }
+
+        if self.forbidden_names.contains(&name) {
+            return Some(tr::tr!("the remote doesn't allow this name"));
+        }
+
+        if let Some(suffix) = self
+            .forbidden_suffixes
+            .iter()
+            .find(|suffix| name.ends_with(*suffix))
+        {
+            return Some(tr::tr!(
+                "the remote doesn't allow names ending in '{}'",
+                suffix
+            ));
+        }
+
+        if let Some(character) = name
+            .chars()
+            .find(|character| self.forbidden_chars.contains(character))
+        {
+            return Some(tr::tr!("the remote doesn't allow '{}' in names", character));
+        }
+
+        if self.no_edge_whitespace && name.trim() != name {
+            return Some(tr::tr!(
+                "the remote doesn't allow names that start or end with spaces"
+            ));
+        }
+
+        None
+    }
+
+    /// Get a name close to `name` that the remote accepts.
+    pub fn suggest(&self, name: &str) -> String {
+        let mut suggestion: String = name
+            .chars()
+            .map(|character| {
+                if self.forbidden_chars.contains(&character) {
+                    '_'
+                } else {
+                    character
+                }
+            })
+            .collect();
+
+        if self.no_edge_whitespace {
+            suggestion = suggestion.trim().to_owned();
+        }
+
+        for suffix in self.forbidden_suffixes {
+            if let Some(stem) = suggestion.strip_suffix(suffix) {
+                suggestion = format!("{stem}{}", suffix.replace('.', "_"));
+            }
+        }
+
+        if self.forbidden_names.contains(&suggestion.as_str()) {
+            suggestion = suggestion.replacen('.', "_", 1);
+        }
+
+        if let Some(max_bytes) = self.max_bytes
+            && suggestion.len() > max_bytes
+        {
+            // Cut the name down while keeping its extension, making sure not to split
+            // a character in half.
+            let (stem, extension) = match suggestion.rsplit_once('.') {
+                Some((stem, extension)) if !stem.is_empty() && extension.len() + 1 < max_bytes => {
+                    (stem.to_owned(), format!(".{extension}"))
+                }
+                _ => (suggestion.clone(), String::new()),
+            };
+            let mut stem_len = max_bytes - extension.len();
+            while !stem.is_char_boundary(stem_len) {
+                stem_len -= 1;
+            }
+            suggestion = format!("{}{extension}", &stem[..stem_len]);
+        }
+
+        suggestion
+    }
+}
+
+/// Show a dialog for renaming the local item at `local_path`. `body` explains
+/// why the item needs to be renamed, `suggested_name` is filled in as its new
+/// name, and only names that pass `is_valid` can be chosen. `on_renamed` is
+/// called once the item has been renamed, and `on_cancel` if the dialog is
+/// closed without renaming it.
+pub fn rename_dialog<F1: Fn(&str) -> bool + 'static, F2: Fn() + 'static, F3: Fn() + 'static>(
+    local_path: &str,
+    body: &str,
+    suggested_name: &str,
+    is_valid: F1,
+    on_renamed: F2,
+    on_cancel: F3,
+) {
+    let path = Path::new(local_path);
+    let parent = path.parent().unwrap().to_owned();
+    let name = path.file_name().unwrap().to_string_lossy().to_string();
+
+    let entry = Entry::builder().activates_default(true).build();
+    let dialog = MessageDialog::builder()
+        .heading(&tr::tr!("Rename Item"))
+        .body(body)
+        .extra_child(&entry)
+        .modal(true)
+        .build();
+    dialog.add_response("cancel", &tr::tr!("Cancel"));
+    dialog.add_response("rename", &tr::tr!("Rename"));
+    dialog.set_default_response(Some("rename"));
+    dialog.set_response_enabled("rename", false);
+
+    entry.connect_changed(glib::clone!(@weak dialog, @strong parent => move |entry| {
+        let new_name = entry.text();
+        let valid = !new_name.is_empty()
+            && !new_name.contains('/')
+            && is_valid(&new_name)
+            && fs::symlink_metadata(parent.join(new_name.as_str())).is_err();
+        dialog.set_response_enabled("rename", valid);
+    }));
+    entry.set_text(suggested_name);
+    dialog.connect_response(
+        None,
+        glib::clone!(@weak entry, @strong parent, @strong name => move |dialog, resp| {
+            dialog.close();
+
+            if resp != "rename" {
+                on_cancel();
+                return;
+            }
+
+            let new_path = parent.join(entry.text().as_str());
+            if let Err(err) = fs::rename(parent.join(&name), &new_path) {
+                gtk_util::show_error(&tr::tr!("Unable to rename '{}'", util::fmt_home(&parent.join(&name).to_string_lossy())), Some(&err.to_string()));
+                on_cancel();
+                return;
+            }
+
+            on_renamed();
+        }),
+    );
+    dialog.show();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The limits for remotes that only limit the length of names.
+    const LENGTH_LIMITS: NameLimits = NameLimits {
+        max_bytes: Some(MAX_NAME_BYTES),
+        forbidden_names: &[],
+        forbidden_suffixes: &[],
+        forbidden_chars: &[],
+        no_edge_whitespace: false,
+    };
+
+    /// Check that `limits` suggests `expected` for `name`, and that the
+    /// suggestion is accepted.
+    fn assert_suggests(limits: &NameLimits, name: &str, expected: &str) {
+        assert!(limits.problem(name).is_some());
+        let suggestion = limits.suggest(name);
+        assert_eq!(suggestion, expected);
+        assert!(limits.problem(&suggestion).is_none());
+    }
+
+    #[test]
+    fn accepted_names_are_kept() {
+        assert!(NEXTCLOUD_LIMITS.problem("notes.txt").is_none());
+        assert_eq!(NEXTCLOUD_LIMITS.suggest("notes.txt"), "notes.txt");
+        assert!(NameLimits::default().problem(&"a".repeat(1000)).is_none());
+    }
+
+    #[test]
+    fn long_multibyte_names() {
+        // Each 'é' is 2 bytes, so the name is cut on a character boundary one byte
+        // short of the limit.
+        let name = format!("{}.txt", "é".repeat(200));
+        let suggestion = format!("{}.txt", "é".repeat(125));
+        assert_suggests(&LENGTH_LIMITS, &name, &suggestion);
+        assert_eq!(suggestion.len(), MAX_NAME_BYTES - 1);
+    }
+
+    #[test]
+    fn long_names_without_extensions() {
+        let name = "日本語".repeat(50);
+        let suggestion = LENGTH_LIMITS.suggest(&name);
+        assert_eq!(suggestion, "日本語".repeat(28) + "日");
+        assert!(LENGTH_LIMITS.problem(&suggestion).is_none());
+    }
+
+    #[test]
+    fn long_encrypted_names() {
+        let limits = NameLimits {
+            max_bytes: Some(MAX_CRYPT_NAME_BYTES),
+            ..Default::default()
+        };
+        let suggestion = limits.suggest(&format!("{}.tar.gz", "ü".repeat(100)));
+        assert_eq!(suggestion, format!("{}.gz", "ü".repeat(70)));
+        assert!(limits.problem(&suggestion).is_none());
+    }
+
+    #[test]
+    fn forbidden_names() {
+        assert_suggests(&NEXTCLOUD_LIMITS, ".htaccess", "_htaccess");
+    }
+
+    #[test]
+    fn forbidden_suffixes() {
+        assert_suggests(&NEXTCLOUD_LIMITS, "foo.part", "foo_part");
+    }
+
+    #[test]
+    fn forbidden_chars() {
+        assert_suggests(&NEXTCLOUD_LIMITS, "a\\b.txt", "a_b.txt");
+    }
+
+    #[test]
+    fn edge_whitespace() {
+        assert_suggests(&NEXTCLOUD_LIMITS, " notes.txt", "notes.txt");
+        assert_suggests(&NEXTCLOUD_LIMITS, "notes.txt  ", "notes.txt");
+        assert_suggests(&NEXTCLOUD_LIMITS, "  foo.part ", "foo_part");
+        assert!(LENGTH_LIMITS.problem(" notes.txt ").is_none());
+    }
+}
@@ -1,11 +1,196 @@
 //! Structs and functions for use with Rclone RPC calls.
-use crate::util;
+use crate::{keyring, util};
 use adw::glib;
+use indexmap::IndexMap;
+use regex::Regex;
 use serde::Deserialize;
 use serde_json::json;
-use std::collections::HashMap;
+use std::{collections::HashMap, sync::Mutex};
 use time::OffsetDateTime;
 
+/// The config keys that hold credentials. These get stored in the keyring
+/// instead of the config file when one is available.
+const SECRET_KEYS: &[&str] = &[
+    "pass",
+    "password",
+    "password2",
+    "token",
+    "bearer_token",
+    "client_uid",
+    "client_access_token",
+    "client_refresh_token",
+    "client_salted_key_pass",
+];
+
+lazy_static::lazy_static! {
+    /// The credentials that have been loaded from the keyring, keyed by remote name.
+    static ref SECRETS: Mutex<HashMap<String, HashMap<String, String>>> = Mutex::new(HashMap::new());
+    /// The connection string parameters from [`secret_params`], keyed by remote
+    /// name. These get cleared whenever any credentials change.
+    static ref SECRET_PARAMS: Mutex<HashMap<String, String>> = Mutex::new(HashMap::new());
+}
+
+/// The suffix Rclone gives to the files it stores symbolic links as, when
+/// they're being translated (see [`sync::copy_link_to_remote`]). The content of
+/// such a file is the link's target.
+pub const LINK_SUFFIX: &str = ".rclonelink";
+
+/// Get the raw config values for a remote from the config file.
+fn get_config(remote: &str) -> HashMap<String, String> {
+    let config_str = librclone::rpc("config/get", json!({ "name": remote }).to_string()).unwrap();
+    serde_json::from_str(&config_str).unwrap()
+}
+
+/// Get the credentials stored in the keyring for a remote, or an empty map if
+/// there aren't any. Returns an error if the keyring couldn't be read (i.e. if
+/// it isn't available, or the user dismissed the prompt to unlock it).
+fn get_secrets(remote: &str) -> Result<HashMap<String, String>, glib::Error> {
+    if let Some(secrets) = SECRETS.lock().unwrap().get(remote) {
+        return Ok(secrets.clone());
+    }
+
+    // The lock isn't held here, as unlocking the keyring can end up running the
+    // main loop while the user's being prompted. Failed lookups aren't cached, so
+    // the credentials get picked up once the keyring can be read again.
+    let secrets = keyring::lookup(remote)?.unwrap_or_default();
+    SECRETS
+        .lock()
+        .unwrap()
+        .insert(remote.to_owned(), secrets.clone());
+    Ok(secrets)
+}
+
+/// Move any credentials in a remote's config file entry into the keyring. This
+/// also picks up any OAuth tokens that Rclone refreshed and wrote back to the
+/// config file. If the keyring isn't available, the credentials are left in
+/// the config file.
+pub fn store_secrets(remote: &str) {
+    let new_secrets: HashMap<String, String> = get_config(remote)
+        .into_iter()
+        .filter(|(key, value)| SECRET_KEYS.contains(&key.as_str()) && !value.is_empty())
+        .collect();
+
+    if new_secrets.is_empty() {
+        return;
+    }
+
+    // The keyring item gets replaced as a whole, so the credentials that are
+    // already in it need to be known first. If they can't be read, leave the new
+    // ones in the config file until they can.
+    let mut secrets = match get_secrets(remote) {
+        Ok(secrets) => secrets,
+        Err(_) => return,
+    };
+    secrets.extend(new_secrets.clone());
+
+    if keyring::store(remote, &secrets).is_err() {
+        return;
+    }
+
+    SECRETS.lock().unwrap().insert(remote.to_owned(), secrets);
+
+    // Values in the config file are already obscured, so make sure Rclone doesn't
+    // obscure the empty values we're replacing them with.
+    let cleared: HashMap<String, String> = new_secrets
+        .into_keys()
+        .map(|key| (key, String::new()))
+        .collect();
+    librclone::rpc(
+        "config/update",
+        json!({
+            "name": remote,
+            "parameters": cleared,
+            "opt": {
+                "nonInteractive": true,
+                "noObscure": true
+            }
+        })
+        .to_string(),
+    )
+    .unwrap();
+    clear_secret_params();
+}
+
+/// Delete the credentials stored in the keyring for a remote.
+pub fn delete_secrets(remote: &str) {
+    SECRETS.lock().unwrap().remove(remote);
+    clear_secret_params();
+    let _ = keyring::delete(remote);
+}
+
+/// Forget the cached connection string parameters for every remote. This needs
+/// to be called after a remote's config gets changed, as values in the config
+/// file change which credentials get passed from the keyring.
+pub fn clear_secret_params() {
+    SECRET_PARAMS.lock().unwrap().clear();
+}
+
+/// Get the connection string parameters needed to pass a remote's credentials
+/// from the keyring to Rclone, such as `,token='...'`. Values in the config
+/// file take precedence, so freshly entered login details get used over older
+/// ones in the keyring.
+///
+/// This gets called for every Rclone command, so the parameters are cached
+/// until the remote's credentials next change (see [`store_secrets`]).
+fn secret_params(remote: &str) -> String {
+    if let Some(params) = SECRET_PARAMS.lock().unwrap().get(remote) {
+        return params.clone();
+    }
+
+    let (params, complete) = load_secret_params(remote);
+
+    // Parameters that are missing credentials because the keyring couldn't be read
+    // aren't cached, so they get filled in once it can be.
+    if complete {
+        SECRET_PARAMS
+            .lock()
+            .unwrap()
+            .insert(remote.to_owned(), params.clone());
+    }
+
+    params
+}
+
+/// Build the parameters for [`secret_params`]. Also returns whether the
+/// keyring could be read for the remote (and for the remote it wraps, for crypt
+/// remotes).
+fn load_secret_params(remote: &str) -> (String, bool) {
+    let (secrets, mut complete) = match get_secrets(remote) {
+        Ok(secrets) => (secrets, true),
+        Err(_) => (HashMap::new(), false),
+    };
+    let config = get_config(remote);
+    let mut params = String::new();
+
+    // Crypt remotes point to another remote, which needs its own credentials passed
+    // along as well.
+    if config.get("type").map(|remote_type| remote_type.as_str()) == Some("crypt")
+        && let Some((wrapped_name, wrapped_path)) = config
+            .get("remote")
+            .and_then(|wrapped| wrapped.split_once(':'))
+    {
+        let (wrapped_params, wrapped_complete) = load_secret_params(wrapped_name);
+        complete &= wrapped_complete;
+
+        if !wrapped_params.is_empty() {
+            let wrapped = format!("{wrapped_name}{wrapped_params}:{wrapped_path}");
+            params.push_str(&format!(",remote='{}'", wrapped.replace('\'', "''")));
+        }
+    }
+
+    let mut keys: Vec<&String> = secrets
+        .keys()
+        .filter(|key| config.get(*key).is_none_or(|value| value.is_empty()))
+        .collect();
+    keys.sort();
+
+    for key in keys {
+        params.push_str(&format!(",{key}='{}'", secrets[key].replace('\'', "''")));
+    }
+
+    (params, complete)
+}
+
 /// Get a remote from the config file.
 pub fn get_remote<T: ToString>(remote: T) -> Option<Remote> {
     let remote = remote.to_string();
@@ -17,39 +202,43 @@ pub fn get_remote<T: ToString>(remote: T) -> Option<Remote> {
     );
     let config: HashMap<String, String> = serde_json::from_str(&config_str).unwrap();
 
-    match config["type"].as_str() {
-        "dropbox" => Some(Remote::Dropbox(DropboxRemote {
+    // Configs that weren't made by Celeste (i.e. imported ones) can leave out
+    // values, such as the client ID for remotes using Rclone's own client.
+    let get = |key: &str| config.get(key).cloned().unwrap_or_default();
+
+    match config.get("type").map(|remote_type| remote_type.as_str()) {
+        Some("dropbox") => Some(Remote::Dropbox(DropboxRemote {
             remote_name: remote,
-            client_id: config["client_id"].clone(),
-            client_secret: config["client_secret"].clone(),
+            client_id: get("client_id"),
+            client_secret: get("client_secret"),
         })),
-        "drive" => Some(Remote::GDrive(GDriveRemote {
+        Some("drive") => Some(Remote::GDrive(GDriveRemote {
             remote_name: remote,
-            client_id: config["client_id"].clone(),
-            client_secret: config["client_secret"].clone(),
+            client_id: get("client_id"),
+            client_secret: get("client_secret"),
         })),
-        "pcloud" => Some(Remote::PCloud(PCloudRemote {
+        Some("pcloud") => Some(Remote::PCloud(PCloudRemote {
             remote_name: remote,
-            client_id: config["client_id"].clone(),
-            client_secret: config["client_secret"].clone(),
+            client_id: get("client_id"),
+            client_secret: get("client_secret"),
         })),
-        "protondrive" => Some(Remote::ProtonDrive(ProtonDriveRemote {
+        Some("protondrive") => Some(Remote::ProtonDrive(ProtonDriveRemote {
             remote_name: remote,
-            username: config["username"].clone(),
+            username: get("username"),
         })),
-        "webdav" => {
-            let vendor = match config["vendor"].as_str() {
-                "nextcloud" => WebDavVendors::Nextcloud,
-                "owncloud" => WebDavVendors::Owncloud,
-                "webdav" => WebDavVendors::WebDav,
-                _ => unreachable!(),
+        Some("webdav") => {
+            let vendor = match config.get("vendor").map(|vendor| vendor.as_str()) {
+                Some("nextcloud") => WebDavVendors::Nextcloud,
+                Some("owncloud") => WebDavVendors::Owncloud,
+                Some("webdav") | None => WebDavVendors::WebDav,
+                _ => return None,
             };
 
             Some(Remote::WebDav(WebDavRemote {
                 remote_name: remote,
-                user: config["user"].clone(),
-                pass: config["pass"].clone(),
-                url: config["user"].clone(),
+                user: get("user"),
+                pass: get("pass"),
+                url: get("url"),
                 vendor,
             }))
         }
@@ -57,25 +246,176 @@ pub fn get_remote<T: ToString>(remote: T) -> Option<Remote> {
     }
 }
 
-/// Get all the remotes from the config file.
-pub fn get_remotes() -> Vec<Remote> {
+/// Get the names of every remote in the config file, including ones Celeste
+/// doesn't manage itself (i.e. the crypt remotes for encrypted sync
+/// directories). New remotes can't reuse any of these names, as that would
+/// replace the existing config.
+pub fn get_remote_names() -> Vec<String> {
     let configs_str = util::run_in_background(move || {
         librclone::rpc("config/listremotes", json!({}).to_string())
             .unwrap_or_else(|_| unreachable!())
     });
-    let configs = {
-        let config: HashMap<String, Vec<String>> = serde_json::from_str(&configs_str).unwrap();
-        config.get(&"remotes".to_string()).unwrap().to_owned()
-    };
+    let config: HashMap<String, Vec<String>> = serde_json::from_str(&configs_str).unwrap();
+    config
+        .get(&"remotes".to_string())
+        .cloned()
+        .unwrap_or_default()
+}
+
+/// Get all the remotes from the config file.
+pub fn get_remotes() -> Vec<Remote> {
     let mut celeste_configs = vec![];
 
-    for config in configs {
-        celeste_configs.push(get_remote(&config).unwrap());
+    // Crypt remotes for encrypted sync directories aren't remotes in their own
+    // right, so skip over them.
+    for config in get_remote_names() {
+        if let Some(remote) = get_remote(&config) {
+            celeste_configs.push(remote);
+        }
     }
 
     celeste_configs
 }
 
+/// Create an Rclone crypt remote that encrypts everything stored through it
+/// on `remote`, using `password` as the encryption password. Returns the name
+/// of the new crypt remote.
+pub fn create_crypt_remote(remote: &str, password: &str) -> Result<String, RcloneError> {
+    let existing = get_remote_names();
+
+    let mut index = 1;
+    let crypt_name = loop {
+        let crypt_name = format!("{remote}-crypt-{index}");
+
+        if !existing.contains(&crypt_name) {
+            break crypt_name;
+        }
+
+        index += 1;
+    };
+
+    let crypt_query = json!({
+        "name": crypt_name,
+        "type": "crypt",
+        "parameters": {
+            "remote": format!("{remote}:"),
+            "password": password,
+            "filename_encryption": "standard",
+            "directory_name_encryption": "true"
+        },
+        "opt": {
+            "obscure": true,
+            "nonInteractive": true
+        }
+    });
+    let resp =
+        util::run_in_background(move || librclone::rpc("config/create", crypt_query.to_string()));
+
+    match resp {
+        Ok(_) => {
+            store_secrets(&crypt_name);
+            Ok(crypt_name)
+        }
+        Err(json_str) => Err(serde_json::from_str(&json_str).unwrap()),
+    }
+}
+
+/// Parse the contents of an Rclone config file. Returns a map of remote names
+/// to the key/value pairs (including `type`) set for each remote.
+pub fn parse_config(content: &str) -> Result<IndexMap<String, IndexMap<String, String>>, String> {
+    let mut remotes: IndexMap<String, IndexMap<String, String>> = IndexMap::new();
+    let mut current_remote: Option<String> = None;
+
+    for (index, line) in content.lines().enumerate() {
+        let line = line.trim();
+
+        if line.starts_with("RCLONE_ENCRYPT_V0:") {
+            return Err(tr::tr!("Encrypted config files aren't supported."));
+        } else if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+            continue;
+        } else if let Some(section) = line.strip_prefix('[')
+            && let Some(name) = section.strip_suffix(']')
+        {
+            remotes.insert(name.to_owned(), IndexMap::new());
+            current_remote = Some(name.to_owned());
+        } else if let Some((key, value)) = line.split_once('=')
+            && let Some(remote) = &current_remote
+        {
+            remotes
+                .get_mut(remote)
+                .unwrap()
+                .insert(key.trim().to_owned(), value.trim().to_owned());
+        } else {
+            return Err(tr::tr!("Invalid line {}: '{}'", index + 1, line));
+        }
+    }
+
+    Ok(remotes)
+}
+
+/// Get the name to show in the UI for a set of config parameters, such as those
+/// returned from [`parse_config`]. Returns [`None`] if Celeste doesn't support
+/// the remote's type.
+pub fn config_type_name(config: &IndexMap<String, String>) -> Option<String> {
+    let name = match config.get("type")?.as_str() {
+        "dropbox" => "Dropbox",
+        "drive" => "Google Drive",
+        "pcloud" => "pCloud",
+        "protondrive" => "Proton Drive",
+        "webdav" => match config.get("vendor").map(|vendor| vendor.as_str()) {
+            Some("nextcloud") => "Nextcloud",
+            Some("owncloud") => "Owncloud",
+            _ => "WebDAV",
+        },
+        _ => return None,
+    };
+
+    Some(name.to_owned())
+}
+
+/// Check that a remote's config can be used by Celeste, for remotes that
+/// weren't set up through Celeste (i.e. ones being imported). Returns why it
+/// can't be used if it can't, which is also the case for unsupported types
+/// (see [`config_type_name`]).
+pub fn config_problem(config: &IndexMap<String, String>) -> Option<String> {
+    let remote_type = config.get("type").cloned().unwrap_or_default();
+    let has = |key: &str| config.get(key).is_some_and(|value| !value.is_empty());
+
+    if config_type_name(config).is_none() {
+        return Some(tr::tr!(
+            "Remotes of type '{}' aren't supported.",
+            remote_type
+        ));
+    }
+
+    match remote_type.as_str() {
+        "protondrive" if !has("username") => {
+            Some(tr::tr!("The remote doesn't have a username set."))
+        }
+        "webdav" => {
+            let vendor = config.get("vendor").map(|vendor| vendor.as_str());
+
+            // Rclone handles other vendors differently (i.e. logging in to SharePoint
+            // with cookies), which Celeste can't keep track of.
+            if !matches!(vendor, Some("nextcloud" | "owncloud" | "webdav") | None) {
+                Some(tr::tr!(
+                    "WebDAV servers from the vendor '{}' aren't supported.",
+                    vendor.unwrap()
+                ))
+            } else if !has("url") {
+                Some(tr::tr!("The remote doesn't have a URL set."))
+            } else if !has("user") || has("bearer_token") || has("bearer_token_command") {
+                Some(tr::tr!(
+                    "Only WebDAV servers that are logged in to with a username and password are supported."
+                ))
+            } else {
+                None
+            }
+        }
+        _ => None,
+    }
+}
+
 /// The types of remotes in the config.
 #[derive(Clone)]
 pub enum Remote {
@@ -178,10 +518,208 @@ impl ToString for WebDavVendors {
     }
 }
 
+/// The kinds of errors that can be returned from Rclone.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum RcloneErrorKind {
+    /// The remote couldn't be reached, such as from the network being down,
+    /// DNS lookups failing, or the server being temporarily unavailable.
+    Network,
+    /// The remote's login details aren't valid, such as from an expired or
+    /// revoked OAuth token.
+    Auth,
+    /// The remote is out of storage space.
+    QuotaExceeded,
+    /// The remote doesn't allow access to the requested item.
+    PermissionDenied,
+    /// The requested item doesn't exist.
+    NotFound,
+    /// Too many requests have been sent to the remote, and it wants us to slow
+    /// down.
+    RateLimited,
+    /// Any other error.
+    Other,
+}
+
+impl RcloneErrorKind {
+    /// Whether errors of this kind are likely to go away on their own, and are
+    /// thus worth retrying.
+    pub fn is_transient(&self) -> bool {
+        matches!(self, Self::Network | Self::RateLimited)
+    }
+
+    /// Find the kind of an error from the HTTP status code Rclone returned it
+    /// with, along with the error message itself.
+    fn from_error(status: u16, error: &str) -> Self {
+        lazy_static::lazy_static! {
+            /// Parts of error messages that name items, such as quoted names and
+            /// paths. These can contain anything, so they're left out of the checks
+            /// below.
+            static ref NAMES_RE: Regex = Regex::new(r#""[^"]*"|\S*/\S*"#).unwrap();
+            /// Dropbox's error tags, such as `path/not_found/..` or
+            /// `too_many_requests/...`. These look like paths to [`NAMES_RE`], so
+            /// they get swapped out for just the tag's name beforehand.
+            static ref DROPBOX_TAGS_RE: Regex = Regex::new(
+                r"(^|[\s:])(?:(?:path|path_lookup|from_lookup|to)/)?(not_found|insufficient_space|no_write_permission|too_many_requests|too_many_write_operations|expired_access_token|invalid_access_token)/\S*"
+            )
+            .unwrap();
+            /// HTTP status codes in error messages, such as in `HTTP error 429`,
+            /// `status code: 507`, or Google's `Error 403:`.
+            static ref HTTP_STATUS_RE: Regex = Regex::new(
+                r"(?i)\b(?:http(?:\s+error|\s+status)?|status(?:\s+code)?|error(?:\s+code)?)[\s:=]*(\d{3})\b"
+            )
+            .unwrap();
+        }
+
+        // Rclone's remote control API returns a 404 when the item being operated on
+        // doesn't exist, but everything else from the remote comes back as a 500, so
+        // we have to go off the error message for those.
+        if status == 404 {
+            return Self::NotFound;
+        }
+
+        // 'i/o' would otherwise get taken for a path.
+        let error = error.to_lowercase().replace("i/o", "io");
+        let error = DROPBOX_TAGS_RE.replace_all(&error, "${1}${2} ");
+        let error = NAMES_RE.replace_all(&error, " ").into_owned();
+        let codes: Vec<u16> = HTTP_STATUS_RE
+            .captures_iter(&error)
+            .filter_map(|captures| captures[1].parse().ok())
+            .collect();
+
+        // Messages are only matched as whole words, so they don't match part of a
+        // longer word.
+        let matches = |msgs: &[&str]| {
+            msgs.iter().any(|msg| {
+                error.match_indices(msg).any(|(index, _)| {
+                    let before = error[..index].chars().next_back();
+                    let after = error[index + msg.len()..].chars().next();
+                    !before.is_some_and(char::is_alphanumeric)
+                        && !after.is_some_and(char::is_alphanumeric)
+                })
+            })
+        };
+        let has_code = |code_list: &[u16]| code_list.iter().any(|code| codes.contains(code));
+
+        if has_code(&[429])
+            || matches(&[
+                "too many requests",
+                "too_many_requests",
+                "too_many_write_operations",
+                "rate limit",
+                "ratelimit",
+                "slow down",
+            ])
+        {
+            Self::RateLimited
+        } else if has_code(&[507])
+            || matches(&[
+                "quota exceeded",
+                "quota has been exceeded",
+                "quotaexceeded",
+                "storagequotaexceeded",
+                "over quota",
+                "insufficient_space",
+                "insufficient storage",
+                "no space left",
+            ])
+        {
+            Self::QuotaExceeded
+        } else if has_code(&[401])
+            || matches(&[
+                "invalid_grant",
+                "cannot fetch token",
+                "couldn't fetch token",
+                "expired_access_token",
+                "invalid_access_token",
+                "unauthorized",
+                "please relogin",
+                "2fa code",
+            ])
+        {
+            Self::Auth
+        } else if has_code(&[403])
+            || matches(&[
+                "forbidden",
+                "permission denied",
+                "access denied",
+                "insufficientpermissions",
+                "no_write_permission",
+            ])
+        {
+            Self::PermissionDenied
+        } else if has_code(&[404])
+            || matches(&[
+                "object not found",
+                "directory not found",
+                "file not found",
+                "path not found",
+                "not_found",
+                "no such file or directory",
+            ])
+        {
+            Self::NotFound
+        } else if has_code(&[502, 503, 504])
+            || matches(&[
+                "no such host",
+                "temporary failure in name resolution",
+                "server misbehaving",
+                "dial tcp",
+                "connection refused",
+                "connection reset by peer",
+                "network is unreachable",
+                "no route to host",
+                "io timeout",
+                "tls handshake timeout",
+                "context deadline exceeded",
+                "bad gateway",
+                "service unavailable",
+                "gateway timeout",
+            ])
+        {
+            Self::Network
+        } else {
+            Self::Other
+        }
+    }
+}
+
+/// The error JSON returned from Rclone's remote control API.
+#[derive(Deserialize)]
+struct RcloneErrorResponse {
+    error: String,
+    #[serde(default)]
+    status: u16,
+}
+
 /// Error returned from Rclone.
 #[derive(Clone, Deserialize, Debug)]
+#[serde(from = "RcloneErrorResponse")]
 pub struct RcloneError {
+    /// The error message.
     pub error: String,
+    /// The HTTP status code Rclone's remote control API returned the error
+    /// with.
+    pub status: u16,
+    /// The kind of error this is.
+    pub kind: RcloneErrorKind,
+}
+
+impl From<RcloneErrorResponse> for RcloneError {
+    fn from(resp: RcloneErrorResponse) -> Self {
+        Self {
+            kind: RcloneErrorKind::from_error(resp.status, &resp.error),
+            error: resp.error,
+            status: resp.status,
+        }
+    }
+}
+
+impl RcloneError {
+    /// Whether this error was caused by the remote needing a 2FA code to log
+    /// in. These also have a kind of [`RcloneErrorKind::Auth`].
+    pub fn needs_2fa_code(&self) -> bool {
+        self.kind == RcloneErrorKind::Auth && self.error.to_lowercase().contains("2fa code")
+    }
 }
 
 /// The output of an `operations/stat` command.
@@ -197,6 +735,23 @@ pub struct RcloneList {
     list: Vec<RcloneRemoteItem>,
 }
 
+/// The output of an `operations/fsinfo` command.
+#[derive(Clone, Deserialize, Debug)]
+pub struct RcloneFsInfo {
+    #[serde(rename = "Features")]
+    features: RcloneFeatures,
+}
+
+/// The optional features a remote supports, from the `Features` object in the
+/// output of the `operations/fsinfo` command.
+#[derive(Clone, Copy, Deserialize, Debug, Default)]
+pub struct RcloneFeatures {
+    /// Whether the remote treats names that only differ in case as the same
+    /// name.
+    #[serde(rename = "CaseInsensitive", default)]
+    pub case_insensitive: bool,
+}
+
 /// The list of items in a folder, from the `list` object in the output of the
 /// `operations/list` command.
 #[derive(Clone, Deserialize, Debug)]
@@ -209,6 +764,34 @@ pub struct RcloneRemoteItem {
     pub name: String,
     #[serde(rename = "ModTime", with = "time::serde::rfc3339")]
     pub mod_time: OffsetDateTime,
+    /// The size of the item in bytes, or `-1` if it's unknown (i.e. for
+    /// directories).
+    #[serde(rename = "Size")]
+    pub size: i64,
+    /// The ID the remote uses for the item, which stays the same when the item
+    /// gets moved. Only some remotes (i.e. Google Drive) have these.
+    #[serde(rename = "ID", default)]
+    pub id: Option<String>,
+    /// The item's metadata. This is only filled in when it's specifically
+    /// requested, and only on remotes that support metadata.
+    #[serde(rename = "Metadata", default)]
+    pub metadata: HashMap<String, String>,
+}
+
+/// The metadata key that a file's POSIX mode gets stored under on the remote.
+/// This is the same key Rclone's local backend uses, so the mode is in the
+/// same form Rclone itself would store it in.
+const MODE_METADATA_KEY: &str = "mode";
+
+impl RcloneRemoteItem {
+    /// The POSIX mode stored in the item's metadata by
+    /// [`sync::copy_to_remote`], if it has one.
+    pub fn mode(&self) -> Option<u32> {
+        self.metadata
+            .get(MODE_METADATA_KEY)
+            .and_then(|mode| u32::from_str_radix(mode, 8).ok())
+            .map(|mode| mode & 0o7777)
+    }
 }
 
 /// The types of items to show in an `operations/list` command.
@@ -223,21 +806,94 @@ pub enum RcloneListFilter {
     Files,
 }
 
+/// Filters for Rclone to apply to the items in an `operations/list` command.
+#[derive(Clone, Debug, Default)]
+pub struct RcloneItemFilter {
+    /// The largest files to return, in bytes.
+    pub max_size: Option<u64>,
+    /// The oldest files to return, in seconds since they were last modified.
+    pub max_age: Option<u64>,
+}
+
 /// Functions for syncing to a remote.
 /// All functions in this module automatically run under
 /// [`util::run_in_background`], so they don't need to be wrapped around
 /// such to be ran during UI execution.
 pub mod sync {
-    use super::{RcloneError, RcloneList, RcloneListFilter, RcloneRemoteItem, RcloneStat};
+    use super::{
+        RcloneError, RcloneErrorKind, RcloneFeatures, RcloneFsInfo, RcloneItemFilter, RcloneList,
+        RcloneListFilter, RcloneRemoteItem, RcloneStat, LINK_SUFFIX, MODE_METADATA_KEY,
+    };
     use crate::util;
+    use rand::Rng;
     use serde_json::json;
+    use std::{
+        collections::HashMap,
+        fs::{self, Permissions},
+        os::unix::fs::{MetadataExt, PermissionsExt},
+        sync::Mutex,
+        thread,
+        time::{Duration, Instant},
+    };
+
+    /// The most times a single command gets retried after a transient error.
+    const MAX_RETRIES: u32 = 4;
+
+    /// The local filesystem, with symbolic links translated to and from files
+    /// ending in [`LINK_SUFFIX`] (the same as Rclone's `--links` flag).
+    const LOCAL_LINKS_FS: &str = ":local,links:/";
+
+    /// The delay before the first retry of a command. This doubles on each
+    /// following retry.
+    const RETRY_DELAY_BASE: Duration = Duration::from_millis(500);
+
+    /// The longest delay between retries of a command.
+    const RETRY_DELAY_MAX: Duration = Duration::from_secs(30);
+
+    /// The most retries allowed for a single remote within
+    /// [`REMOTE_RETRY_WINDOW`]. Once a remote hits this, errors from it are
+    /// returned straight away, so a remote that's having lasting issues
+    /// doesn't hold up syncing with retries that are going to keep failing.
+    const REMOTE_RETRY_LIMIT: usize = 20;
+
+    /// The window of time that [`REMOTE_RETRY_LIMIT`] applies to.
+    const REMOTE_RETRY_WINDOW: Duration = Duration::from_secs(300);
+
+    lazy_static::lazy_static! {
+        /// When each remote's recent retries happened, keyed by remote name.
+        static ref REMOTE_RETRIES: Mutex<HashMap<String, Vec<Instant>>> = Mutex::new(HashMap::new());
+    }
+
+    /// Try to reserve a retry for a remote. Returns [`false`] if the remote has
+    /// hit [`REMOTE_RETRY_LIMIT`].
+    fn reserve_retry(remote_name: &str) -> bool {
+        let mut remote_retries = (*REMOTE_RETRIES).lock().unwrap();
+        let retries = remote_retries.entry(remote_name.to_owned()).or_default();
+        retries.retain(|retry| retry.elapsed() < REMOTE_RETRY_WINDOW);
+
+        if retries.len() >= REMOTE_RETRY_LIMIT {
+            false
+        } else {
+            retries.push(Instant::now());
+            true
+        }
+    }
+
+    /// Get the delay before the given retry of a command, with some jitter
+    /// added so retries from multiple commands don't all line up.
+    fn retry_delay(retry: u32) -> Duration {
+        let delay = (RETRY_DELAY_BASE * 2u32.pow(retry)).min(RETRY_DELAY_MAX);
+        let jitter = rand::thread_rng().gen_range(0..=delay.as_millis() as u64 / 2);
+        delay + Duration::from_millis(jitter)
+    }
 
     /// Get a remote name.
     fn get_remote_name(remote: &str) -> String {
         if remote.ends_with(':') {
             panic!("Remote '{remote}' is not allowed to end with a ':'. Please omit it.",);
         }
-        format!("{remote}:")
+        let secret_params = super::secret_params(remote);
+        format!("{remote}{secret_params}:")
     }
 
     /// Run an Rclone command without blocking the GUI.
@@ -247,25 +903,101 @@ pub mod sync {
         util::run_in_background(|| librclone::rpc(method, input))
     }
 
+    /// Run an Rclone command against a remote without blocking the GUI,
+    /// retrying it with an increasing delay if it fails with a transient error
+    /// (see [`super::RcloneErrorKind::is_transient`]).
+    fn run_with_retries(
+        remote_name: &str,
+        method: &str,
+        input: &str,
+    ) -> Result<String, RcloneError> {
+        let remote_name = remote_name.to_owned();
+        let method = method.to_owned();
+        let input = input.to_owned();
+
+        util::run_in_background(move || {
+            let mut retry = 0;
+
+            loop {
+                let err: RcloneError = match librclone::rpc(method.as_str(), input.as_str()) {
+                    Ok(resp) => return Ok(resp),
+                    Err(json_str) => serde_json::from_str(&json_str).unwrap(),
+                };
+
+                if !err.kind.is_transient() || retry >= MAX_RETRIES || !reserve_retry(&remote_name)
+                {
+                    return Err(err);
+                }
+
+                thread::sleep(retry_delay(retry));
+                retry += 1;
+            }
+        })
+    }
+
+    /// Run a command that changes something on a remote, such as moving or
+    /// deleting an item. Unlike [`run_with_retries`], these can't just be run
+    /// again after a transient error: the change may have still been made on
+    /// the server (i.e. if only the response timed out), and running it again
+    /// would then fail or act on something else. Instead, `is_done` is checked
+    /// to see if the change was made, and the command is only retried if it
+    /// wasn't.
+    fn run_change<F: Fn() -> bool>(
+        remote_name: &str,
+        method: &str,
+        input: &str,
+        is_done: F,
+    ) -> Result<(), RcloneError> {
+        let mut retry = 0;
+
+        loop {
+            let err: RcloneError = match run(method, input) {
+                Ok(_) => return Ok(()),
+                Err(json_str) => serde_json::from_str(&json_str).unwrap(),
+            };
+
+            if !err.kind.is_transient() {
+                return Err(err);
+            } else if is_done() {
+                return Ok(());
+            } else if retry >= MAX_RETRIES || !reserve_retry(remote_name) {
+                return Err(err);
+            }
+
+            let delay = retry_delay(retry);
+            util::run_in_background(move || thread::sleep(delay));
+            retry += 1;
+        }
+    }
+
+    /// Check if an item doesn't exist on a remote.
+    fn is_gone(remote_name: &str, path: &str) -> bool {
+        matches!(stat(remote_name, path), Ok(None))
+    }
+
+    /// Check if an item on a remote has been moved from `src_path` to
+    /// `dst_path`.
+    fn has_moved(remote_name: &str, src_path: &str, dst_path: &str) -> bool {
+        is_gone(remote_name, src_path) && matches!(stat(remote_name, dst_path), Ok(Some(_)))
+    }
+
     /// Common function for some of the below command.
     fn common(command: &str, remote_name: &str, path: &str) -> Result<(), RcloneError> {
-        let resp = run(
+        run_with_retries(
+            remote_name,
             command,
             &json!({
                 "fs": get_remote_name(remote_name),
                 "remote": util::strip_slashes(path),
             })
             .to_string(),
-        );
-
-        match resp {
-            Ok(_) => Ok(()),
-            Err(json_str) => Err(serde_json::from_str(&json_str).unwrap()),
-        }
+        )
+        .map(|_| ())
     }
 
     /// Delete a config.
     pub fn delete_config(remote_name: &str) -> Result<(), RcloneError> {
+        super::delete_secrets(remote_name);
         let resp = run("config/delete", &json!({ "name": remote_name }).to_string());
 
         match resp {
@@ -274,21 +1006,33 @@ pub mod sync {
         }
     }
 
+    /// Get the optional features a remote supports.
+    pub fn features(remote_name: &str) -> Result<RcloneFeatures, RcloneError> {
+        let resp = run_with_retries(
+            remote_name,
+            "operations/fsinfo",
+            &json!({ "fs": get_remote_name(remote_name) }).to_string(),
+        )?;
+
+        Ok(serde_json::from_str::<RcloneFsInfo>(&resp)
+            .unwrap()
+            .features)
+    }
+
     /// Get statistics about a file or folder.
     pub fn stat(remote_name: &str, path: &str) -> Result<Option<RcloneRemoteItem>, RcloneError> {
-        let resp = run(
+        let resp = run_with_retries(
+            remote_name,
             "operations/stat",
             &json!({
                 "fs": get_remote_name(remote_name),
-                "remote": util::strip_slashes(path)
+                "remote": util::strip_slashes(path),
+                "opt": { "metadata": true }
             })
             .to_string(),
-        );
+        )?;
 
-        match resp {
-            Ok(json_str) => Ok(serde_json::from_str::<RcloneStat>(&json_str).unwrap().item),
-            Err(json_str) => Err(serde_json::from_str(&json_str).unwrap()),
-        }
+        Ok(serde_json::from_str::<RcloneStat>(&resp).unwrap().item)
     }
 
     /// List the files/folders in a path.
@@ -297,27 +1041,54 @@ pub mod sync {
         path: &str,
         recursive: bool,
         filter: RcloneListFilter,
+    ) -> Result<Vec<RcloneRemoteItem>, RcloneError> {
+        list_filtered(
+            remote_name,
+            path,
+            recursive,
+            filter,
+            &RcloneItemFilter::default(),
+        )
+    }
+
+    /// List the files/folders in a path, leaving out any files that don't pass
+    /// `item_filter`. Directories are always returned.
+    pub fn list_filtered(
+        remote_name: &str,
+        path: &str,
+        recursive: bool,
+        filter: RcloneListFilter,
+        item_filter: &RcloneItemFilter,
     ) -> Result<Vec<RcloneRemoteItem>, RcloneError> {
         let opts = match filter {
-            RcloneListFilter::All => json!({ "recurse": recursive }),
+            RcloneListFilter::All => json!({ "recurse": recursive, "metadata": true }),
             RcloneListFilter::Dirs => json!({"dirsOnly": true, "recurse": recursive}),
-            RcloneListFilter::Files => json!({"filesOnly": true, "recurse": recursive}),
+            RcloneListFilter::Files => {
+                json!({"filesOnly": true, "recurse": recursive, "metadata": true})
+            }
         };
+        let mut input = json!({
+            "fs": get_remote_name(remote_name),
+            "remote": util::strip_slashes(path),
+            "opt": opts
+        });
 
-        let resp = run(
-            "operations/list",
-            &json!({
-                "fs": get_remote_name(remote_name),
-                "remote": util::strip_slashes(path),
-                "opt": opts
-            })
-            .to_string(),
-        );
-
-        match resp {
-            Ok(json_str) => Ok(serde_json::from_str::<RcloneList>(&json_str).unwrap().list),
-            Err(json_str) => Err(serde_json::from_str(&json_str).unwrap()),
+        // Rclone only applies size and age filters to files, so these don't affect
+        // which directories get returned.
+        let mut filter_opts = serde_json::Map::new();
+        if let Some(max_size) = item_filter.max_size {
+            filter_opts.insert("MaxSize".to_owned(), json!(format!("{max_size}B")));
+        }
+        if let Some(max_age) = item_filter.max_age {
+            filter_opts.insert("MaxAge".to_owned(), json!(format!("{max_age}s")));
         }
+        if !filter_opts.is_empty() {
+            input["_filter"] = filter_opts.into();
+        }
+
+        let resp = run_with_retries(remote_name, "operations/list", &input.to_string())?;
+
+        Ok(serde_json::from_str::<RcloneList>(&resp).unwrap().list)
     }
 
     /// make a directory on the remote.
@@ -327,62 +1098,302 @@ pub mod sync {
 
     /// Delete a file.
     pub fn delete(remote_name: &str, path: &str) -> Result<(), RcloneError> {
-        common("operations/delete", remote_name, path)
+        run_change(
+            remote_name,
+            "operations/delete",
+            &json!({
+                "fs": get_remote_name(remote_name),
+                "remote": util::strip_slashes(path),
+            })
+            .to_string(),
+            || is_gone(remote_name, path),
+        )
     }
     /// Remove a directory and all of its contents.
     pub fn purge(remote_name: &str, path: &str) -> Result<(), RcloneError> {
-        common("operations/purge", remote_name, path)
+        run_change(
+            remote_name,
+            "operations/purge",
+            &json!({
+                "fs": get_remote_name(remote_name),
+                "remote": util::strip_slashes(path),
+            })
+            .to_string(),
+            || is_gone(remote_name, path),
+        )
+    }
+
+    /// Move a file to a new path on the same remote. This happens on the server
+    /// where the remote supports it, so the file doesn't have to be uploaded
+    /// again.
+    pub fn move_file(remote_name: &str, src_path: &str, dst_path: &str) -> Result<(), RcloneError> {
+        let remote = get_remote_name(remote_name);
+        run_change(
+            remote_name,
+            "operations/movefile",
+            &json!({
+                "srcFs": remote,
+                "srcRemote": util::strip_slashes(src_path),
+                "dstFs": remote,
+                "dstRemote": util::strip_slashes(dst_path),
+            })
+            .to_string(),
+            || has_moved(remote_name, src_path, dst_path),
+        )
     }
 
-    /// Utility for copy functions.
+    /// Move a directory and all of its contents to a new path on the same
+    /// remote. Like [`move_file`], this happens on the server where the remote
+    /// supports it.
+    pub fn move_dir(remote_name: &str, src_path: &str, dst_path: &str) -> Result<(), RcloneError> {
+        let remote = get_remote_name(remote_name);
+        run_change(
+            remote_name,
+            "sync/move",
+            &json!({
+                "srcFs": format!("{remote}{}", util::strip_slashes(src_path)),
+                "dstFs": format!("{remote}{}", util::strip_slashes(dst_path)),
+                "deleteEmptySrcDirs": true,
+            })
+            .to_string(),
+            || has_moved(remote_name, src_path, dst_path),
+        )
+    }
+
+    /// Utility for copy functions. `config` gets passed along as the command's
+    /// `_config` options.
     fn copy(
+        remote_name: &str,
         src_fs: &str,
         src_remote: &str,
         dst_fs: &str,
         dst_remote: &str,
+        config: serde_json::Value,
     ) -> Result<(), RcloneError> {
-        let resp = run(
+        run_with_retries(
+            remote_name,
             "operations/copyfile",
             &json!({
                 "srcFs": src_fs,
                 "srcRemote": util::strip_slashes(src_remote),
                 "dstFs": dst_fs,
-                "dstRemote": util::strip_slashes(dst_remote)
+                "dstRemote": util::strip_slashes(dst_remote),
+                "_config": config
             })
             .to_string(),
-        );
-
-        match resp {
-            Ok(_) => Ok(()),
-            Err(json_str) => Err(serde_json::from_str(&json_str).unwrap()),
-        }
+        )
+        .map(|_| ())
     }
 
-    /// Copy a file from the local machine to the remote.
+    /// Copy a file from the local machine to the remote. The file's POSIX mode
+    /// (i.e. the executable bit) gets stored in the remote file's metadata on
+    /// remotes that support it, so that [`copy_to_local`] can restore it.
     pub fn copy_to_remote(
         local_file: &str,
         remote_name: &str,
         remote_destination: &str,
     ) -> Result<(), RcloneError> {
+        // Rclone only applies `MetadataSet` when `Metadata` is turned on, but
+        // that would also copy the local file's owner and timestamps along, so
+        // only the mode gets stored by hand.
+        let config = match fs::metadata(local_file) {
+            Ok(metadata) => json!({
+                "Metadata": true,
+                "MetadataSet": { MODE_METADATA_KEY: format!("{:o}", metadata.mode()) }
+            }),
+            Err(_) => json!({}),
+        };
+
         copy(
+            remote_name,
             "/",
             local_file,
             &get_remote_name(remote_name),
             remote_destination,
+            config,
         )
     }
 
-    /// Copy a file from the remote to the local machine.
+    /// Copy a file from the remote to the local machine. `mode` should be the
+    /// mode stored by [`copy_to_remote`] (see [`RcloneRemoteItem::mode`]), and
+    /// gets applied to the local file if there is one.
+    ///
+    /// Rclone's own metadata handling isn't used here, as the local backend
+    /// would then also try to set the file's owner, which fails for files
+    /// uploaded by other users.
     pub fn copy_to_local(
         local_destination: &str,
         remote_name: &str,
         remote_file: &str,
+        mode: Option<u32>,
     ) -> Result<(), RcloneError> {
         copy(
+            remote_name,
             &get_remote_name(remote_name),
             remote_file,
             "/",
             local_destination,
+            json!({}),
+        )?;
+
+        if let Some(mode) = mode {
+            fs::set_permissions(local_destination, Permissions::from_mode(mode)).map_err(
+                |err| RcloneError {
+                    kind: RcloneErrorKind::Other,
+                    error: err.to_string(),
+                    status: 0,
+                },
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Copy a symbolic link from the local machine to the remote, where it's
+    /// stored as a file containing the link's target. `remote_destination`
+    /// should end in [`LINK_SUFFIX`].
+    pub fn copy_link_to_remote(
+        local_link: &str,
+        remote_name: &str,
+        remote_destination: &str,
+    ) -> Result<(), RcloneError> {
+        copy(
+            remote_name,
+            LOCAL_LINKS_FS,
+            &format!("{local_link}{LINK_SUFFIX}"),
+            &get_remote_name(remote_name),
+            remote_destination,
+            json!({}),
+        )
+    }
+
+    /// Copy a symbolic link stored with [`copy_link_to_remote`] from the
+    /// remote, recreating it as a link on the local machine.
+    pub fn copy_link_to_local(
+        local_destination: &str,
+        remote_name: &str,
+        remote_file: &str,
+    ) -> Result<(), RcloneError> {
+        copy(
+            remote_name,
+            &get_remote_name(remote_name),
+            remote_file,
+            LOCAL_LINKS_FS,
+            &format!("{local_destination}{LINK_SUFFIX}"),
+            json!({}),
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::RcloneErrorKind;
+
+    /// Find the kind of an error returned from the remote, which Rclone's
+    /// remote control API always returns with a 500.
+    fn kind(error: &str) -> RcloneErrorKind {
+        RcloneErrorKind::from_error(500, error)
+    }
+
+    #[test]
+    fn google_errors() {
+        assert_eq!(
+            kind("googleapi: Error 401: Invalid Credentials, authError"),
+            RcloneErrorKind::Auth
+        );
+        assert_eq!(
+            kind("googleapi: Error 403: The user does not have sufficient permissions for this file., insufficientFilePermissions"),
+            RcloneErrorKind::PermissionDenied
+        );
+        assert_eq!(
+            kind("googleapi: Error 403: The user's Drive storage quota has been exceeded., storageQuotaExceeded"),
+            RcloneErrorKind::QuotaExceeded
+        );
+        assert_eq!(
+            kind("googleapi: Error 403: User Rate Limit Exceeded. Rate of requests for user exceed configured project quota., userRateLimitExceeded"),
+            RcloneErrorKind::RateLimited
+        );
+        assert_eq!(
+            kind(
+                r#"Get "https://www.googleapis.com/drive/v3/files?alt=json": couldn't fetch token - maybe it has expired? - refresh with "rclone config reconnect gdrive:": oauth2: cannot fetch token: 400 Bad Request"#
+            ),
+            RcloneErrorKind::Auth
+        );
+    }
+
+    #[test]
+    fn dropbox_errors() {
+        assert_eq!(
+            kind("error reading source directory: path/not_found/.."),
+            RcloneErrorKind::NotFound
+        );
+        assert_eq!(kind("path_lookup/not_found/..."), RcloneErrorKind::NotFound);
+        assert_eq!(
+            kind("upload failed: too_many_requests/.."),
+            RcloneErrorKind::RateLimited
+        );
+        assert_eq!(
+            kind("too_many_write_operations/."),
+            RcloneErrorKind::RateLimited
+        );
+        assert_eq!(kind("expired_access_token/.."), RcloneErrorKind::Auth);
+        assert_eq!(
+            kind("upload failed: path/insufficient_space/.."),
+            RcloneErrorKind::QuotaExceeded
+        );
+        assert_eq!(
+            kind("path/no_write_permission/.."),
+            RcloneErrorKind::PermissionDenied
+        );
+    }
+
+    #[test]
+    fn webdav_errors() {
+        assert_eq!(kind("401 Unauthorized: "), RcloneErrorKind::Auth);
+        assert_eq!(
+            kind("Insufficient Storage: 507 Insufficient Storage"),
+            RcloneErrorKind::QuotaExceeded
+        );
+        assert_eq!(
+            kind("HTTP error 429 (429 Too Many Requests) returned body: \"\""),
+            RcloneErrorKind::RateLimited
+        );
+    }
+
+    #[test]
+    fn network_errors() {
+        assert_eq!(
+            kind(
+                r#"Post "https://api.dropboxapi.com/2/files/list_folder": dial tcp: lookup api.dropboxapi.com: no such host"#
+            ),
+            RcloneErrorKind::Network
+        );
+        assert_eq!(
+            kind(
+                r#"Propfind "https://cloud.example.com/remote.php/webdav/Documents/": dial tcp 192.0.2.1:443: i/o timeout"#
+            ),
+            RcloneErrorKind::Network
+        );
+        assert!(RcloneErrorKind::Network.is_transient());
+    }
+
+    #[test]
+    fn names_are_ignored() {
+        assert_eq!(
+            kind("failed to open source object: open /home/user/forbidden/notes.txt: no such file or directory"),
+            RcloneErrorKind::NotFound
+        );
+        assert_eq!(
+            kind(r#"can't copy "Quota exceeded.txt": unexpected end of file"#),
+            RcloneErrorKind::Other
+        );
+    }
+
+    #[test]
+    fn not_found_status() {
+        assert_eq!(
+            RcloneErrorKind::from_error(404, "object not found"),
+            RcloneErrorKind::NotFound
+        );
+    }
+}
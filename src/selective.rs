@@ -0,0 +1,222 @@
+//! Selective sync, which lets the user choose which remote folders in a sync
+//! directory get downloaded. Folders that are unselected are never downloaded,
+//! though anything newly created in them locally still gets uploaded. Items in
+//! them that were already synced are left alone until the folder is selected
+//! again.
+use crate::{
+    entities::{
+        SyncDirsModel, UnselectedFoldersActiveModel, UnselectedFoldersColumn,
+        UnselectedFoldersEntity,
+    },
+    gtk_util,
+    rclone::{self, RcloneListFilter},
+    util,
+};
+use adw::{
+    glib,
+    gtk::{Align, CheckButton, Label, ListBox, ScrolledWindow, SelectionMode},
+    prelude::*,
+    ExpanderRow, MessageDialog,
+};
+use sea_orm::{entity::prelude::*, ActiveValue, DatabaseConnection};
+use std::{cell::Cell, rc::Rc};
+
+/// The folders in a sync directory that have been unselected.
+#[derive(Clone, Debug, Default)]
+pub struct Selection {
+    /// The unselected folders, relative to the sync directory's remote path.
+    folders: Vec<String>,
+}
+
+impl Selection {
+    /// Load the unselected folders for a sync directory.
+    pub fn load(db: &DatabaseConnection, sync_dir_id: i32) -> Self {
+        let folders = util::await_future(
+            UnselectedFoldersEntity::find()
+                .filter(UnselectedFoldersColumn::SyncDirId.eq(sync_dir_id))
+                .all(db),
+        )
+        .unwrap()
+        .into_iter()
+        .map(|folder| folder.remote_path)
+        .collect();
+
+        Self { folders }
+    }
+
+    /// Check if a path is an unselected folder, or is inside of one. `path` is
+    /// relative to the sync directory's remote path.
+    pub fn is_unselected(&self, path: &str) -> bool {
+        self.folders
+            .iter()
+            .any(|folder| path == folder || path.starts_with(&format!("{folder}/")))
+    }
+}
+
+/// Select or unselect a folder in a sync directory. `folder` is relative to
+/// the sync directory's remote path.
+pub fn set_selected(
+    db: &DatabaseConnection,
+    sync_dir: &SyncDirsModel,
+    folder: &str,
+    selected: bool,
+) {
+    let folder = util::strip_slashes(folder);
+
+    util::await_future(async {
+        UnselectedFoldersEntity::delete_many()
+            .filter(UnselectedFoldersColumn::SyncDirId.eq(sync_dir.id))
+            .filter(UnselectedFoldersColumn::RemotePath.eq(folder.clone()))
+            .exec(db)
+            .await
+            .unwrap();
+
+        if selected {
+            return;
+        }
+
+        UnselectedFoldersActiveModel {
+            sync_dir_id: ActiveValue::Set(sync_dir.id),
+            remote_path: ActiveValue::Set(folder.clone()),
+            ..Default::default()
+        }
+        .insert(db)
+        .await
+        .unwrap();
+    });
+}
+
+/// Generate the row for a remote folder, whose subfolders get listed once the
+/// row is expanded. `path` is the full path of the folder on the remote.
+fn folder_row(
+    db: &DatabaseConnection,
+    sync_dir: &SyncDirsModel,
+    selection: &Rc<Selection>,
+    rclone_remote: &str,
+    path: &str,
+    name: &str,
+) -> ExpanderRow {
+    let relative_path =
+        util::strip_slashes(path.strip_prefix(&sync_dir.remote_path).unwrap_or(path));
+    let row = ExpanderRow::builder().title(name).build();
+    let check = CheckButton::builder()
+        .active(!selection.folders.contains(&relative_path))
+        .valign(Align::Center)
+        .build();
+    check.connect_toggled(
+        glib::clone!(@strong db, @strong sync_dir, @strong relative_path => move |check| {
+            set_selected(&db, &sync_dir, &relative_path, check.is_active());
+        }),
+    );
+    row.add_prefix(&check);
+
+    let loaded = Rc::new(Cell::new(false));
+    let path = path.to_owned();
+    let rclone_remote = rclone_remote.to_owned();
+    row.connect_expanded_notify(
+        glib::clone!(@strong db, @strong sync_dir, @strong selection, @strong loaded, @weak check => move |row| {
+            if !row.is_expanded() || loaded.get() {
+                return;
+            }
+            loaded.set(true);
+
+            let items = match rclone::sync::list(&rclone_remote, &path, false, RcloneListFilter::Dirs) {
+                Ok(items) => items,
+                Err(err) => {
+                    loaded.set(false);
+                    gtk_util::show_error(&tr::tr!("Unable to list folders in '/{}'", path), Some(&err.error));
+                    return;
+                }
+            };
+
+            if items.is_empty() {
+                row.add_row(
+                    &Label::builder()
+                        .label(&tr::tr!("No subfolders"))
+                        .margin_top(10)
+                        .margin_bottom(10)
+                        .css_classes(vec!["dim-label".to_string()])
+                        .build(),
+                );
+            }
+
+            for item in items {
+                let child = folder_row(&db, &sync_dir, &selection, &rclone_remote, &item.path, &item.name);
+                // Subfolders of an unselected folder are never downloaded either.
+                check.bind_property("active", &child, "sensitive").sync_create().build();
+                row.add_row(&child);
+            }
+        }),
+    );
+
+    row
+}
+
+/// Show a dialog for choosing which remote folders in a sync directory get
+/// downloaded. `remote_name` is the name of the sync directory's remote.
+pub fn dialog(db: &DatabaseConnection, sync_dir: &SyncDirsModel, remote_name: &str) {
+    let rclone_remote = sync_dir.rclone_remote(remote_name);
+    let items = match rclone::sync::list(
+        &rclone_remote,
+        &sync_dir.remote_path,
+        false,
+        RcloneListFilter::Dirs,
+    ) {
+        Ok(items) => items,
+        Err(err) => {
+            gtk_util::show_error(
+                &tr::tr!("Unable to list folders in '/{}'", sync_dir.remote_path),
+                Some(&err.error),
+            );
+            return;
+        }
+    };
+    let selection = Rc::new(Selection::load(db, sync_dir.id));
+
+    let list = ListBox::builder()
+        .selection_mode(SelectionMode::None)
+        .css_classes(vec!["boxed-list".to_string()])
+        .valign(Align::Start)
+        .margin_top(5)
+        .margin_end(5)
+        .margin_bottom(5)
+        .margin_start(5)
+        .build();
+    list.set_placeholder(Some(
+        &Label::builder()
+            .label(&tr::tr!("No subfolders"))
+            .margin_top(10)
+            .margin_bottom(10)
+            .css_classes(vec!["dim-label".to_string()])
+            .build(),
+    ));
+    for item in items {
+        list.append(&folder_row(
+            db,
+            sync_dir,
+            &selection,
+            &rclone_remote,
+            &item.path,
+            &item.name,
+        ));
+    }
+    let list_scrolled = ScrolledWindow::builder()
+        .child(&list)
+        .min_content_height(300)
+        .vexpand_set(true)
+        .vexpand(true)
+        .build();
+
+    let dialog = MessageDialog::builder()
+        .heading(&tr::tr!("Choose Folders to Sync"))
+        .body(&tr::tr!(
+            "Unchecked folders won't be downloaded, though anything created in them locally will still be uploaded."
+        ))
+        .extra_child(&list_scrolled)
+        .modal(true)
+        .resizable(true)
+        .build();
+    dialog.add_response("close", &tr::tr!("Close"));
+    dialog.connect_response(None, |dialog, _| dialog.close());
+    dialog.show();
+}
@@ -0,0 +1,134 @@
+//! The sync status of the running instance. This gets written out to a file in
+//! Celeste's config directory, so the CLI can report on it.
+use crate::util;
+use serde::{Deserialize, Serialize};
+use std::{fs, io::Write, path::PathBuf};
+use tempfile::NamedTempFile;
+use time::OffsetDateTime;
+
+/// The state of a sync directory.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DirState {
+    /// The directory hasn't been checked for changes yet.
+    Waiting,
+    /// The directory is currently being checked for changes and synced.
+    Syncing,
+    /// The directory is synced, though there may still be errors that need
+    /// resolving.
+    Synced,
+    /// Syncing of the directory has been paused.
+    Paused,
+    /// The directory's remote needs to be signed in to again.
+    NeedsSignIn,
+    /// The directory's remote can't currently be reached.
+    Disconnected,
+}
+
+impl DirState {
+    /// Get the name of the icon used to show this state in the UI. Returns
+    /// [`None`] for [`DirState::Syncing`], which gets shown with a spinner
+    /// instead.
+    pub fn icon_name(&self, has_errors: bool) -> Option<&'static str> {
+        match self {
+            Self::Waiting => Some("content-loading-symbolic"),
+            Self::Syncing => None,
+            Self::Synced if has_errors => Some("dialog-warning-symbolic"),
+            Self::Synced => Some("object-select-symbolic"),
+            Self::Paused => Some("media-playback-pause-symbolic"),
+            Self::NeedsSignIn => Some("dialog-password-symbolic"),
+            Self::Disconnected => Some("network-offline-symbolic"),
+        }
+    }
+}
+
+/// The progress of a directory that's currently being synced.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Progress {
+    /// The directory currently being checked for changes.
+    pub current_path: String,
+    /// How many files and directories have been checked so far.
+    pub items_checked: usize,
+}
+
+/// The status of a sync directory.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct DirStatus {
+    /// The local directory being synced.
+    pub local_path: String,
+    /// The remote directory being synced.
+    pub remote_path: String,
+    /// The current state of the directory.
+    pub state: DirState,
+    /// The current status message (things like 'Awaiting sync check...').
+    pub status: String,
+    /// When the directory was last synced, if it has been since Celeste was
+    /// started.
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub last_sync: Option<OffsetDateTime>,
+    /// The number of errors that need resolving.
+    pub error_count: usize,
+    /// The messages for any errors found while syncing.
+    pub errors: Vec<String>,
+    /// The progress of the current sync, if one is running.
+    pub progress: Option<Progress>,
+}
+
+/// The state of a remote's connection.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RemoteConnection {
+    /// The remote can be synced.
+    Connected,
+    /// The remote's login details are no longer valid.
+    NeedsSignIn,
+    /// The remote can't currently be reached.
+    Disconnected,
+}
+
+/// The status of a remote.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct RemoteStatus {
+    /// The name of the remote.
+    pub name: String,
+    /// The state of the remote's connection.
+    pub connection: RemoteConnection,
+    /// The status of each of the remote's sync directories.
+    pub dirs: Vec<DirStatus>,
+}
+
+/// The status of everything being synced.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Status {
+    pub remotes: Vec<RemoteStatus>,
+}
+
+/// Get the path to the status file.
+fn status_path() -> PathBuf {
+    let mut path = util::get_config_dir();
+    path.push("status.json");
+    path
+}
+
+impl Status {
+    /// Write out this status for the CLI to read. The status gets written to a
+    /// temporary file first and then moved into place, so the CLI never reads
+    /// a partially written one.
+    pub fn write(&self) {
+        // This is only used for reporting, so there's nothing useful to do if it
+        // can't be written.
+        let path = status_path();
+        let _ = NamedTempFile::new_in(path.parent().unwrap()).and_then(|mut file| {
+            file.write_all(serde_json::to_string(self).unwrap().as_bytes())?;
+            file.persist(&path)?;
+            Ok(())
+        });
+    }
+
+    /// Read the status last written by the running instance. Returns [`None`]
+    /// if there isn't one.
+    pub fn read() -> Option<Self> {
+        let content = fs::read_to_string(status_path()).ok()?;
+        serde_json::from_str(&content).ok()
+    }
+}
@@ -0,0 +1,111 @@
+//! The handling of symbolic links in sync directories.
+use crate::{entities::SyncDirsModel, filters};
+use adw::{glib, gtk::StringList, prelude::*, ComboRow};
+use sea_orm::{ActiveValue, DatabaseConnection};
+use std::{fs, path::Path};
+
+/// How symbolic links in a sync directory are handled.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SymlinkPolicy {
+    /// Links are left alone, and files on the remote that were stored as links
+    /// (see [`crate::rclone::LINK_SUFFIX`]) are never downloaded.
+    #[default]
+    Skip,
+    /// Links are synced as the files and folders they point to. Links to
+    /// folders that would loop back to one of their parent folders are
+    /// skipped.
+    Follow,
+    /// Links are stored on the remote as files containing their target, and
+    /// recreated as links when downloaded.
+    Links,
+}
+
+impl SymlinkPolicy {
+    /// Every policy, in the order they're shown in the UI.
+    pub const ALL: [Self; 3] = [Self::Skip, Self::Follow, Self::Links];
+
+    /// Get the policy set on a sync directory.
+    pub fn from_sync_dir(sync_dir: &SyncDirsModel) -> Self {
+        Self::from_setting(&sync_dir.symlinks)
+    }
+
+    /// Get a policy from how it's stored in the database. Unknown values are
+    /// treated as [`SymlinkPolicy::Skip`].
+    pub fn from_setting(setting: &str) -> Self {
+        match setting {
+            "follow" => Self::Follow,
+            "links" => Self::Links,
+            _ => Self::Skip,
+        }
+    }
+
+    /// Get how this policy is stored in the database.
+    pub fn setting(self) -> &'static str {
+        match self {
+            Self::Skip => "skip",
+            Self::Follow => "follow",
+            Self::Links => "links",
+        }
+    }
+
+    /// Get the name of this policy to show in the UI.
+    pub fn label(self) -> String {
+        match self {
+            Self::Skip => tr::tr!("Skip"),
+            Self::Follow => tr::tr!("Follow"),
+            Self::Links => tr::tr!("Store as links"),
+        }
+    }
+}
+
+/// Check if following the symbolic link at `path` would loop back to the
+/// folder it's in, or to any folder above that. This is checked against the
+/// resolved path of each parent, so loops that go through other links are
+/// caught as well.
+pub fn creates_loop(path: &Path) -> bool {
+    let target = match fs::canonicalize(path) {
+        Ok(target) => target,
+        Err(_) => return false,
+    };
+
+    path.ancestors()
+        .skip(1)
+        .filter_map(|ancestor| fs::canonicalize(ancestor).ok())
+        .any(|ancestor| ancestor.starts_with(&target))
+}
+
+/// Generate the row for choosing a sync directory's symlink policy. Changes are
+/// saved straight away, and get picked up on the next sync pass.
+pub fn row(
+    db: &DatabaseConnection,
+    local_path: &str,
+    remote_path: &str,
+    policy: SymlinkPolicy,
+) -> ComboRow {
+    let labels: Vec<String> = SymlinkPolicy::ALL
+        .iter()
+        .map(|policy| policy.label())
+        .collect();
+    let model = StringList::new(&labels.iter().map(String::as_str).collect::<Vec<_>>());
+    let row = ComboRow::builder()
+        .title(&tr::tr!("Symbolic links"))
+        .model(&model)
+        .selected(
+            SymlinkPolicy::ALL
+                .iter()
+                .position(|item| *item == policy)
+                .unwrap() as u32,
+        )
+        .build();
+
+    let local_path = local_path.to_owned();
+    let remote_path = remote_path.to_owned();
+    row.connect_selected_notify(glib::clone!(@strong db => move |row| {
+        let policy = SymlinkPolicy::ALL[row.selected() as usize];
+        filters::update_sync_dir(&db, &local_path, &remote_path, |sync_dir| {
+            sync_dir.symlinks = ActiveValue::Set(policy.setting().to_owned());
+        });
+    }));
+
+    row
+}
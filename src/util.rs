@@ -1,6 +1,19 @@
+use crate::{
+    entities::{
+        ActivityColumn, ActivityEntity, SyncDirsModel, SyncErrorsColumn, SyncErrorsEntity,
+        SyncItemsColumn, SyncItemsEntity, UnselectedFoldersColumn, UnselectedFoldersEntity,
+    },
+    migrations::{Migrator, MigratorTrait},
+    rclone, versions,
+};
 use adw::glib::{self, MainContext};
 use futures::future::Future;
-use std::path::PathBuf;
+use sea_orm::{
+    entity::prelude::*,
+    sea_query::{Expr, SimpleExpr},
+    Database, DatabaseConnection, IdenStatic,
+};
+use std::{fs, path::PathBuf};
 
 /// The ID of the app.
 pub static APP_ID: &str = "com.hunterwittenborn.Celeste";
@@ -32,6 +45,44 @@ pub fn get_config_dir() -> PathBuf {
     config_dir
 }
 
+/// Open Celeste's database, creating it and running any pending migrations as
+/// needed. Returns a message describing the problem if any of that fails.
+pub fn open_db() -> Result<DatabaseConnection, String> {
+    // Create the configuration directory if it doesn't exist.
+    let config_path = get_config_dir();
+    if !config_path.exists()
+        && let Err(err) = fs::create_dir_all(&config_path)
+    {
+        return Err(tr::tr!(
+            "Unable to create Celeste's config directory [{}].",
+            err
+        ));
+    }
+
+    // Create the database file if it doesn't exist.
+    let mut db_path = config_path;
+    db_path.push("celeste.db");
+    if !db_path.exists()
+        && let Err(err) = fs::File::create(&db_path)
+    {
+        return Err(tr::tr!(
+            "Unable to create Celeste's database file [{}].",
+            err
+        ));
+    }
+
+    // Connect to the database.
+    let db = await_future(Database::connect(format!("sqlite://{}", db_path.display())))
+        .map_err(|err| tr::tr!("Unable to connect to database [{}].", err))?;
+
+    // Run migrations.
+    if let Err(err) = await_future(Migrator::up(&db, None)) {
+        return Err(tr::tr!("Unable to run database migrations [{}]", err));
+    }
+
+    Ok(db)
+}
+
 /// Strip the slashes from the beginning and end of a string.
 pub fn strip_slashes(string: &str) -> String {
     let stripped_prefix = match string.strip_prefix('/') {
@@ -45,6 +96,16 @@ pub fn strip_slashes(string: &str) -> String {
     }
 }
 
+/// Get a database condition for `column` starting with `prefix`. SeaORM's
+/// `starts_with` becomes a `LIKE` pattern, which ignores case and treats '_'
+/// and '%' as wildcards, so paths are compared exactly here instead.
+pub fn starts_with<C: ColumnTrait>(column: C, prefix: &str) -> SimpleExpr {
+    Expr::cust_with_values(
+        &format!("substr(\"{}\", 1, length(?)) = ?", column.as_str()),
+        [prefix, prefix],
+    )
+}
+
 /// Macro to get the title of a window.
 #[macro_export]
 macro_rules! get_title {
@@ -54,3 +115,38 @@ macro_rules! get_title {
 }
 
 pub use crate::get_title;
+
+/// Delete a sync directory from the database, along with everything stored for
+/// it and the crypt remote used to encrypt it (if there is one).
+pub fn delete_sync_dir(db: &DatabaseConnection, sync_dir: SyncDirsModel) {
+    let crypt_remote = sync_dir.crypt_remote.clone();
+
+    await_future(async {
+        SyncItemsEntity::delete_many()
+            .filter(SyncItemsColumn::SyncDirId.eq(sync_dir.id))
+            .exec(db)
+            .await
+            .unwrap();
+        SyncErrorsEntity::delete_many()
+            .filter(SyncErrorsColumn::SyncDirId.eq(sync_dir.id))
+            .exec(db)
+            .await
+            .unwrap();
+        ActivityEntity::delete_many()
+            .filter(ActivityColumn::SyncDirId.eq(sync_dir.id))
+            .exec(db)
+            .await
+            .unwrap();
+        versions::delete_for_sync_dir(db, sync_dir.id).await;
+        UnselectedFoldersEntity::delete_many()
+            .filter(UnselectedFoldersColumn::SyncDirId.eq(sync_dir.id))
+            .exec(db)
+            .await
+            .unwrap();
+        sync_dir.delete(db).await.unwrap();
+    });
+
+    if let Some(crypt_remote) = crypt_remote {
+        rclone::sync::delete_config(&crypt_remote).unwrap();
+    }
+}
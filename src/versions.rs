@@ -0,0 +1,267 @@
+//! The local version history, which keeps copies of local files before they
+//! get overwritten by changes from the remote.
+use crate::{
+    entities::{
+        FileVersionsActiveModel, FileVersionsColumn, FileVersionsEntity, FileVersionsModel,
+        SyncDirsEntity, SyncDirsModel,
+    },
+    gtk_util, util,
+};
+use adw::{
+    glib,
+    gtk::{pango::EllipsizeMode, Align, Box, Button, Label, ListBox, Orientation, SelectionMode},
+    prelude::*,
+    MessageDialog,
+};
+use sea_orm::{entity::prelude::*, ActiveValue, DatabaseConnection, QueryOrder};
+use std::{collections::HashSet, fs, path::PathBuf};
+use time::OffsetDateTime;
+
+/// The most versions to keep of a single file.
+const MAX_VERSIONS: usize = 10;
+
+/// The most space all stored versions can take up together, in bytes. The
+/// oldest versions get removed first once this is exceeded.
+const MAX_STORE_SIZE: u64 = 1024 * 1024 * 1024;
+
+/// Get the directory versions are stored in.
+fn store_dir() -> PathBuf {
+    let mut path = glib::user_data_dir();
+    path.push("celeste");
+    path.push("versions");
+    path
+}
+
+/// Get the path a version's content is stored at.
+fn version_path(version: &FileVersionsModel) -> PathBuf {
+    let mut path = store_dir();
+    path.push(version.id.to_string());
+    path
+}
+
+/// Delete a version, along with its stored content.
+async fn delete_version(db: &DatabaseConnection, version: FileVersionsModel) {
+    // The content may already be gone if the user cleared it out themselves, which
+    // is fine.
+    let _ = fs::remove_file(version_path(&version));
+    version.delete(db).await.unwrap();
+}
+
+/// Save the current content of a local file as a version, so that it can be
+/// restored after it gets overwritten. Nothing is saved if the file doesn't
+/// exist, or if it's too big to be kept in the store.
+pub fn save(
+    db: &DatabaseConnection,
+    sync_dir: &SyncDirsModel,
+    local_path: &str,
+) -> Result<(), String> {
+    let metadata = match fs::symlink_metadata(local_path) {
+        Ok(metadata) if metadata.is_file() => metadata,
+        _ => return Ok(()),
+    };
+    if metadata.len() > MAX_STORE_SIZE {
+        return Ok(());
+    }
+
+    let store_path = store_dir();
+    if !store_path.exists() {
+        fs::create_dir_all(&store_path).map_err(|err| {
+            tr::tr!(
+                "Unable to create the directory for previous versions [{}].",
+                err
+            )
+        })?;
+    }
+
+    let version = util::await_future(
+        FileVersionsActiveModel {
+            sync_dir_id: ActiveValue::Set(sync_dir.id),
+            local_path: ActiveValue::Set(local_path.to_owned()),
+            size: ActiveValue::Set(metadata.len() as i64),
+            timestamp: ActiveValue::Set(OffsetDateTime::now_utc().unix_timestamp()),
+            ..Default::default()
+        }
+        .insert(db),
+    )
+    .unwrap();
+
+    if let Err(err) = fs::copy(local_path, version_path(&version)) {
+        util::await_future(delete_version(db, version));
+        return Err(tr::tr!(
+            "Unable to save the previous version of '{}' [{}].",
+            util::fmt_home(local_path),
+            err
+        ));
+    }
+
+    prune(db, local_path);
+    Ok(())
+}
+
+/// Remove old versions, so that no file has more than [`MAX_VERSIONS`] and the
+/// store doesn't go over [`MAX_STORE_SIZE`].
+fn prune(db: &DatabaseConnection, local_path: &str) {
+    util::await_future(async {
+        let file_versions = FileVersionsEntity::find()
+            .filter(FileVersionsColumn::LocalPath.eq(local_path))
+            .order_by_desc(FileVersionsColumn::Timestamp)
+            .order_by_desc(FileVersionsColumn::Id)
+            .all(db)
+            .await
+            .unwrap();
+        for version in file_versions.into_iter().skip(MAX_VERSIONS) {
+            delete_version(db, version).await;
+        }
+
+        let all_versions = FileVersionsEntity::find()
+            .order_by_asc(FileVersionsColumn::Timestamp)
+            .order_by_asc(FileVersionsColumn::Id)
+            .all(db)
+            .await
+            .unwrap();
+        let mut total_size: u64 = all_versions.iter().map(|version| version.size as u64).sum();
+        for version in all_versions {
+            if total_size <= MAX_STORE_SIZE {
+                break;
+            }
+
+            total_size -= version.size as u64;
+            delete_version(db, version).await;
+        }
+    });
+}
+
+/// Get the stored versions of a local file, newest first.
+pub fn list(db: &DatabaseConnection, local_path: &str) -> Vec<FileVersionsModel> {
+    util::await_future(
+        FileVersionsEntity::find()
+            .filter(FileVersionsColumn::LocalPath.eq(local_path))
+            .order_by_desc(FileVersionsColumn::Timestamp)
+            .order_by_desc(FileVersionsColumn::Id)
+            .all(db),
+    )
+    .unwrap()
+}
+
+/// Get every local file that has at least one stored version.
+pub fn paths_with_versions(db: &DatabaseConnection) -> HashSet<String> {
+    util::await_future(FileVersionsEntity::find().all(db))
+        .unwrap()
+        .into_iter()
+        .map(|version| version.local_path)
+        .collect()
+}
+
+/// Restore a version of a file. The file's current content gets saved as a
+/// version first, so the restore itself can be undone.
+pub fn restore(db: &DatabaseConnection, version: &FileVersionsModel) -> Result<(), String> {
+    let restore_err = |err: std::io::Error| {
+        tr::tr!(
+            "Unable to restore '{}' [{}].",
+            util::fmt_home(&version.local_path),
+            err
+        )
+    };
+
+    // Saving the current content can prune the version being restored, so take
+    // a copy of it out of the store first.
+    let mut restoring_path = store_dir();
+    restoring_path.push(format!("{}.restoring", version.id));
+    fs::copy(version_path(version), &restoring_path).map_err(restore_err)?;
+
+    if let Some(sync_dir) =
+        util::await_future(SyncDirsEntity::find_by_id(version.sync_dir_id).one(db)).unwrap()
+        && let Err(err) = save(db, &sync_dir, &version.local_path)
+    {
+        let _ = fs::remove_file(&restoring_path);
+        return Err(err);
+    }
+
+    // Copying the content in (instead of renaming it) gives the file a new
+    // modification time, so the restored version gets synced back to the remote.
+    let result = fs::copy(&restoring_path, &version.local_path)
+        .map(|_| ())
+        .map_err(restore_err);
+    let _ = fs::remove_file(&restoring_path);
+    result
+}
+
+/// Delete all the stored versions for a sync directory.
+pub async fn delete_for_sync_dir(db: &DatabaseConnection, sync_dir_id: i32) {
+    let versions = FileVersionsEntity::find()
+        .filter(FileVersionsColumn::SyncDirId.eq(sync_dir_id))
+        .all(db)
+        .await
+        .unwrap();
+
+    for version in versions {
+        delete_version(db, version).await;
+    }
+}
+
+/// Show a dialog listing the stored versions of a local file, from which one
+/// can be restored.
+pub fn dialog(db: &DatabaseConnection, local_path: &str) {
+    let version_list = ListBox::builder()
+        .selection_mode(SelectionMode::None)
+        .css_classes(vec!["boxed-list".to_string()])
+        .build();
+    version_list.set_placeholder(Some(
+        &Label::builder()
+            .label(&tr::tr!("No previous versions are available."))
+            .margin_top(10)
+            .margin_bottom(10)
+            .css_classes(vec!["dim-label".to_string()])
+            .build(),
+    ));
+    let dialog = MessageDialog::builder()
+        .heading(&tr::tr!("Restore Previous Version"))
+        .body(&util::fmt_home(local_path))
+        .extra_child(&version_list)
+        .modal(true)
+        .resizable(true)
+        .build();
+    dialog.add_response("close", &tr::tr!("Close"));
+
+    for version in list(db, local_path) {
+        let row = Box::builder()
+            .orientation(Orientation::Horizontal)
+            .spacing(10)
+            .margin_top(6)
+            .margin_end(6)
+            .margin_bottom(6)
+            .margin_start(6)
+            .build();
+        let timestamp = glib::DateTime::from_unix_local(version.timestamp)
+            .and_then(|datetime| datetime.format("%c"))
+            .map(|datetime| datetime.to_string())
+            .unwrap_or_else(|_| version.timestamp.to_string());
+        let label = Label::builder()
+            .label(&format!(
+                "{timestamp} · {}",
+                glib::format_size(version.size as u64)
+            ))
+            .halign(Align::Start)
+            .hexpand_set(true)
+            .hexpand(true)
+            .ellipsize(EllipsizeMode::End)
+            .build();
+        let restore_button = Button::builder()
+            .label(&tr::tr!("Restore"))
+            .valign(Align::Center)
+            .build();
+        restore_button.connect_clicked(glib::clone!(@strong db, @weak dialog => move |_| {
+            if let Err(err) = restore(&db, &version) {
+                gtk_util::show_error(&tr::tr!("Unable to restore previous version"), Some(&err));
+            }
+
+            dialog.close();
+        }));
+        row.append(&label);
+        row.append(&restore_button);
+        version_list.append(&row);
+    }
+
+    dialog.connect_response(None, |dialog, _| dialog.close());
+    dialog.show();
+}